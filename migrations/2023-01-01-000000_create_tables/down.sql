@@ -0,0 +1,3 @@
+DROP TABLE habit_entry;
+DROP TABLE habit;
+DROP TABLE user;
@@ -0,0 +1,32 @@
+CREATE TABLE IF NOT EXISTS user (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    name TEXT NOT NULL,
+    date_of_birth TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS habit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL REFERENCES user (id),
+    name TEXT NOT NULL,
+    description TEXT,
+    categories TEXT,
+    icon TEXT,
+    frequency TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT,
+    reminder_times TEXT,
+    note TEXT,
+    archived INTEGER NOT NULL DEFAULT 0,
+    archived_date TEXT,
+    archived_reason TEXT
+);
+
+CREATE TABLE IF NOT EXISTS habit_entry (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user_id INTEGER NOT NULL REFERENCES user (id),
+    habit_id INTEGER NOT NULL REFERENCES habit (id),
+    entry_time TEXT NOT NULL,
+    note TEXT,
+    value INTEGER NOT NULL
+);
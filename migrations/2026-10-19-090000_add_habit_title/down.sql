@@ -0,0 +1 @@
+ALTER TABLE habit DROP COLUMN title;
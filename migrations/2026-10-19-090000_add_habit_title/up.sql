@@ -0,0 +1,2 @@
+-- Display name of custom habits, which all share the `custom` name
+ALTER TABLE habit ADD COLUMN title TEXT;
@@ -0,0 +1,13 @@
+DROP TABLE sync_cursor;
+DROP TABLE sync_state;
+DROP TABLE sync_deletion;
+
+DROP INDEX habit_entry_uuid;
+DROP INDEX habit_uuid;
+DROP INDEX user_uuid;
+
+ALTER TABLE habit_entry DROP COLUMN updated_at;
+ALTER TABLE habit_entry DROP COLUMN uuid;
+ALTER TABLE habit DROP COLUMN uuid;
+ALTER TABLE user DROP COLUMN updated_at;
+ALTER TABLE user DROP COLUMN uuid;
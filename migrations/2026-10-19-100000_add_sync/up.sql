@@ -0,0 +1,40 @@
+-- Stable ids to identify records across devices
+ALTER TABLE user ADD COLUMN uuid TEXT;
+ALTER TABLE user ADD COLUMN updated_at TEXT;
+ALTER TABLE habit ADD COLUMN uuid TEXT;
+ALTER TABLE habit_entry ADD COLUMN uuid TEXT;
+ALTER TABLE habit_entry ADD COLUMN updated_at TEXT;
+
+-- Random version 4 UUIDs for the existing records
+UPDATE user SET uuid = lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)));
+UPDATE habit SET uuid = lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)));
+UPDATE habit_entry SET uuid = lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + (abs(random()) % 4), 1) ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)));
+
+CREATE UNIQUE INDEX user_uuid ON user (uuid);
+CREATE UNIQUE INDEX habit_uuid ON habit (uuid);
+CREATE UNIQUE INDEX habit_entry_uuid ON habit_entry (uuid);
+
+-- Deleted records, so that deletions can be synced too
+CREATE TABLE sync_deletion (
+    uuid TEXT PRIMARY KEY NOT NULL,
+    table_name TEXT NOT NULL,
+    deleted_at TEXT NOT NULL
+);
+
+-- Device id and time of the last export
+CREATE TABLE sync_state (
+    key TEXT PRIMARY KEY NOT NULL,
+    value TEXT NOT NULL
+);
+
+-- Number of lines already merged from the change log of each other device
+CREATE TABLE sync_cursor (
+    device_id TEXT PRIMARY KEY NOT NULL,
+    position INTEGER NOT NULL
+);
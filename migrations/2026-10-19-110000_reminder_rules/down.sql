@@ -0,0 +1,13 @@
+-- Rules can only be turned back into instants on some day, today will do.
+-- Rules are objects like `{"time":"07:30:00"}` or just their time, `"07:30"`.
+UPDATE habit
+SET reminder_rules = (
+    SELECT json_group_array(strftime('%Y-%m-%d', 'now', 'localtime') || 'T' || CASE type
+        WHEN 'text' THEN value
+        ELSE json_extract(value, '$.time')
+    END)
+    FROM json_each(habit.reminder_rules)
+)
+WHERE reminder_rules IS NOT NULL AND json_valid(reminder_rules);
+
+ALTER TABLE habit RENAME COLUMN reminder_rules TO reminder_times;
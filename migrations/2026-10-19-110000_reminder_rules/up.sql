@@ -0,0 +1,25 @@
+-- Reminders become rules at a time of day, e.g. `{"time":"07:30:00"}`, instead
+-- of absolute instants. The time of day is the local one of each instant, so
+-- instants with an offset are converted through it. SQLite only reads offsets
+-- as `+HH:MM`, glib writes whole hours as `+HH`. Instants without an offset
+-- are local already.
+ALTER TABLE habit RENAME COLUMN reminder_times TO reminder_rules;
+
+UPDATE habit
+SET reminder_rules = (
+    SELECT json_group_array(json_object('time', CASE
+        WHEN value LIKE '%Z'
+            OR instr(substr(value, instr(value, 'T')), '+') > 0
+            OR instr(substr(value, instr(value, 'T')), '-') > 0
+        THEN coalesce(
+            time(
+                CASE WHEN substr(value, -3, 1) IN ('+', '-') THEN value || ':00' ELSE value END,
+                'localtime'
+            ),
+            substr(value, instr(value, 'T') + 1, 8)
+        )
+        ELSE substr(value, instr(value, 'T') + 1, 8)
+    END))
+    FROM json_each(habit.reminder_rules)
+)
+WHERE reminder_rules IS NOT NULL AND json_valid(reminder_rules);
@@ -0,0 +1,6 @@
+DROP TRIGGER habit_strength_habit_delete;
+DROP TRIGGER habit_strength_habit_update;
+DROP TRIGGER habit_strength_entry_delete;
+DROP TRIGGER habit_strength_entry_update;
+DROP TRIGGER habit_strength_entry_insert;
+DROP TABLE habit_strength;
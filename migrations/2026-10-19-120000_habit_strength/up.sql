@@ -0,0 +1,39 @@
+-- Cached strength of each habit as of the end of `day`, the last day of its
+-- last closed period
+CREATE TABLE habit_strength (
+    habit_id INTEGER PRIMARY KEY NOT NULL REFERENCES habit (id) ON DELETE CASCADE,
+    day TEXT NOT NULL,
+    score DOUBLE NOT NULL
+);
+
+-- Entries changed on or before the cached day make the cache stale. Entry
+-- times start with their local date, like `day`.
+CREATE TRIGGER habit_strength_entry_insert AFTER INSERT ON habit_entry
+BEGIN
+    DELETE FROM habit_strength
+    WHERE habit_id = NEW.habit_id AND substr(NEW.entry_time, 1, 10) <= day;
+END;
+
+CREATE TRIGGER habit_strength_entry_update AFTER UPDATE ON habit_entry
+BEGIN
+    DELETE FROM habit_strength
+    WHERE (habit_id = OLD.habit_id AND substr(OLD.entry_time, 1, 10) <= day)
+        OR (habit_id = NEW.habit_id AND substr(NEW.entry_time, 1, 10) <= day);
+END;
+
+CREATE TRIGGER habit_strength_entry_delete AFTER DELETE ON habit_entry
+BEGIN
+    DELETE FROM habit_strength
+    WHERE habit_id = OLD.habit_id AND substr(OLD.entry_time, 1, 10) <= day;
+END;
+
+-- A new target or start changes all periods
+CREATE TRIGGER habit_strength_habit_update AFTER UPDATE OF frequency, created_at ON habit
+BEGIN
+    DELETE FROM habit_strength WHERE habit_id = NEW.id;
+END;
+
+CREATE TRIGGER habit_strength_habit_delete AFTER DELETE ON habit
+BEGIN
+    DELETE FROM habit_strength WHERE habit_id = OLD.id;
+END;
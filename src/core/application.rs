@@ -16,8 +16,15 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
 use adw::{subclass::prelude::*, AboutWindow};
 
+use anyhow::{anyhow, Result};
 use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::{
@@ -28,27 +35,59 @@ use log::{debug, info};
 
 use crate::{
     config::{APP_ID, PKGDATADIR, PROFILE, VERSION},
-    core::{i18n, spawn},
-    models::User,
-    windows::Window,
+    core::{
+        background,
+        dashboard::Dashboard,
+        database,
+        date::{Date, Time},
+        digest::{Digest, DigestScheduler},
+        encryption::DecryptError,
+        export::DataExport,
+        i18n, i18n_f, insights,
+        reminder::{
+            DatabaseProgress, QuietHours, ReminderKind, ReminderScheduler, SystemClock,
+            SNOOZE_DURATION,
+        },
+        report::{Report, ReportFormat},
+        review::{self, YearReview},
+        spawn, sync,
+    },
+    models::{Habit, HabitEntry, User},
+    windows::{DashboardWindow, PreferencesWindow, ReviewWindow, Window},
 };
 
+/// Number of days the insights of the dashboard look back.
+const INSIGHT_DAYS: i32 = 90;
+
+/// Number of days covered by a report when no range is given.
+const DEFAULT_REPORT_DAYS: i32 = 30;
+
 mod imp {
     use super::*;
-    use glib::WeakRef;
-    use once_cell::sync::OnceCell;
+    use glib::{subclass::Signal, WeakRef};
+    use once_cell::sync::{Lazy, OnceCell};
+    use std::{cell::RefCell, rc::Rc};
 
-    #[derive(Debug)]
     pub struct Application {
         pub settings: gio::Settings,
-        pub window: OnceCell<WeakRef<Window>>,
+        pub window: WeakRef<Window>,
+        pub reminders: OnceCell<Rc<ReminderScheduler>>,
+        pub digest: OnceCell<Rc<DigestScheduler>>,
+        /// Fires at midnight, when what's due and the streaks change
+        pub day_change: OnceCell<Rc<DigestScheduler>>,
+        /// Keeps the application running without a window, to send reminders
+        pub background_hold: RefCell<Option<gio::ApplicationHoldGuard>>,
     }
 
     impl Default for Application {
         fn default() -> Self {
             Self {
                 settings: gio::Settings::new(APP_ID),
-                window: OnceCell::new(),
+                window: WeakRef::new(),
+                reminders: OnceCell::new(),
+                digest: OnceCell::new(),
+                day_change: OnceCell::new(),
+                background_hold: RefCell::default(),
             }
         }
     }
@@ -60,7 +99,23 @@ mod imp {
         type ParentType = adw::Application;
     }
 
-    impl ObjectImpl for Application {}
+    impl ObjectImpl for Application {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_command_line_options();
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("habits-changed").build(),
+                    Signal::builder("entries-changed").build(),
+                    Signal::builder("day-changed").build(),
+                ]
+            });
+            SIGNALS.as_ref()
+        }
+    }
 
     impl ApplicationImpl for Application {
         fn activate(&self) {
@@ -68,12 +123,25 @@ mod imp {
             self.parent_activate();
             let app = self.obj();
 
-            if let Some(window) = self.window.get() {
-                let window = window.upgrade().unwrap();
+            // Back in the foreground until the window is closed again
+            self.background_hold.take();
+
+            if let Some(window) = self.window.upgrade() {
                 window.present();
                 return;
             }
             let window = Window::new(&app);
+            window.connect_close_request(
+                clone!(@weak app => @default-return glib::Propagation::Proceed, move |_| {
+                    if app.imp().settings.boolean("run-in-background")
+                        || app.flags().contains(gio::ApplicationFlags::IS_SERVICE)
+                    {
+                        app.run_in_background();
+                    }
+                    glib::Propagation::Proceed
+                }),
+            );
+            self.window.set(Some(&window));
 
             spawn(clone!(@strong app => async move {
                 if app.imp().settings.int("active-user-id") <= 0 {
@@ -87,14 +155,20 @@ mod imp {
                     }
                 }
 
-                app.imp().window
-                    .set(window.downgrade())
-                    .expect("Window already set.");
-
                 app.main_window().present();
+                app.sync();
             }));
         }
 
+        fn open(&self, files: &[gio::File], _hint: &str) {
+            debug!("GtkApplication<Application>::open");
+            self.obj().activate();
+            let window = self.obj().main_window();
+            for file in files {
+                window.import_habit_file(file);
+            }
+        }
+
         fn startup(&self) {
             debug!("GtkApplication<Application>::startup");
             self.parent_startup();
@@ -106,6 +180,43 @@ mod imp {
             app.setup_css();
             app.setup_gactions();
             app.setup_accels();
+            app.setup_reminders();
+            app.setup_digest();
+            app.setup_day_change();
+            app.setup_background();
+            app.setup_year_review();
+
+            if app.flags().contains(gio::ApplicationFlags::IS_SERVICE) {
+                info!("Running as a background service");
+                app.run_in_background();
+            }
+        }
+
+        fn shutdown(&self) {
+            if let Some(reminders) = self.reminders.get() {
+                reminders.stop();
+            }
+            if let Some(digest) = self.digest.get() {
+                digest.stop();
+            }
+            if let Some(day_change) = self.day_change.get() {
+                day_change.stop();
+            }
+            self.parent_shutdown();
+        }
+
+        fn handle_local_options(&self, options: &glib::VariantDict) -> glib::ExitCode {
+            if options.contains("report") {
+                return match self.obj().print_report(options) {
+                    Ok(()) => glib::ExitCode::SUCCESS,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        glib::ExitCode::FAILURE
+                    }
+                };
+            }
+
+            self.parent_handle_local_options(options)
         }
     }
 
@@ -120,8 +231,33 @@ glib::wrapper! {
 }
 
 impl Application {
+    // The main window, opened first when running without one, e.g. in the
+    // background or as a service
     fn main_window(&self) -> Window {
-        self.imp().window.get().unwrap().upgrade().unwrap()
+        if let Some(window) = self.imp().window.upgrade() {
+            return window;
+        }
+        self.activate();
+        self.imp().window.upgrade().unwrap()
+    }
+
+    /// Keeps running without a window, so reminders are still sent.
+    fn run_in_background(&self) {
+        let imp = self.imp();
+        if imp.background_hold.borrow().is_none() {
+            imp.background_hold.replace(Some(self.hold()));
+        }
+    }
+
+    // Keeps the autostart entry in line with the setting
+    fn setup_background(&self) {
+        let settings = &self.imp().settings;
+        self.add_action(&settings.create_action("run-in-background"));
+        settings.connect_changed(Some("run-in-background"), |settings, key| {
+            if let Err(e) = background::set_autostart(settings.boolean(key)) {
+                tracing::warn!("Failed to update autostart: {}", e);
+            }
+        });
     }
 
     fn setup_gactions(&self) {
@@ -129,24 +265,571 @@ impl Application {
         let action_quit = gio::ActionEntry::builder("quit")
             .activate(move |app: &Self, _, _| {
                 // This is needed to trigger the delete event and saving the window state
-                app.main_window().close();
+                if let Some(window) = app.imp().window.upgrade() {
+                    window.close();
+                }
                 app.quit();
             })
             .build();
 
+        // Raise the window, e.g. from a notification
+        let action_show_window = gio::ActionEntry::builder("show-window")
+            .activate(|app: &Self, _, _| {
+                app.activate();
+            })
+            .build();
+
         // About
         let action_about = gio::ActionEntry::builder("about")
             .activate(|app: &Self, _, _| {
                 app.show_about_window();
             })
             .build();
-        self.add_action_entries([action_quit, action_about]);
+
+        // Preferences
+        let action_preferences = gio::ActionEntry::builder("preferences")
+            .activate(|app: &Self, _, _| {
+                app.show_preferences_window();
+            })
+            .build();
+
+        // Dashboard
+        let action_dashboard = gio::ActionEntry::builder("dashboard")
+            .activate(|app: &Self, _, _| {
+                app.show_dashboard_window();
+            })
+            .build();
+
+        // Year in Review
+        let action_year_review = gio::ActionEntry::builder("year-review")
+            .activate(|app: &Self, _, _| {
+                app.show_review_window();
+            })
+            .build();
+
+        // Export Report
+        let action_export_report = gio::ActionEntry::builder("export-report")
+            .activate(|app: &Self, _, _| {
+                app.show_export_report_dialog();
+            })
+            .build();
+
+        // Export and import all data
+        let action_export_data = gio::ActionEntry::builder("export-data")
+            .activate(|app: &Self, _, _| {
+                app.show_export_data_dialog();
+            })
+            .build();
+        let action_import_data = gio::ActionEntry::builder("import-data")
+            .activate(|app: &Self, _, _| {
+                app.show_import_data_dialog();
+            })
+            .build();
+
+        // Sync through a shared folder
+        let action_sync = gio::ActionEntry::builder("sync")
+            .activate(|app: &Self, _, _| {
+                if app.imp().settings.string("sync-folder").is_empty() {
+                    app.show_sync_folder_dialog();
+                } else {
+                    app.sync();
+                }
+            })
+            .build();
+        let action_choose_sync_folder = gio::ActionEntry::builder("choose-sync-folder")
+            .activate(|app: &Self, _, _| {
+                app.show_sync_folder_dialog();
+            })
+            .build();
+        // Buttons of reminder notifications, with the habit id as target
+        let action_reminder_done = gio::ActionEntry::builder("reminder-done")
+            .parameter_type(Some(&u32::static_variant_type()))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(habit_id) = parameter.and_then(|p| p.get::<u32>()) {
+                    app.complete_habit(habit_id);
+                }
+            })
+            .build();
+        let action_reminder_log_value = gio::ActionEntry::builder("reminder-log-value")
+            .parameter_type(Some(&u32::static_variant_type()))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(habit_id) = parameter.and_then(|p| p.get::<u32>()) {
+                    app.show_log_value_dialog(habit_id);
+                }
+            })
+            .build();
+        let action_reminder_snooze = gio::ActionEntry::builder("reminder-snooze")
+            .parameter_type(Some(&u32::static_variant_type()))
+            .activate(|app: &Self, _, parameter| {
+                if let Some(habit_id) = parameter.and_then(|p| p.get::<u32>()) {
+                    app.snooze_reminder(habit_id);
+                }
+            })
+            .build();
+
+        self.add_action_entries([
+            action_quit,
+            action_show_window,
+            action_about,
+            action_preferences,
+            action_dashboard,
+            action_year_review,
+            action_export_report,
+            action_export_data,
+            action_import_data,
+            action_sync,
+            action_choose_sync_folder,
+            action_reminder_done,
+            action_reminder_log_value,
+            action_reminder_snooze,
+        ]);
+    }
+
+    /// Lets everything showing or using habits know they changed in the database.
+    /// The models don't call this themselves, so that bulk changes like imports
+    /// can call it once when done.
+    pub fn notify_habits_changed() {
+        if let Some(app) = gio::Application::default().and_downcast::<Self>() {
+            app.emit_by_name::<()>("habits-changed", &[]);
+        }
+    }
+
+    /// Lets everything showing the progress of habits know that entries were
+    /// logged, changed or deleted.
+    pub fn notify_entries_changed() {
+        if let Some(app) = gio::Application::default().and_downcast::<Self>() {
+            app.emit_by_name::<()>("entries-changed", &[]);
+        }
+    }
+
+    fn setup_reminders(&self) {
+        let reminders = ReminderScheduler::new(
+            SystemClock,
+            DatabaseProgress,
+            clone!(@weak self as app => move |due: &[(Habit, ReminderKind)]| {
+                app.send_reminders(due);
+                // Firing may have used up a snooze
+                app.save_snoozes();
+            }),
+        );
+        self.imp()
+            .reminders
+            .set(reminders.clone())
+            .unwrap_or_else(|_| panic!("Reminders already set up."));
+
+        self.connect_local(
+            "habits-changed",
+            false,
+            clone!(@weak self as app => @default-return None, move |_| {
+                app.reload_reminders();
+                None
+            }),
+        );
+        let settings = &self.imp().settings;
+        for key in [
+            "quiet-hours-enabled",
+            "quiet-hours-start",
+            "quiet-hours-end",
+        ] {
+            settings.connect_changed(
+                Some(key),
+                clone!(@weak self as app, @weak reminders => move |_, _| {
+                    reminders.set_quiet_hours(app.quiet_hours());
+                }),
+            );
+        }
+        // Only the active user's unmuted habits remind
+        for key in ["active-user-id", "muted-habits"] {
+            settings.connect_changed(
+                Some(key),
+                clone!(@weak self as app => move |_, _| {
+                    app.reload_reminders();
+                }),
+            );
+        }
+
+        self.reload_reminders();
+        reminders.set_quiet_hours(self.quiet_hours());
+        let snoozes = self
+            .imp()
+            .settings
+            .get::<Vec<(u32, i64)>>("snoozed-reminders")
+            .into_iter()
+            .filter_map(|(habit_id, until)| {
+                Some((habit_id, glib::DateTime::from_unix_local(until).ok()?))
+            });
+        reminders.set_snoozes(snoozes);
+        reminders.start();
+    }
+
+    fn save_snoozes(&self) {
+        let Some(reminders) = self.imp().reminders.get() else {
+            return;
+        };
+        let snoozes = reminders
+            .snoozes()
+            .into_iter()
+            .map(|(habit_id, until)| (habit_id, until.to_unix()))
+            .collect::<Vec<_>>();
+        if let Err(e) = self
+            .imp()
+            .settings
+            .set("snoozed-reminders", snoozes.to_variant())
+        {
+            tracing::warn!("Failed to save snoozed reminders: {}", e);
+        }
+    }
+
+    fn reminder_notification_id(habit_id: u32) -> String {
+        format!("reminder-{habit_id}")
+    }
+
+    /// Logs the habit with `habit_id` as done with its default value.
+    fn complete_habit(&self, habit_id: u32) {
+        self.withdraw_notification(&Self::reminder_notification_id(habit_id));
+        let result = Habit::find(habit_id).and_then(|habit| {
+            HabitEntry::create(
+                habit.user_id(),
+                habit_id,
+                None,
+                habit.frequency().default_entry_value(),
+            )
+        });
+        match result {
+            Ok(_) => Self::notify_entries_changed(),
+            Err(e) => tracing::warn!("Failed to log habit {}: {}", habit_id, e),
+        }
+    }
+
+    fn snooze_reminder(&self, habit_id: u32) {
+        self.withdraw_notification(&Self::reminder_notification_id(habit_id));
+        if let Some(reminders) = self.imp().reminders.get() {
+            reminders.snooze(habit_id);
+            self.save_snoozes();
+        }
+    }
+
+    // Asks how much of the habit was done, in its unit, and logs that
+    fn show_log_value_dialog(&self, habit_id: u32) {
+        self.withdraw_notification(&Self::reminder_notification_id(habit_id));
+        let habit = match Habit::find(habit_id) {
+            Ok(habit) => habit,
+            Err(e) => {
+                tracing::warn!("Failed to find habit {}: {}", habit_id, e);
+                return;
+            }
+        };
+        self.activate();
+        let window = self.main_window();
+
+        let frequency = habit.frequency();
+        let dialog = adw::MessageDialog::new(
+            Some(&window),
+            Some(&habit.display_name()),
+            Some(&i18n_f(
+                "How much did you do? Target: {}",
+                &[&frequency.target_text()],
+            )),
+        );
+        let spin_button = gtk::SpinButton::with_range(0.0, f64::from(i32::MAX), 1.0);
+        spin_button.set_value(f64::from(frequency.default_entry_value()));
+        spin_button.set_activates_default(true);
+        dialog.set_extra_child(Some(&spin_button));
+        dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("log", &i18n("_Log"))]);
+        dialog.set_response_appearance("log", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("log"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("log"),
+            clone!(@weak window, @weak spin_button => move |_, _| {
+                let result = HabitEntry::create(
+                    habit.user_id(),
+                    habit.id(),
+                    None,
+                    spin_button.value_as_int(),
+                );
+                match result {
+                    Ok(_) => Self::notify_entries_changed(),
+                    Err(e) => window.show_error(&i18n("Couldn't Log Habit"), &e.to_string()),
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    fn reload_reminders(&self) {
+        let Some(reminders) = self.imp().reminders.get() else {
+            return;
+        };
+        let muted = self.imp().settings.get::<Vec<u32>>("muted-habits");
+        match Habit::find_by_user(self.active_user_id()) {
+            Ok(habits) => reminders.set_habits(
+                habits
+                    .into_iter()
+                    .filter(|habit| !muted.contains(&habit.id()))
+                    .collect(),
+            ),
+            Err(e) => tracing::warn!("Failed to load habits for reminders: {}", e),
+        }
+    }
+
+    fn setup_digest(&self) {
+        let digest = DigestScheduler::new(
+            SystemClock,
+            clone!(@weak self as app => move || {
+                app.send_digest();
+            }),
+        );
+        self.imp()
+            .digest
+            .set(digest.clone())
+            .unwrap_or_else(|_| panic!("Digest already set up."));
+
+        let settings = &self.imp().settings;
+        for key in ["digest-enabled", "digest-time"] {
+            settings.connect_changed(
+                Some(key),
+                clone!(@weak self as app, @weak digest => move |_, _| {
+                    digest.set_time(app.digest_time());
+                }),
+            );
+        }
+        digest.set_time(self.digest_time());
+        digest.start();
+    }
+
+    // Lets the window know at midnight, as it lists what's due today
+    fn setup_day_change(&self) {
+        let day_change = DigestScheduler::new(
+            SystemClock,
+            clone!(@weak self as app => move || {
+                app.emit_by_name::<()>("day-changed", &[]);
+            }),
+        );
+        self.imp()
+            .day_change
+            .set(day_change.clone())
+            .unwrap_or_else(|_| panic!("Day change already set up."));
+        day_change.set_time(Time::new(0, 0, 0).ok());
+        day_change.start();
+    }
+
+    /// The time to send the digest at from the settings, if enabled and valid.
+    fn digest_time(&self) -> Option<Time> {
+        let settings = &self.imp().settings;
+        if !settings.boolean("digest-enabled") {
+            return None;
+        }
+        Time::parse(&settings.string("digest-time")).ok()
+    }
+
+    fn send_digest(&self) {
+        let digest = match Digest::generate(self.active_user_id(), Date::today()) {
+            Ok(digest) => digest,
+            Err(e) => {
+                tracing::warn!("Failed to build the digest: {}", e);
+                return;
+            }
+        };
+        if digest.is_empty() {
+            return;
+        }
+
+        let notification = gio::Notification::new(&digest.title());
+        notification.set_body(Some(&digest.body()));
+        notification.set_default_action("app.show-window");
+        self.send_notification(Some("digest"), &notification);
+    }
+
+    // Checks at startup and then hourly, to also catch New Year's Eve when
+    // running in the background
+    fn setup_year_review(&self) {
+        self.send_year_review();
+        glib::timeout_add_seconds_local(
+            60 * 60,
+            clone!(@weak self as app => @default-return glib::ControlFlow::Break, move || {
+                app.send_year_review();
+                glib::ControlFlow::Continue
+            }),
+        );
+    }
+
+    /// Tells the user once a year that the review of the year that just
+    /// ended is ready, unless nothing was logged in it.
+    fn send_year_review(&self) {
+        let settings = &self.imp().settings;
+        let year = review::finished_year(Date::today());
+        if settings.int("last-year-review") >= i32::from(year) {
+            return;
+        }
+        let review = match YearReview::generate(self.active_user_id(), year) {
+            Ok(review) => review,
+            Err(e) => {
+                tracing::warn!("Failed to build the year in review: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = settings.set_int("last-year-review", i32::from(year)) {
+            tracing::warn!("Failed to save the year of the last review: {}", e);
+        }
+        if review.is_empty() {
+            return;
+        }
+
+        let notification = gio::Notification::new(&review.title());
+        notification.set_body(Some(&i18n_f(
+            "{} entries over {} days. See your highlights of the year.",
+            &[
+                &review.total_entries.to_string(),
+                &review.active_days.to_string(),
+            ],
+        )));
+        notification.set_default_action("app.year-review");
+        self.send_notification(Some("year-review"), &notification);
+    }
+
+    /// The quiet hours from the settings, if enabled and valid.
+    fn quiet_hours(&self) -> Option<QuietHours> {
+        let settings = &self.imp().settings;
+        if !settings.boolean("quiet-hours-enabled") {
+            return None;
+        }
+        let time = |key| Time::parse(&settings.string(key)).ok();
+        Some(QuietHours {
+            start: time("quiet-hours-start")?,
+            end: time("quiet-hours-end")?,
+        })
+    }
+
+    // Reminders are bundled into one notification if enabled and more than one
+    // habit needs doing
+    fn send_reminders(&self, due: &[(Habit, ReminderKind)]) {
+        let (reminders, encouragements): (Vec<_>, Vec<_>) = due
+            .iter()
+            .partition(|(_, kind)| *kind == ReminderKind::Reminder);
+        for (habit, kind) in &encouragements {
+            self.send_reminder(habit, *kind);
+        }
+
+        if reminders.len() < 2 || !self.imp().settings.boolean("bundle-reminders") {
+            for (habit, kind) in &reminders {
+                self.send_reminder(habit, *kind);
+            }
+            return;
+        }
+
+        let notification = gio::Notification::new(&i18n_f(
+            "{} habits are due",
+            &[&reminders.len().to_string()],
+        ));
+        let names = reminders
+            .iter()
+            .map(|(habit, _)| habit.display_name())
+            .collect::<Vec<_>>()
+            .join(", ");
+        notification.set_body(Some(&names));
+        notification.set_default_action("app.show-window");
+        self.send_notification(Some("reminders"), &notification);
+    }
+
+    fn send_reminder(&self, habit: &Habit, kind: ReminderKind) {
+        let notification = gio::Notification::new(&habit.display_name());
+        if let Some(icon) = habit.icon() {
+            notification.set_icon(&gio::ThemedIcon::new(&icon));
+        }
+        notification.set_default_action("app.show-window");
+
+        // There's nothing to log for abstention habits, only to keep going
+        if kind == ReminderKind::Encouragement {
+            notification.set_body(Some(&i18n("You're on track today, keep it up!")));
+            self.send_notification(
+                Some(&Self::reminder_notification_id(habit.id())),
+                &notification,
+            );
+            return;
+        }
+
+        notification.set_body(Some(&i18n_f(
+            "Time for your habit: {}",
+            &[&habit.frequency().target_text()],
+        )));
+        let target = habit.id().to_variant();
+        notification.add_button_with_target_value(
+            &i18n("Done"),
+            "app.reminder-done",
+            Some(&target),
+        );
+        notification.add_button_with_target_value(
+            &i18n("Log Value…"),
+            "app.reminder-log-value",
+            Some(&target),
+        );
+        notification.add_button_with_target_value(
+            &i18n_f(
+                "Snooze {} min",
+                &[&(SNOOZE_DURATION.as_secs() / 60).to_string()],
+            ),
+            "app.reminder-snooze",
+            Some(&target),
+        );
+        self.send_notification(
+            Some(&Self::reminder_notification_id(habit.id())),
+            &notification,
+        );
+    }
+
+    fn setup_command_line_options(&self) {
+        self.add_main_option(
+            "report",
+            glib::Char::from(b'r'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::None,
+            &i18n("Print a progress report of the active user and exit"),
+            None,
+        );
+        self.add_main_option(
+            "report-from",
+            glib::Char::from(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            &i18n("First day of the report (default: 30 days ago)"),
+            Some("YYYY-MM-DD"),
+        );
+        self.add_main_option(
+            "report-to",
+            glib::Char::from(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            &i18n("Last day of the report (default: today)"),
+            Some("YYYY-MM-DD"),
+        );
+        self.add_main_option(
+            "report-format",
+            glib::Char::from(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            &i18n("Format of the report, markdown or html"),
+            Some("FORMAT"),
+        );
+        self.add_main_option(
+            "report-output",
+            glib::Char::from(0),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            &i18n("Write the report to FILE instead of the standard output"),
+            Some("FILE"),
+        );
     }
 
     // Sets up keyboard shortcuts
     fn setup_accels(&self) {
         self.set_accels_for_action("app.quit", &["<Control>q"]);
         self.set_accels_for_action("window.close", &["<Control>w"]);
+        self.set_accels_for_action("win.add-habit", &["<Control>n"]);
+        self.set_accels_for_action("win.search", &["<Control>f"]);
+        self.set_accels_for_action("win.move-habit-up", &["<Alt>Up"]);
+        self.set_accels_for_action("win.move-habit-down", &["<Alt>Down"]);
     }
 
     fn setup_css(&self) {
@@ -183,6 +866,307 @@ impl Application {
         Ok(())
     }
 
+    fn active_user_id(&self) -> u32 {
+        self.imp().settings.int("active-user-id").max(0) as u32
+    }
+
+    fn print_report(&self, options: &glib::VariantDict) -> Result<()> {
+        let lookup = |key: &str| options.lookup::<String>(key).ok().flatten();
+
+        let end = match lookup("report-to") {
+            Some(date) => Date::parse(&date)?,
+            None => Date::today(),
+        };
+        let start = match lookup("report-from") {
+            Some(date) => Date::parse(&date)?,
+            None => end.add_days(1 - DEFAULT_REPORT_DAYS),
+        };
+        let output = lookup("report-output");
+        let format = match lookup("report-format") {
+            Some(format) => ReportFormat::from_str(&format)
+                .map_err(|_| anyhow!("Unknown report format: {}", format))?,
+            None => output.as_deref().map_or(ReportFormat::Markdown, |path| {
+                ReportFormat::from_path(Path::new(path))
+            }),
+        };
+
+        let report = Report::generate(self.active_user_id(), start, end)?;
+        match output {
+            Some(path) => fs::write(path, report.render(format))?,
+            None => print!("{}", report.render(format)),
+        }
+        Ok(())
+    }
+
+    fn export_report(&self, path: &Path) -> Result<()> {
+        let end = Date::today();
+        let start = end.add_days(1 - DEFAULT_REPORT_DAYS);
+        let report = Report::generate(self.active_user_id(), start, end)?;
+        fs::write(path, report.render(ReportFormat::from_path(path)))?;
+        Ok(())
+    }
+
+    fn show_export_report_dialog(&self) {
+        let dialog = gtk::FileChooserNative::new(
+            Some(&i18n("Export Report")),
+            Some(&self.main_window()),
+            gtk::FileChooserAction::Save,
+            Some(&i18n("_Export")),
+            Some(&i18n("_Cancel")),
+        );
+        dialog.set_modal(true);
+        dialog.set_current_name(&format!(
+            "declutter-report.{}",
+            ReportFormat::Html.extension()
+        ));
+
+        for (name, pattern) in [
+            (i18n("HTML Document"), "*.html"),
+            (i18n("Markdown Document"), "*.md"),
+        ] {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(&name));
+            filter.add_pattern(pattern);
+            dialog.add_filter(&filter);
+        }
+
+        dialog.connect_response(
+            clone!(@weak self as app, @strong dialog => move |_, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = app.export_report(&path) {
+                            tracing::warn!("Failed to export report: {}", e);
+                        }
+                    }
+                }
+                dialog.destroy();
+            }),
+        );
+        dialog.show();
+    }
+
+    fn show_export_data_dialog(&self) {
+        let dialog = gtk::FileChooserNative::new(
+            Some(&i18n("Export Data")),
+            Some(&self.main_window()),
+            gtk::FileChooserAction::Save,
+            Some(&i18n("_Export")),
+            Some(&i18n("_Cancel")),
+        );
+        dialog.set_modal(true);
+        dialog.set_current_name("declutter-export.json");
+
+        dialog.connect_response(
+            clone!(@weak self as app, @strong dialog => move |_, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        app.show_export_encryption_dialog(path);
+                    }
+                }
+                dialog.destroy();
+            }),
+        );
+        dialog.show();
+    }
+
+    // Offers to encrypt the export, as notes may hold personal health information
+    fn show_export_encryption_dialog(&self, path: PathBuf) {
+        let dialog = adw::MessageDialog::new(
+            Some(&self.main_window()),
+            Some(&i18n("Encrypt Export?")),
+            Some(&i18n(
+                "Notes may contain personal information. Choose a passphrase to encrypt the export with. It's needed to import the export again and can't be recovered.",
+            )),
+        );
+        let entry = gtk::PasswordEntry::builder()
+            .show_peek_icon(true)
+            .activates_default(true)
+            .build();
+        dialog.set_extra_child(Some(&entry));
+        dialog.add_responses(&[
+            ("cancel", &i18n("_Cancel")),
+            ("plain", &i18n("Export _Unencrypted")),
+            ("encrypt", &i18n("_Encrypt")),
+        ]);
+        dialog.set_response_appearance("encrypt", adw::ResponseAppearance::Suggested);
+        dialog.set_response_enabled("encrypt", false);
+        dialog.set_default_response(Some("encrypt"));
+        dialog.set_close_response("cancel");
+        entry.connect_changed(clone!(@weak dialog => move |entry| {
+            dialog.set_response_enabled("encrypt", !entry.text().is_empty());
+        }));
+
+        dialog.connect_response(
+            None,
+            clone!(@weak self as app, @weak entry => move |_, response| {
+                let passphrase = match response {
+                    "encrypt" => Some(entry.text()),
+                    "plain" => None,
+                    _ => return,
+                };
+                if let Err(e) = app.export_data(&path, passphrase.as_deref()) {
+                    app.main_window()
+                        .show_error(&i18n("Couldn't Export Data"), &e.to_string());
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    fn export_data(&self, path: &Path, passphrase: Option<&str>) -> Result<()> {
+        let export = DataExport::collect(self.active_user_id())?;
+        fs::write(path, export.to_bytes(passphrase)?)?;
+        Ok(())
+    }
+
+    fn show_import_data_dialog(&self) {
+        let dialog = gtk::FileChooserNative::new(
+            Some(&i18n("Import Data")),
+            Some(&self.main_window()),
+            gtk::FileChooserAction::Open,
+            Some(&i18n("_Import")),
+            Some(&i18n("_Cancel")),
+        );
+        dialog.set_modal(true);
+
+        dialog.connect_response(
+            clone!(@weak self as app, @strong dialog => move |_, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        match fs::read(&path) {
+                            Ok(data) if DataExport::needs_passphrase(&data) => {
+                                app.show_import_passphrase_dialog(data, None);
+                            }
+                            Ok(data) => app.import_data(&data, None),
+                            Err(e) => app
+                                .main_window()
+                                .show_error(&i18n("Couldn't Import Data"), &e.to_string()),
+                        }
+                    }
+                }
+                dialog.destroy();
+            }),
+        );
+        dialog.show();
+    }
+
+    // Asks for the passphrase of an encrypted export, again if it was wrong
+    fn show_import_passphrase_dialog(&self, data: Vec<u8>, error: Option<DecryptError>) {
+        let body = match error {
+            Some(error) => error.to_string(),
+            None => i18n("Enter the passphrase the export was encrypted with."),
+        };
+        let dialog = adw::MessageDialog::new(
+            Some(&self.main_window()),
+            Some(&i18n("Encrypted Export")),
+            Some(&body),
+        );
+        let entry = gtk::PasswordEntry::builder()
+            .show_peek_icon(true)
+            .activates_default(true)
+            .build();
+        dialog.set_extra_child(Some(&entry));
+        dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("decrypt", &i18n("_Decrypt"))]);
+        dialog.set_response_appearance("decrypt", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("decrypt"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("decrypt"),
+            clone!(@weak self as app, @weak entry => move |_, _| {
+                app.import_data(&data, Some(&entry.text()));
+            }),
+        );
+        dialog.present();
+    }
+
+    fn import_data(&self, data: &[u8], passphrase: Option<&str>) {
+        let window = self.main_window();
+        let export = match DataExport::from_bytes(data, passphrase) {
+            Ok(export) => export,
+            Err(e) => {
+                match e.downcast_ref::<DecryptError>() {
+                    Some(error @ DecryptError::WrongPassphrase) => {
+                        self.show_import_passphrase_dialog(data.to_vec(), Some(*error));
+                    }
+                    _ => window.show_error(&i18n("Couldn't Import Data"), &e.to_string()),
+                }
+                return;
+            }
+        };
+
+        match export.import(self.active_user_id()) {
+            Ok((habits, entries)) => {
+                let dialog = adw::MessageDialog::new(
+                    Some(&window),
+                    Some(&i18n("Data Imported")),
+                    Some(&i18n_f(
+                        "Imported {} habits with {} entries.",
+                        &[&habits.to_string(), &entries.to_string()],
+                    )),
+                );
+                dialog.add_response("close", &i18n("_Close"));
+                dialog.present();
+            }
+            Err(e) => window.show_error(&i18n("Couldn't Import Data"), &e.to_string()),
+        }
+    }
+
+    fn show_sync_folder_dialog(&self) {
+        let dialog = gtk::FileChooserNative::new(
+            Some(&i18n("Choose Sync Folder")),
+            Some(&self.main_window()),
+            gtk::FileChooserAction::SelectFolder,
+            Some(&i18n("_Select")),
+            Some(&i18n("_Cancel")),
+        );
+        dialog.set_modal(true);
+
+        dialog.connect_response(
+            clone!(@weak self as app, @strong dialog => move |_, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        let folder = path.to_string_lossy();
+                        if let Err(e) = app.imp().settings.set_string("sync-folder", &folder) {
+                            tracing::warn!("Failed to save sync folder: {}", e);
+                        }
+                        app.sync();
+                    }
+                }
+                dialog.destroy();
+            }),
+        );
+        dialog.show();
+    }
+
+    /// Syncs with the other devices sharing the sync folder, if one is set.
+    fn sync(&self) {
+        let folder = self.imp().settings.string("sync-folder");
+        if folder.is_empty() {
+            return;
+        }
+
+        let result = database::connection()
+            .get()
+            .map_err(anyhow::Error::from)
+            .and_then(|mut conn| sync::sync(&mut conn, Path::new(folder.as_str())));
+        match result {
+            Ok(summary) => {
+                info!(
+                    "Synced with {}: {} changes exported, {} merged",
+                    folder, summary.exported, summary.merged
+                );
+                if summary.merged > 0 {
+                    Self::notify_habits_changed();
+                    Self::notify_entries_changed();
+                }
+            }
+            Err(e) => self
+                .main_window()
+                .show_error(&i18n("Couldn't Sync"), &e.to_string()),
+        }
+    }
+
     fn show_about_window(&self) {
         let about_window = AboutWindow::builder()
             .application_icon(APP_ID)
@@ -203,7 +1187,40 @@ impl Application {
     }
 
     fn show_preferences_window(&self) {
-        todo!()
+        let habits = Habit::find_by_user(self.active_user_id()).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load habits for preferences: {}", e);
+            Vec::new()
+        });
+        PreferencesWindow::new(&self.main_window(), &habits).present();
+    }
+
+    fn show_dashboard_window(&self) {
+        let window = self.main_window();
+        let user_id = self.active_user_id();
+        let today = Date::today();
+        let data = Dashboard::generate(user_id, today).and_then(|dashboard| {
+            // Today isn't over, so it would look like a day without the habits
+            let end = today.add_days(-1);
+            let insights = insights::generate(user_id, end.add_days(1 - INSIGHT_DAYS), end)?;
+            Ok((dashboard, insights))
+        });
+        match data {
+            Ok((dashboard, insights)) => {
+                DashboardWindow::new(&window, &dashboard, &insights).present();
+            }
+            Err(e) => window.show_error(&i18n("Couldn't Load Dashboard"), &e.to_string()),
+        }
+    }
+
+    fn show_review_window(&self) {
+        self.activate();
+        let window = self.main_window();
+        ReviewWindow::new(
+            &window,
+            self.active_user_id(),
+            review::default_year(Date::today()),
+        )
+        .present();
     }
 
     pub fn run(&self) -> glib::ExitCode {
@@ -220,6 +1237,7 @@ impl Default for Application {
         glib::Object::builder()
             .property("application-id", APP_ID)
             .property("resource-base-path", "/com/amankrx/Declutter/")
+            .property("flags", gio::ApplicationFlags::HANDLES_OPEN)
             .build()
     }
 }
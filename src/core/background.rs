@@ -0,0 +1,105 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use gtk::{gio, glib, prelude::*};
+
+use crate::{config::APP_ID, core::i18n};
+
+/// Command line option that starts Declutter as a background service,
+/// handled by [gio::Application] itself.
+pub const SERVICE_OPTION: &str = "--gapplication-service";
+
+const PORTAL_BUS_NAME: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const BACKGROUND_INTERFACE: &str = "org.freedesktop.portal.Background";
+
+fn is_flatpak() -> bool {
+    std::path::Path::new("/.flatpak-info").exists()
+}
+
+/// Where the autostart entry lives outside of Flatpak.
+pub fn autostart_file() -> PathBuf {
+    glib::user_config_dir()
+        .join("autostart")
+        .join(format!("{APP_ID}.desktop"))
+}
+
+/// The autostart entry, starting the background service on login.
+pub fn autostart_entry() -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Declutter\n\
+         Comment={}\n\
+         Exec=declutter {SERVICE_OPTION}\n\
+         Icon={APP_ID}\n\
+         NoDisplay=true\n\
+         X-GNOME-Autostart-enabled=true\n",
+        i18n("Send habit reminders in the background")
+    )
+}
+
+/// Starts the reminder service on login, or stops doing so.
+///
+/// Inside Flatpak this asks the Background portal, which may ask the user
+/// for permission. Elsewhere it writes or removes an entry in the user's
+/// autostart folder.
+pub fn set_autostart(enabled: bool) -> Result<()> {
+    if is_flatpak() {
+        return request_background(enabled);
+    }
+
+    let path = autostart_file();
+    if enabled {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, autostart_entry())?;
+    } else if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+fn request_background(autostart: bool) -> Result<()> {
+    let connection = gio::bus_get_sync(gio::BusType::Session, gio::Cancellable::NONE)?;
+
+    let options = glib::VariantDict::new(None);
+    options.insert("reason", i18n("Send habit reminders in the background"));
+    options.insert("autostart", autostart);
+    options.insert("commandline", vec!["declutter", SERVICE_OPTION]);
+    let parameters = glib::Variant::tuple_from_iter(["".to_variant(), options.end()]);
+
+    // The portal answers through a request object, the reply only confirms
+    // the call arrived
+    connection.call(
+        Some(PORTAL_BUS_NAME),
+        PORTAL_OBJECT_PATH,
+        BACKGROUND_INTERFACE,
+        "RequestBackground",
+        Some(&parameters),
+        None,
+        gio::DBusCallFlags::NONE,
+        -1,
+        gio::Cancellable::NONE,
+        move |result| {
+            if let Err(e) = result {
+                tracing::warn!("Failed to request running in the background: {}", e);
+            }
+        },
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autostart_entry() {
+        let entry = autostart_entry();
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Exec=declutter --gapplication-service\n"));
+        assert!(autostart_file().ends_with(format!("autostart/{APP_ID}.desktop")));
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    core::{
+        date::{Date, GDateTimeExt},
+        progress,
+        stats::{self, StatsRange},
+    },
+    models::{Habit, HabitCategory, HabitCategoryMap, HabitEntry},
+};
+
+/// Number of months [CategorySummary::trend] goes back, this one included.
+pub const TREND_MONTHS: usize = 6;
+
+/// The categories of `habit`: its own, or those of its preset if it has none.
+pub fn habit_categories(habit: &Habit, map: &HabitCategoryMap) -> Vec<HabitCategory> {
+    let mut categories = habit
+        .categories()
+        .filter(|categories| !categories.is_empty())
+        .or_else(|| map.get_categories(&habit.name()).cloned())
+        .unwrap_or_else(|| vec![HabitCategory::Other]);
+    categories.sort();
+    categories.dedup();
+    categories
+}
+
+/// The completion score of a category in one month.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TrendPoint {
+    /// First day of the month
+    pub month: Date,
+    pub score: Option<f64>,
+}
+
+/// How a category of habits is going.
+///
+/// A habit in several categories is split evenly between them, so that it
+/// weighs as much as a habit in a single category: a habit in Body and Health
+/// counts half towards each of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategorySummary {
+    pub category: HabitCategory,
+    /// Weighted completion rate of the last 30 days, [None] if there is
+    /// nothing to judge yet
+    pub score: Option<f64>,
+    /// Number of habits that aren't archived
+    pub active_habits: usize,
+    /// The share of habits the category makes up, i.e. the sum of the weights
+    pub weight: f64,
+    /// Weighted completion rate per month, oldest first
+    pub trend: Vec<TrendPoint>,
+}
+
+// Sums up weighted completion rates
+#[derive(Debug, Default, Copy, Clone)]
+struct WeightedRate {
+    sum: f64,
+    weight: f64,
+}
+
+impl WeightedRate {
+    fn add(&mut self, rate: Option<f64>, weight: f64) {
+        if let Some(rate) = rate {
+            self.sum += rate * weight;
+            self.weight += weight;
+        }
+    }
+
+    fn rate(&self) -> Option<f64> {
+        (self.weight > 0.0).then(|| self.sum / self.weight)
+    }
+}
+
+/// Per-category summaries of a user's habits.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dashboard {
+    /// The categories that have active habits, in the order of [HabitCategory]
+    pub categories: Vec<CategorySummary>,
+}
+
+impl Dashboard {
+    pub fn new(
+        habits: &[Habit],
+        entries: &[HabitEntry],
+        map: &HabitCategoryMap,
+        today: Date,
+    ) -> Self {
+        let months = trend_months(today);
+        let mut categories = BTreeMap::<HabitCategory, CategorySummary>::new();
+        let mut scores = BTreeMap::<HabitCategory, WeightedRate>::new();
+        let mut trends = BTreeMap::<HabitCategory, Vec<WeightedRate>>::new();
+
+        for habit in habits.iter().filter(|habit| !habit.archived()) {
+            let frequency = habit.frequency();
+            let created = habit.created_at().to_local().unwrap().date().min(today);
+            let habit_entries = entries
+                .iter()
+                .filter(|entry| entry.habit_id() == habit.id())
+                .cloned()
+                .collect::<Vec<_>>();
+            let totals = progress::daily_totals(&habit_entries);
+
+            let score = stats::completion_rate(
+                &frequency,
+                &totals,
+                StatsRange::Month.start(today, created),
+                today,
+            );
+            let trend = months
+                .iter()
+                .map(|month| {
+                    let end = month.last_of_month().min(today);
+                    if end < created {
+                        return None;
+                    }
+                    let start = (*month).max(created);
+                    let periods = progress::periods(&frequency, &totals, start, end);
+                    progress::completion_rate(&periods, today)
+                })
+                .collect::<Vec<_>>();
+
+            let habit_categories = habit_categories(habit, map);
+            let weight = 1.0 / habit_categories.len() as f64;
+            for category in habit_categories {
+                let summary = categories
+                    .entry(category)
+                    .or_insert_with(|| CategorySummary {
+                        category,
+                        score: None,
+                        active_habits: 0,
+                        weight: 0.0,
+                        trend: Vec::new(),
+                    });
+                summary.active_habits += 1;
+                summary.weight += weight;
+                scores.entry(category).or_default().add(score, weight);
+                let category_trend = trends
+                    .entry(category)
+                    .or_insert_with(|| vec![WeightedRate::default(); months.len()]);
+                for (point, rate) in category_trend.iter_mut().zip(&trend) {
+                    point.add(*rate, weight);
+                }
+            }
+        }
+
+        let categories = categories
+            .into_values()
+            .map(|mut summary| {
+                summary.score = scores[&summary.category].rate();
+                summary.trend = months
+                    .iter()
+                    .zip(&trends[&summary.category])
+                    .map(|(month, rate)| TrendPoint {
+                        month: *month,
+                        score: rate.rate(),
+                    })
+                    .collect();
+                summary
+            })
+            .collect();
+        Self { categories }
+    }
+
+    /// Builds the dashboard for all habits of the user with `user_id`.
+    pub fn generate(user_id: u32, today: Date) -> Result<Self> {
+        let habits = Habit::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        let entries = HabitEntry::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        Ok(Self::new(
+            &habits,
+            &entries,
+            &HabitCategoryMap::default(),
+            today,
+        ))
+    }
+
+    pub fn category(&self, category: HabitCategory) -> Option<&CategorySummary> {
+        self.categories
+            .iter()
+            .find(|summary| summary.category == category)
+    }
+}
+
+// The first days of the months of the trend, oldest first
+fn trend_months(today: Date) -> Vec<Date> {
+    let mut months = vec![today.first_of_month()];
+    while months.len() < TREND_MONTHS {
+        let previous = months.last().unwrap().add_days(-1).first_of_month();
+        months.push(previous);
+    }
+    months.reverse();
+    months
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::date::Time,
+        models::{DurationKind, Frequency, HabitName},
+    };
+
+    // 2024-05-06 is a Monday
+    fn date(month: u8, day: u8) -> Date {
+        Date::new(2024, month, day).unwrap()
+    }
+
+    fn habit(
+        id: u32,
+        name: HabitName,
+        categories: Option<Vec<HabitCategory>>,
+        created: Date,
+    ) -> Habit {
+        Habit::new(
+            id,
+            1,
+            name,
+            None,
+            None,
+            categories,
+            None,
+            Frequency::new(Some(DurationKind::Daily), None, Some(1), None, None),
+            created.and_time_local(Time::new(8, 0, 0).unwrap()),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn entries(id: u32, habit_id: u32, days: impl IntoIterator<Item = Date>) -> Vec<HabitEntry> {
+        days.into_iter()
+            .enumerate()
+            .map(|(i, day)| {
+                HabitEntry::new(
+                    id + i as u32,
+                    1,
+                    habit_id,
+                    day.and_time_local(Time::new(9, 0, 0).unwrap()),
+                    None,
+                    1,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_habit_categories() {
+        let map = HabitCategoryMap::default();
+        let own = habit(
+            1,
+            HabitName::Running,
+            Some(vec![HabitCategory::Social]),
+            date(5, 1),
+        );
+        assert_eq!(habit_categories(&own, &map), vec![HabitCategory::Social]);
+        let preset = habit(2, HabitName::Running, None, date(5, 1));
+        let mut expected = map
+            .get_categories(HabitName::Running.as_str())
+            .cloned()
+            .unwrap();
+        expected.sort();
+        assert_eq!(habit_categories(&preset, &map), expected);
+    }
+
+    #[test]
+    fn test_dashboard() {
+        let body = Some(vec![HabitCategory::Body]);
+        let body_and_mind = Some(vec![HabitCategory::Body, HabitCategory::Mind]);
+        let habits = vec![
+            // Done every day since April
+            habit(1, HabitName::Running, body.clone(), date(4, 1)),
+            // Never done
+            habit(2, HabitName::Yoga, body_and_mind, date(5, 1)),
+        ];
+        let mut all_entries = entries(1, 1, Date::range(date(4, 1), date(5, 10)));
+        all_entries.extend(entries(100, 99, [date(5, 3)]));
+
+        let dashboard = Dashboard::new(
+            &habits,
+            &all_entries,
+            &HabitCategoryMap::default(),
+            date(5, 10),
+        );
+        assert_eq!(dashboard.categories.len(), 2);
+
+        let body_summary = dashboard.category(HabitCategory::Body).unwrap();
+        assert_eq!(body_summary.active_habits, 2);
+        assert_eq!(body_summary.weight, 1.5);
+        // Running weighs 1, yoga 1/2
+        assert_eq!(body_summary.score, Some(1.0 / 1.5));
+        assert_eq!(body_summary.trend.len(), TREND_MONTHS);
+        assert_eq!(body_summary.trend[3].month, date(3, 1));
+        assert_eq!(body_summary.trend[3].score, None);
+        assert_eq!(body_summary.trend[4].score, Some(1.0));
+        assert_eq!(body_summary.trend[5].score, Some(1.0 / 1.5));
+
+        let mind_summary = dashboard.category(HabitCategory::Mind).unwrap();
+        assert_eq!(mind_summary.active_habits, 1);
+        assert_eq!(mind_summary.score, Some(0.0));
+        assert_eq!(mind_summary.trend[4].score, None);
+    }
+}
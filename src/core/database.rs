@@ -16,9 +16,13 @@
  * along with this program. If not, see <http://www.gnu.org/licenses/>.
  */
 
-use std::{fs, fs::File, path::PathBuf};
+use std::{
+    fs,
+    fs::File,
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use diesel::{prelude::*, r2d2, r2d2::ConnectionManager};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 use once_cell::sync::Lazy;
@@ -52,3 +56,12 @@ fn init_pool() -> Result<Pool> {
     tracing::info!("Database pool initialized.");
     Ok(pool)
 }
+
+/// Opens the database at `path` on its own, outside of the pool, and brings it
+/// up to date. Used to work on several databases at once, e.g. in tests.
+pub fn open(path: &Path) -> Result<SqliteConnection> {
+    let mut conn = SqliteConnection::establish(path.to_str().unwrap())?;
+    conn.run_pending_migrations(MIGRATIONS)
+        .map_err(|e| anyhow!("Failed to run migrations: {e}"))?;
+    Ok(conn)
+}
@@ -2,6 +2,8 @@ use anyhow::{bail, Result};
 use gtk::glib::{self, Boxed};
 use std::fmt;
 
+use crate::models::Weekday;
+
 pub mod prelude {
     pub use super::*;
 }
@@ -85,6 +87,12 @@ impl glib::DateTime {
         self.year() == other.year() && self.day_of_year() == other.day_of_year()
     }
 
+    /// Formats the timepoint as ISO 8601 in the local timezone. Entry times are
+    /// stored like this, so that their first ten characters are the local day.
+    pub fn format_iso8601_local(&self) -> Result<glib::GString, glib::BoolError> {
+        self.to_local()?.format_iso8601()
+    }
+
     #[must_use]
     pub fn date(&self) -> Date {
         Date::new(
@@ -135,13 +143,20 @@ impl Time {
         })
     }
 
+    /// Parses `HH:MM:SS`, or `HH:MM` with the seconds defaulting to zero.
     pub fn parse(string: &str) -> Result<Self> {
         let split: Vec<&str> = string.split(':').collect();
-        if split.len() < 3 {
-            bail!("Invalid string!");
+        match split.len() {
+            2 => Self::new(split[0].parse()?, split[1].parse()?, 0),
+            3 => Self::new(split[0].parse()?, split[1].parse()?, split[2].parse()?),
+            _ => bail!("Invalid string!"),
         }
+    }
 
-        Self::new(split[0].parse()?, split[1].parse()?, split[2].parse()?)
+    /// Formats the time as `HH:MM:SS`, the inverse of [Time::parse].
+    #[must_use]
+    pub fn to_iso8601(&self) -> String {
+        format!("{:02}:{:02}:{:02}", self.hour, self.minutes, self.seconds)
     }
 
     #[must_use]
@@ -160,6 +175,25 @@ impl Time {
     }
 }
 
+impl serde::Serialize for Time {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_iso8601())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Time {
+    fn deserialize<D>(deserializer: D) -> Result<Time, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let time_str = String::deserialize(deserializer)?;
+        Time::parse(&time_str).map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
 pub struct Date {
     year: u16,
@@ -192,6 +226,12 @@ impl Date {
         Self::new(split[0].parse()?, split[1].parse()?, split[2].parse()?)
     }
 
+    /// Formats the date as `YYYY-MM-DD`, the inverse of [Date::parse].
+    #[must_use]
+    pub fn to_iso8601(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+
     #[must_use]
     pub fn and_time_local(&self, time: Time) -> glib::DateTime {
         glib::DateTime::from_local(
@@ -232,6 +272,69 @@ impl Date {
     pub fn day(&self) -> u8 {
         self.day
     }
+
+    #[must_use]
+    pub fn today() -> Self {
+        glib::DateTime::local().date()
+    }
+
+    /// Returns the date `days` days after this one (or before, if negative).
+    #[must_use]
+    pub fn add_days(&self, days: i32) -> Self {
+        // Go through noon so that DST transitions at midnight can't shift the day
+        self.and_time_local(Time::new(12, 0, 0).unwrap())
+            .add_days(days)
+            .unwrap()
+            .date()
+    }
+
+    #[must_use]
+    pub fn weekday(&self) -> Weekday {
+        Weekday::from(self.and_time_local(Time::new(12, 0, 0).unwrap()))
+    }
+
+    /// The Monday of this date's week.
+    #[must_use]
+    pub fn start_of_week(&self) -> Self {
+        let days = Weekday::range(Weekday::Monday, self.weekday()).len() - 1;
+        self.add_days(-(days as i32))
+    }
+
+    #[must_use]
+    pub fn first_of_month(&self) -> Self {
+        Self { day: 1, ..*self }
+    }
+
+    #[must_use]
+    pub fn last_of_month(&self) -> Self {
+        Self {
+            day: glib::DateTime::days_of_month(self.year.into(), self.month.into()) as u8,
+            ..*self
+        }
+    }
+
+    /// Number of days from this date to `other`, negative if `other` lies before.
+    #[must_use]
+    pub fn days_until(&self, other: &Self) -> i64 {
+        let noon = Time::new(12, 0, 0).unwrap();
+        // Round, as a DST change in between makes the span an hour off
+        let span = other
+            .and_time_local(noon)
+            .difference(&self.and_time_local(noon));
+        (span.as_hours() as f64 / 24.0).round() as i64
+    }
+
+    /// All dates from `start` to `end`, both inclusive.
+    #[must_use]
+    pub fn range(start: Self, end: Self) -> Vec<Self> {
+        let mut range = Vec::new();
+        let mut current = start;
+        while current <= end {
+            range.push(current);
+            current = current.add_days(1);
+        }
+        range
+    }
 }
 
 #[cfg(test)]
@@ -246,4 +349,34 @@ mod test {
         gettextrs::setlocale(gettextrs::LocaleCategory::LcAll, "de_DE.UTF-8");
         assert_eq!(date.format_local(), "09.09.2001");
     }
+
+    #[test]
+    fn test_time_parse() {
+        assert_eq!(Time::parse("9:00").unwrap(), Time::new(9, 0, 0).unwrap());
+        assert_eq!(
+            Time::parse("07:30:15").unwrap(),
+            Time::new(7, 30, 15).unwrap()
+        );
+        assert!(Time::parse("7").is_err());
+        assert_eq!(Time::new(7, 5, 0).unwrap().to_iso8601(), "07:05:00");
+    }
+
+    #[test]
+    fn test_date_add_days() {
+        let date = Date::new(2024, 2, 28).unwrap();
+        assert_eq!(date.add_days(1), Date::new(2024, 2, 29).unwrap());
+        assert_eq!(date.add_days(2), Date::new(2024, 3, 1).unwrap());
+        assert_eq!(date.add_days(-28), Date::new(2024, 1, 31).unwrap());
+        assert_eq!(date.days_until(&Date::new(2024, 3, 1).unwrap()), 2);
+    }
+
+    #[test]
+    fn test_date_month_bounds() {
+        let date = Date::new(2023, 2, 14).unwrap();
+        assert_eq!(date.first_of_month(), Date::new(2023, 2, 1).unwrap());
+        assert_eq!(date.last_of_month(), Date::new(2023, 2, 28).unwrap());
+        assert_eq!(date.weekday(), Weekday::Tuesday);
+        assert_eq!(date.start_of_week(), Date::new(2023, 2, 13).unwrap());
+        assert_eq!(Date::range(date, Date::new(2023, 2, 16).unwrap()).len(), 3);
+    }
 }
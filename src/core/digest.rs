@@ -0,0 +1,350 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use gtk::glib;
+
+use crate::{
+    core::{
+        date::{Date, GDateTimeExt, Time},
+        i18n, i18n_f, progress,
+        reminder::Clock,
+    },
+    models::{DurationKind, Habit, HabitEntry},
+};
+
+/// Longest time to sleep before checking again, like for reminders.
+const MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Monthly habits are at risk in the last days of the month.
+const MONTHLY_RISK_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreakAtRisk {
+    pub name: String,
+    pub streak: u32,
+}
+
+/// The morning summary of a user's habits: what's left to do today, what
+/// was missed yesterday and which streaks end unless the habit is done today.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Digest {
+    pub due_today: Vec<String>,
+    pub missed_yesterday: Vec<String>,
+    pub at_risk: Vec<StreakAtRisk>,
+}
+
+impl Digest {
+    pub fn new(habits: &[Habit], entries: &[HabitEntry], today: Date) -> Self {
+        let mut digest = Self::default();
+        let yesterday = today.add_days(-1);
+
+        for habit in habits.iter().filter(|habit| !habit.archived()) {
+            let frequency = habit.frequency();
+            let name = habit.display_name();
+            let created = habit.created_at().to_local().unwrap().date();
+            let habit_entries = entries
+                .iter()
+                .filter(|entry| entry.habit_id() == habit.id())
+                .cloned()
+                .collect::<Vec<_>>();
+            let totals = progress::daily_totals(&habit_entries);
+            let periods = progress::periods(&frequency, &totals, created.min(today), today);
+            let current = periods.iter().find(|period| period.contains(today));
+
+            // Abstention habits have nothing to do, only to be missed
+            let needs_doing = current.map_or(false, |period| !period.completed);
+            if needs_doing && !frequency.is_abstraction() {
+                digest.due_today.push(name.clone());
+            }
+
+            if frequency.duration_kind == DurationKind::Daily
+                && created <= yesterday
+                && frequency.is_due_on(yesterday.weekday())
+                && !frequency.is_satisfied(totals.get(&yesterday).copied().unwrap_or(0))
+            {
+                digest.missed_yesterday.push(name.clone());
+            }
+
+            let streak = progress::current_streak(&periods, today);
+            let risky = match frequency.duration_kind {
+                DurationKind::Daily => true,
+                DurationKind::Monthly => {
+                    today.days_until(&today.last_of_month()) < MONTHLY_RISK_DAYS
+                }
+            };
+            if needs_doing && risky && streak > 0 && !frequency.is_abstraction() {
+                digest.at_risk.push(StreakAtRisk { name, streak });
+            }
+        }
+        digest
+    }
+
+    /// Builds the digest for all habits of the user with `user_id`.
+    pub fn generate(user_id: u32, today: Date) -> Result<Self> {
+        let habits = Habit::find_all()
+            .map_err(|e| anyhow!("{e}"))?
+            .into_iter()
+            .filter(|habit| habit.user_id() == user_id)
+            .collect::<Vec<_>>();
+        let entries = HabitEntry::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        Ok(Self::new(&habits, &entries, today))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.due_today.is_empty() && self.missed_yesterday.is_empty() && self.at_risk.is_empty()
+    }
+
+    pub fn title(&self) -> String {
+        i18n("Your Habits Today")
+    }
+
+    /// The body of the digest notification, one line per section.
+    pub fn body(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.due_today.is_empty() {
+            lines.push(i18n_f("Due today: {}", &[&self.due_today.join(", ")]));
+        }
+        if !self.missed_yesterday.is_empty() {
+            lines.push(i18n_f(
+                "Missed yesterday: {}",
+                &[&self.missed_yesterday.join(", ")],
+            ));
+        }
+        if !self.at_risk.is_empty() {
+            let at_risk = self
+                .at_risk
+                .iter()
+                .map(|habit| format!("{} ({})", habit.name, habit.streak))
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(i18n_f("Streaks at risk: {}", &[&at_risk]));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Calls back once a day at the digest time, if one is set.
+///
+/// Like [crate::core::reminder::ReminderScheduler], the timer only drives
+/// [DigestScheduler::tick] so tests can step through time by hand.
+pub struct DigestScheduler {
+    clock: Box<dyn Clock>,
+    time: Cell<Option<Time>>,
+    last_check: RefCell<glib::DateTime>,
+    on_fire: Box<dyn Fn()>,
+    source: RefCell<Option<glib::SourceId>>,
+    running: Cell<bool>,
+}
+
+impl DigestScheduler {
+    pub fn new(clock: impl Clock + 'static, on_fire: impl Fn() + 'static) -> Rc<Self> {
+        let now = clock.now();
+        Rc::new(Self {
+            clock: Box::new(clock),
+            time: Cell::new(None),
+            last_check: RefCell::new(now),
+            on_fire: Box::new(on_fire),
+            source: RefCell::default(),
+            running: Cell::new(false),
+        })
+    }
+
+    /// Sets the time of day to send the digest at, [None] to not send it.
+    pub fn set_time(self: &Rc<Self>, time: Option<Time>) {
+        self.time.set(time);
+        self.reschedule();
+    }
+
+    /// The first digest after `after`.
+    pub fn next_after(&self, after: &glib::DateTime) -> Option<glib::DateTime> {
+        let time = self.time.get()?;
+        let today = after.to_local().unwrap().date();
+        (0..=1)
+            .map(|days| today.add_days(days).and_time_local(time))
+            .find(|datetime| datetime > after)
+    }
+
+    /// Fires if the digest came due since the last tick. Returns whether it did.
+    pub fn tick(&self) -> bool {
+        let now = self.clock.now();
+        let last_check = self.last_check.replace(now.clone());
+        let due = self
+            .next_after(&last_check)
+            .map_or(false, |time| time <= now);
+        if due {
+            (self.on_fire)();
+        }
+        due
+    }
+
+    /// Starts the timer. Needs a running main loop.
+    pub fn start(self: &Rc<Self>) {
+        self.running.set(true);
+        self.reschedule();
+    }
+
+    pub fn stop(&self) {
+        self.running.set(false);
+        if let Some(source) = self.source.take() {
+            source.remove();
+        }
+    }
+
+    fn reschedule(self: &Rc<Self>) {
+        if let Some(source) = self.source.take() {
+            source.remove();
+        }
+        if !self.running.get() {
+            return;
+        }
+
+        let now = self.clock.now();
+        let wait = self.next_after(&now).map_or(MAX_WAIT, |time| {
+            let span = time.difference(&now);
+            Duration::from_micros(span.as_microseconds().max(0) as u64).min(MAX_WAIT)
+        });
+        let scheduler = Rc::downgrade(self);
+        let source = glib::timeout_add_local_once(wait, move || {
+            if let Some(scheduler) = Weak::upgrade(&scheduler) {
+                // The source is done, it mustn't be removed again
+                scheduler.source.take();
+                scheduler.tick();
+                scheduler.reschedule();
+            }
+        });
+        self.source.replace(Some(source));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Compare, Frequency, HabitName, Weekday};
+
+    // 2024-05-06 is a Monday
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    fn time(hour: u8, minutes: u8) -> Time {
+        Time::new(hour, minutes, 0).unwrap()
+    }
+
+    fn habit(id: u32, name: HabitName, frequency: Frequency) -> Habit {
+        Habit::new(
+            id,
+            1,
+            name,
+            None,
+            None,
+            None,
+            None,
+            frequency,
+            date(1).and_time_local(time(8, 0)),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn entry(id: u32, habit_id: u32, day: u8, value: i32) -> HabitEntry {
+        HabitEntry::new(
+            id,
+            1,
+            habit_id,
+            date(day).and_time_local(time(9, 0)),
+            None,
+            value,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_digest() {
+        let daily = Frequency::new(Some(DurationKind::Daily), None, None, None, None);
+        let weekends = Frequency::new(
+            Some(DurationKind::Daily),
+            None,
+            None,
+            Some(vec![Weekday::Saturday, Weekday::Sunday]),
+            None,
+        );
+        let no_sugar = Frequency::new(
+            Some(DurationKind::Daily),
+            None,
+            Some(0),
+            None,
+            Some(Compare::LessOrEqual),
+        );
+        let habits = vec![
+            habit(1, HabitName::Reading, daily.clone()),
+            habit(2, HabitName::Cleaning, weekends),
+            habit(3, HabitName::NoSugar, no_sugar),
+            habit(4, HabitName::Meditation, daily),
+        ];
+        let entries = vec![
+            // Reading on a streak of 2 until yesterday
+            entry(1, 1, 4, 1),
+            entry(2, 1, 5, 1),
+            // Sugar yesterday
+            entry(3, 3, 5, 1),
+            // Meditation already done today
+            entry(4, 4, 6, 1),
+        ];
+
+        let digest = Digest::new(&habits, &entries, date(6));
+        assert_eq!(digest.due_today, vec!["Reading"]);
+        assert_eq!(
+            digest.missed_yesterday,
+            vec!["Cleaning", "No Sugar", "Meditation"]
+        );
+        assert_eq!(
+            digest.at_risk,
+            vec![StreakAtRisk {
+                name: "Reading".to_string(),
+                streak: 2
+            }]
+        );
+        assert_eq!(
+            digest.body(),
+            "Due today: Reading\n\
+             Missed yesterday: Cleaning, No Sugar, Meditation\n\
+             Streaks at risk: Reading (2)"
+        );
+        assert!(Digest::default().is_empty());
+    }
+
+    #[derive(Clone)]
+    struct FakeClock(Rc<RefCell<glib::DateTime>>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> glib::DateTime {
+            self.0.borrow().clone()
+        }
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let clock = FakeClock(Rc::new(RefCell::new(date(6).and_time_local(time(7, 0)))));
+        let scheduler = DigestScheduler::new(clock.clone(), || {});
+        assert!(!scheduler.tick());
+
+        scheduler.set_time(Some(time(8, 0)));
+        let at = |day, hour, minutes| date(day).and_time_local(time(hour, minutes));
+        assert_eq!(scheduler.next_after(&at(6, 7, 0)), Some(at(6, 8, 0)));
+        assert_eq!(scheduler.next_after(&at(6, 8, 0)), Some(at(7, 8, 0)));
+
+        clock.0.replace(at(6, 8, 1));
+        assert!(scheduler.tick());
+        clock.0.replace(at(6, 12, 0));
+        assert!(!scheduler.tick());
+    }
+}
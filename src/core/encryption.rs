@@ -0,0 +1,259 @@
+use std::fmt;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::core::i18n;
+
+/// Marks the start of every encrypted archive.
+const MAGIC: &[u8; 8] = b"DCLTRENC";
+const FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const KEY_CHECK_LEN: usize = 16;
+// magic, version, Argon2 memory, iterations and lanes, salt, key check, nonce
+const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + KEY_CHECK_LEN + NONCE_LEN;
+const TAG_LEN: usize = 16;
+// 1 GiB, in KiB
+const MAX_M_COST: u32 = 1024 * 1024;
+
+/// Why an archive couldn't be decrypted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecryptError {
+    /// The data doesn't start with the archive header.
+    NotEncrypted,
+    UnsupportedVersion(u8),
+    /// The header or its key derivation parameters are cut off or invalid.
+    Corrupted,
+    WrongPassphrase,
+    /// The passphrase is right, but the contents were modified.
+    Tampered,
+}
+
+impl fmt::Display for DecryptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecryptError::NotEncrypted => write!(f, "{}", i18n("The file is not encrypted")),
+            DecryptError::UnsupportedVersion(version) => write!(
+                f,
+                "{} ({})",
+                i18n("The file was encrypted by a newer version of Declutter"),
+                version
+            ),
+            DecryptError::Corrupted => write!(f, "{}", i18n("The encrypted file is damaged")),
+            DecryptError::WrongPassphrase => write!(f, "{}", i18n("Wrong passphrase")),
+            DecryptError::Tampered => write!(
+                f,
+                "{}",
+                i18n("The encrypted file has been modified or damaged and can't be trusted")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecryptError {}
+
+/// Whether `data` looks like an archive made by [encrypt].
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypts `plaintext` with a key derived from `passphrase` by Argon2id,
+/// using XChaCha20-Poly1305.
+///
+/// Besides the key, the derivation yields a key check value that's stored in
+/// the header. It tells a wrong passphrase apart from modified contents. The
+/// whole header is authenticated along with the contents.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> anyhow::Result<Vec<u8>> {
+    encrypt_with_params(plaintext, passphrase, Params::default())
+}
+
+fn encrypt_with_params(
+    plaintext: &[u8],
+    passphrase: &str,
+    params: Params,
+) -> anyhow::Result<Vec<u8>> {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let (key, key_check) = derive_key(passphrase, &salt, params.clone())
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {e}"))?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    header.extend_from_slice(&params.m_cost().to_le_bytes());
+    header.extend_from_slice(&params.t_cost().to_le_bytes());
+    header.extend_from_slice(&params.p_cost().to_le_bytes());
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&key_check);
+    header.extend_from_slice(&nonce);
+
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut archive = header;
+    archive.extend_from_slice(&ciphertext);
+    Ok(archive)
+}
+
+/// Decrypts an archive made by [encrypt].
+pub fn decrypt(archive: &[u8], passphrase: &str) -> Result<Vec<u8>, DecryptError> {
+    if !is_encrypted(archive) {
+        return Err(DecryptError::NotEncrypted);
+    }
+    if archive.len() < HEADER_LEN + TAG_LEN {
+        return Err(DecryptError::Corrupted);
+    }
+    let (header, ciphertext) = archive.split_at(HEADER_LEN);
+
+    let version = header[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(DecryptError::UnsupportedVersion(version));
+    }
+
+    let mut rest = &header[MAGIC.len() + 1..];
+    let mut take = |len: usize| {
+        let (taken, remaining) = rest.split_at(len);
+        rest = remaining;
+        taken
+    };
+    let read_u32 = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap());
+    let m_cost = read_u32(take(4));
+    let t_cost = read_u32(take(4));
+    let p_cost = read_u32(take(4));
+    let salt = take(SALT_LEN);
+    let stored_key_check = take(KEY_CHECK_LEN);
+    let nonce = XNonce::from_slice(take(NONCE_LEN));
+
+    // Don't let a crafted header make the key derivation eat all memory
+    if m_cost > MAX_M_COST {
+        return Err(DecryptError::Corrupted);
+    }
+    let params = Params::new(m_cost, t_cost, p_cost, None).map_err(|_| DecryptError::Corrupted)?;
+    let (key, key_check) =
+        derive_key(passphrase, salt, params).map_err(|_| DecryptError::Corrupted)?;
+    if !constant_time_eq(&key_check, stored_key_check) {
+        return Err(DecryptError::WrongPassphrase);
+    }
+
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| DecryptError::Tampered)
+}
+
+fn derive_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: Params,
+) -> Result<([u8; KEY_LEN], [u8; KEY_CHECK_LEN]), argon2::Error> {
+    let mut output = [0; KEY_LEN + KEY_CHECK_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params).hash_password_into(
+        passphrase.as_bytes(),
+        salt,
+        &mut output,
+    )?;
+
+    let mut key = [0; KEY_LEN];
+    let mut key_check = [0; KEY_CHECK_LEN];
+    key.copy_from_slice(&output[..KEY_LEN]);
+    key_check.copy_from_slice(&output[KEY_LEN..]);
+    Ok((key, key_check))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAINTEXT: &[u8] = b"{\"note\": \"Blood pressure 120/80\"}";
+
+    // Cheap parameters, the defaults make the tests slow
+    fn encrypt(plaintext: &[u8], passphrase: &str) -> Vec<u8> {
+        let params = Params::new(Params::MIN_M_COST, 1, 1, None).unwrap();
+        encrypt_with_params(plaintext, passphrase, params).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let archive = encrypt(PLAINTEXT, "correct horse");
+        assert!(is_encrypted(&archive));
+        assert!(!archive
+            .windows(PLAINTEXT.len())
+            .any(|window| window == PLAINTEXT));
+        assert_eq!(decrypt(&archive, "correct horse").unwrap(), PLAINTEXT);
+    }
+
+    #[test]
+    fn test_wrong_passphrase() {
+        let archive = encrypt(PLAINTEXT, "correct horse");
+        assert_eq!(
+            decrypt(&archive, "battery staple"),
+            Err(DecryptError::WrongPassphrase)
+        );
+    }
+
+    #[test]
+    fn test_tampered() {
+        let archive = encrypt(PLAINTEXT, "correct horse");
+
+        let mut tampered = archive.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            decrypt(&tampered, "correct horse"),
+            Err(DecryptError::Tampered)
+        );
+
+        // The nonce is part of the authenticated header
+        let mut tampered = archive.clone();
+        tampered[HEADER_LEN - 1] ^= 1;
+        assert_eq!(
+            decrypt(&tampered, "correct horse"),
+            Err(DecryptError::Tampered)
+        );
+
+        assert_eq!(
+            decrypt(&archive[..HEADER_LEN], "correct horse"),
+            Err(DecryptError::Corrupted)
+        );
+    }
+
+    #[test]
+    fn test_not_encrypted() {
+        assert!(!is_encrypted(PLAINTEXT));
+        assert_eq!(
+            decrypt(PLAINTEXT, "correct horse"),
+            Err(DecryptError::NotEncrypted)
+        );
+
+        let mut archive = encrypt(PLAINTEXT, "correct horse");
+        archive[MAGIC.len()] = FORMAT_VERSION + 1;
+        assert_eq!(
+            decrypt(&archive, "correct horse"),
+            Err(DecryptError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+    }
+}
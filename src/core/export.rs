@@ -0,0 +1,461 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use diesel::prelude::*;
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{database, date::GDateTimeExt, encryption, i18n, i18n_f, serialize, sync, Application},
+    models::{Frequency, Habit, HabitCategory, HabitEntry, ReminderRule, User},
+    schema::{habit, habit_entry},
+};
+
+const FORMAT_VERSION: u32 = 1;
+
+/// A full export of a user's habits and entries.
+///
+/// Exports are JSON, optionally encrypted with a passphrase by
+/// [encryption::encrypt] since notes may hold personal health information.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DataExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub user: ExportedUser,
+    pub habits: Vec<ExportedHabit>,
+    pub entries: Vec<ExportedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExportedUser {
+    pub name: String,
+    pub date_of_birth: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExportedHabit {
+    /// Id of the habit in the exporting database, to link entries to it.
+    pub id: u32,
+    /// Id of the [HabitPreset](crate::models::HabitPreset) the habit was made from.
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<HabitCategory>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    pub frequency: Frequency,
+    pub created_at: String,
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub reminder_rules: Vec<ReminderRule>,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub archived_date: Option<String>,
+    #[serde(default)]
+    pub archived_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExportedEntry {
+    pub habit_id: u32,
+    pub entry_time: String,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub value: i32,
+}
+
+fn format_datetime(datetime: &glib::DateTime) -> String {
+    datetime.format_iso8601().unwrap().to_string()
+}
+
+fn parse_datetime(string: &str) -> Result<glib::DateTime> {
+    glib::DateTime::from_iso8601(string, None).map_err(|_| anyhow!("Invalid date: {}", string))
+}
+
+impl DataExport {
+    pub fn new(user: &User, habits: &[Habit], entries: &[HabitEntry]) -> Self {
+        let habits = habits
+            .iter()
+            .map(|habit| ExportedHabit {
+                id: habit.id(),
+                name: habit.name(),
+                title: habit.title(),
+                description: habit.description(),
+                categories: habit.categories().unwrap_or_default(),
+                icon: habit.icon(),
+                frequency: habit.frequency(),
+                created_at: format_datetime(&habit.created_at()),
+                updated_at: habit.updated_at().as_ref().map(format_datetime),
+                reminder_rules: habit.reminder_rules(),
+                note: habit.note(),
+                archived: habit.archived(),
+                archived_date: habit.archived_date().as_ref().map(format_datetime),
+                archived_reason: habit.archived_reason(),
+            })
+            .collect();
+        let entries = entries
+            .iter()
+            .map(|entry| ExportedEntry {
+                habit_id: entry.habit_id(),
+                entry_time: format_datetime(&entry.entry_time()),
+                note: entry.note(),
+                value: entry.value(),
+            })
+            .collect();
+
+        Self {
+            version: FORMAT_VERSION,
+            exported_at: format_datetime(&glib::DateTime::local()),
+            user: ExportedUser {
+                name: user.name(),
+                date_of_birth: format_datetime(&user.date_of_birth()),
+            },
+            habits,
+            entries,
+        }
+    }
+
+    /// Collects everything of the user with `user_id` from the database.
+    pub fn collect(user_id: u32) -> Result<Self> {
+        let user = User::find(user_id)?;
+        let habits = Habit::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        let entries = HabitEntry::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        Ok(Self::new(&user, &habits, &entries))
+    }
+
+    /// Serializes the export, encrypting it if a `passphrase` is given.
+    pub fn to_bytes(&self, passphrase: Option<&str>) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec_pretty(self)?;
+        match passphrase {
+            Some(passphrase) => encryption::encrypt(&json, passphrase),
+            None => Ok(json),
+        }
+    }
+
+    /// Whether `data` must be decrypted with a passphrase before it can be read.
+    pub fn needs_passphrase(data: &[u8]) -> bool {
+        encryption::is_encrypted(data)
+    }
+
+    /// Reads an export made by [DataExport::to_bytes]. Decryption errors are
+    /// returned as [encryption::DecryptError].
+    pub fn from_bytes(data: &[u8], passphrase: Option<&str>) -> Result<Self> {
+        let json = match (Self::needs_passphrase(data), passphrase) {
+            (true, Some(passphrase)) => encryption::decrypt(data, passphrase)?,
+            (true, None) => bail!(i18n("The file is encrypted, a passphrase is needed")),
+            (false, _) => data.to_vec(),
+        };
+        let export: Self = serde_json::from_slice(&json)
+            .map_err(|e| anyhow!("{}: {}", i18n("Not a valid Declutter export"), e))?;
+        if export.version == 0 || export.version > FORMAT_VERSION {
+            bail!(i18n_f(
+                "Exports of version {} are not supported",
+                &[&export.version.to_string()]
+            ));
+        }
+        Ok(export)
+    }
+
+    /// Adds the exported habits and entries to the user with `user_id`.
+    /// Returns the number of habits and entries imported.
+    pub fn import(&self, user_id: u32) -> Result<(usize, usize)> {
+        let mut conn = database::connection().get()?;
+        let imported = self.import_into(&mut conn, user_id)?;
+        Application::notify_habits_changed();
+        Application::notify_entries_changed();
+        Ok(imported)
+    }
+
+    /// Like [DataExport::import], into the database of `conn`. Either
+    /// everything is imported or, on an error, nothing.
+    pub fn import_into(&self, conn: &mut SqliteConnection, user_id: u32) -> Result<(usize, usize)> {
+        // Dates are checked and stored the way the app writes them
+        let reformat = |string: &str| parse_datetime(string).map(|d| format_datetime(&d));
+        // Entry times in local time though, their date is the day they count for here
+        let local_time = |string: &str| -> Result<String> {
+            Ok(parse_datetime(string)?.format_iso8601_local()?.to_string())
+        };
+        conn.transaction(|conn| {
+            let mut habit_ids = HashMap::new();
+            for exported in &self.habits {
+                diesel::insert_into(habit::table)
+                    .values((
+                        habit::user_id.eq(user_id as i32),
+                        habit::name.eq(exported.name.as_str()),
+                        habit::title.eq(&exported.title),
+                        habit::description.eq(&exported.description),
+                        habit::categories.eq((!exported.categories.is_empty())
+                            .then(|| serde_json::to_string(&exported.categories))
+                            .transpose()?),
+                        habit::icon.eq(&exported.icon),
+                        habit::frequency
+                            .eq(serialize::serialize_struct_to_string(&exported.frequency)),
+                        habit::created_at.eq(reformat(&exported.created_at)?),
+                        // Changed now as far as sync goes, so the habit reaches
+                        // other devices along with its entries
+                        habit::updated_at.eq(sync::now()),
+                        habit::reminder_rules.eq((!exported.reminder_rules.is_empty())
+                            .then(|| serde_json::to_string(&exported.reminder_rules))
+                            .transpose()?),
+                        habit::note.eq(&exported.note),
+                        habit::archived.eq(i32::from(exported.archived)),
+                        habit::archived_date.eq(exported
+                            .archived_date
+                            .as_deref()
+                            .map(reformat)
+                            .transpose()?),
+                        habit::archived_reason.eq(&exported.archived_reason),
+                        habit::uuid.eq(sync::new_uuid()),
+                    ))
+                    .execute(conn)?;
+                let id = habit::table
+                    .select(habit::id)
+                    .order(habit::id.desc())
+                    .first::<i32>(conn)?;
+                habit_ids.insert(exported.id, id);
+            }
+
+            let mut imported_entries = 0;
+            for entry in &self.entries {
+                let Some(habit_id) = habit_ids.get(&entry.habit_id) else {
+                    tracing::warn!("Skipping entry of unknown habit {}", entry.habit_id);
+                    continue;
+                };
+                diesel::insert_into(habit_entry::table)
+                    .values((
+                        habit_entry::user_id.eq(user_id as i32),
+                        habit_entry::habit_id.eq(*habit_id),
+                        habit_entry::entry_time.eq(local_time(&entry.entry_time)?),
+                        habit_entry::note.eq(&entry.note),
+                        habit_entry::value.eq(entry.value),
+                        habit_entry::uuid.eq(sync::new_uuid()),
+                        habit_entry::updated_at.eq(sync::now()),
+                    ))
+                    .execute(conn)?;
+                imported_entries += 1;
+            }
+
+            Ok::<_, anyhow::Error>((habit_ids.len(), imported_entries))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{
+            date::{Date, Time},
+            encryption::DecryptError,
+            progress,
+            stats::{self, HabitStats},
+        },
+        models::{Compare, HabitName, UnitSystem},
+    };
+
+    fn export() -> DataExport {
+        let now = glib::DateTime::local();
+        let user = User::new(1, "User", now.clone(), now.clone()).unwrap();
+        let habit = Habit::new(
+            4,
+            1,
+            HabitName::Custom,
+            Some("Blood pressure".to_string()),
+            None,
+            Some(vec![HabitCategory::Health]),
+            None,
+            Frequency::default(),
+            now.clone(),
+            None,
+            Vec::new(),
+            Some("Doctor's orders".to_string()),
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        let entry = HabitEntry::new(9, 1, 4, now, Some("120/80".to_string()), 1).unwrap();
+        DataExport::new(&user, &[habit], &[entry])
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let export = export();
+        assert_eq!(export.habits[0].id, export.entries[0].habit_id);
+        let bytes = export.to_bytes(None).unwrap();
+        assert!(!DataExport::needs_passphrase(&bytes));
+        assert_eq!(DataExport::from_bytes(&bytes, None).unwrap(), export);
+    }
+
+    #[test]
+    fn test_encrypted_round_trip() {
+        let export = export();
+        let bytes = export.to_bytes(Some("secret")).unwrap();
+        assert!(DataExport::needs_passphrase(&bytes));
+        assert!(!String::from_utf8_lossy(&bytes).contains("120/80"));
+        assert!(DataExport::from_bytes(&bytes, None).is_err());
+        assert_eq!(
+            DataExport::from_bytes(&bytes, Some("wrong"))
+                .unwrap_err()
+                .downcast::<DecryptError>()
+                .unwrap(),
+            DecryptError::WrongPassphrase
+        );
+        assert_eq!(
+            DataExport::from_bytes(&bytes, Some("secret")).unwrap(),
+            export
+        );
+    }
+
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    // A daily habit created weeks ago, done on the last three days but one
+    fn history() -> (User, Habit, Vec<HabitEntry>) {
+        let created = date(1).and_time_local(Time::new(8, 0, 0).unwrap());
+        let user = User::new(1, "User", created.clone(), created.clone()).unwrap();
+        let habit = Habit::new(
+            1,
+            1,
+            HabitName::Meditation,
+            None,
+            None,
+            None,
+            None,
+            Frequency::new(
+                None,
+                Some(UnitSystem::Minutes),
+                Some(10),
+                None,
+                Some(Compare::GreaterOrEqual),
+            ),
+            created,
+            Some(date(3).and_time_local(Time::new(9, 0, 0).unwrap())),
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        let entries = [
+            (1, 2, 10),
+            (2, 10, 15),
+            (3, 18, 10),
+            (4, 19, 5),
+            (5, 20, 10),
+        ]
+        .into_iter()
+        .map(|(id, day, value)| {
+            let time = date(day).and_time_local(Time::new(7, 0, 0).unwrap());
+            HabitEntry::new(id, 1, 1, time, None, value).unwrap()
+        })
+        .collect();
+        (user, habit, entries)
+    }
+
+    fn count(conn: &mut SqliteConnection) -> (i64, i64) {
+        (
+            habit::table.count().get_result(conn).unwrap(),
+            habit_entry::table.count().get_result(conn).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_import_keeps_history() {
+        let (user, habit, entries) = history();
+        let export = DataExport::new(&user, &[habit.clone()], &entries);
+        let bytes = export.to_bytes(None).unwrap();
+
+        let dir = tempdir::TempDir::new("import").unwrap();
+        let mut conn = database::open(&dir.path().join("declutter.db")).unwrap();
+        let imported = DataExport::from_bytes(&bytes, None)
+            .unwrap()
+            .import_into(&mut conn, 1)
+            .unwrap();
+        assert_eq!(imported, (1, 5));
+
+        let (id, created_at, updated_at) = habit::table
+            .select((habit::id, habit::created_at, habit::updated_at))
+            .first::<(i32, String, Option<String>)>(&mut conn)
+            .unwrap();
+        assert_eq!(id as u32, habit.id());
+        assert_eq!(created_at, export.habits[0].created_at);
+        let exported_updated_at = export.habits[0].updated_at.as_deref().unwrap();
+        assert!(
+            parse_datetime(&updated_at.unwrap()).unwrap()
+                > parse_datetime(exported_updated_at).unwrap()
+        );
+
+        let today = date(21);
+        let created = parse_datetime(&created_at)
+            .unwrap()
+            .to_local()
+            .unwrap()
+            .date();
+        let status = progress::status(
+            &habit.frequency(),
+            &stats::sql::daily_totals(&mut conn, habit.id()).unwrap(),
+            created,
+            today,
+        );
+        let expected = progress::status(
+            &habit.frequency(),
+            &progress::daily_totals(&entries),
+            date(1),
+            today,
+        );
+        assert_eq!(status, expected);
+        assert_eq!(status.streak, 1);
+        assert_eq!(
+            HabitStats::load(&mut conn, &habit, today).unwrap(),
+            HabitStats::new(&habit, &entries, today)
+        );
+    }
+
+    #[test]
+    fn test_failed_import_changes_nothing() {
+        let (user, habit, entries) = history();
+        let mut export = DataExport::new(&user, &[habit], &entries);
+        export.entries[3].entry_time = "yesterday".to_string();
+
+        let dir = tempdir::TempDir::new("import").unwrap();
+        let mut conn = database::open(&dir.path().join("declutter.db")).unwrap();
+        assert!(export.import_into(&mut conn, 1).is_err());
+        assert_eq!(count(&mut conn), (0, 0));
+
+        export.entries[3].entry_time = export.entries[2].entry_time.clone();
+        assert_eq!(export.import_into(&mut conn, 1).unwrap(), (1, 5));
+        assert_eq!(count(&mut conn), (1, 5));
+    }
+
+    #[test]
+    fn test_import_stores_local_entry_times() {
+        let (user, habit, entries) = history();
+        let mut export = DataExport::new(&user, &[habit], &entries);
+        // Made on a device far east, where it's already the next day
+        let entry_time = "2024-05-02T23:30:00+14:00";
+        export.entries.truncate(1);
+        export.entries[0].entry_time = entry_time.to_string();
+
+        let dir = tempdir::TempDir::new("import").unwrap();
+        let mut conn = database::open(&dir.path().join("declutter.db")).unwrap();
+        export.import_into(&mut conn, 1).unwrap();
+
+        let stored = habit_entry::table
+            .select(habit_entry::entry_time)
+            .first::<String>(&mut conn)
+            .unwrap();
+        let local = parse_datetime(entry_time).unwrap().to_local().unwrap();
+        assert_eq!(stored, format_datetime(&local));
+        assert_eq!(&stored[..10], local.date().to_iso8601());
+    }
+}
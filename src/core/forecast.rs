@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use gtk::glib;
+
+use crate::{
+    core::{
+        date::{Date, GDateTimeExt},
+        i18n_f,
+    },
+    models::{Compare, DurationKind, Frequency, UnitSystem},
+};
+
+/// Where a monthly habit with a cumulative target, like running 100 km a
+/// month, is heading at its current pace.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct MonthlyForecast {
+    pub unit: UnitSystem,
+    pub target: i64,
+    /// Sum of the month so far, today included
+    pub total: i64,
+    pub days_in_month: i64,
+    /// Days of the month so far, today included
+    pub days_elapsed: i64,
+}
+
+impl MonthlyForecast {
+    /// The forecast for the month of `today` from the daily `totals`. [None]
+    /// unless `frequency` is monthly with a minimum to reach.
+    pub fn new(frequency: &Frequency, totals: &BTreeMap<Date, i64>, today: Date) -> Option<Self> {
+        let cumulative = matches!(
+            frequency.comparator,
+            Compare::Greater | Compare::GreaterOrEqual
+        );
+        if frequency.duration_kind != DurationKind::Monthly
+            || !cumulative
+            || frequency.target_value == 0
+        {
+            return None;
+        }
+        Some(Self {
+            unit: frequency.unit,
+            target: i64::from(frequency.target_value),
+            total: totals
+                .range(today.first_of_month()..=today)
+                .map(|(_, value)| value)
+                .sum(),
+            days_in_month: glib::DateTime::days_of_month(
+                i32::from(today.year()),
+                i32::from(today.month()),
+            ),
+            days_elapsed: i64::from(today.day()),
+        })
+    }
+
+    /// Days left in the month, today included.
+    pub fn remaining_days(&self) -> i64 {
+        self.days_in_month - self.days_elapsed + 1
+    }
+
+    /// The total at the end of the month if the pace so far keeps up.
+    pub fn projected_total(&self) -> i64 {
+        (self.total as f64 * self.days_in_month as f64 / self.days_elapsed as f64).round() as i64
+    }
+
+    pub fn is_met(&self) -> bool {
+        self.total >= self.target
+    }
+
+    pub fn is_on_track(&self) -> bool {
+        self.projected_total() >= self.target
+    }
+
+    /// What has to be done per day for the rest of the month to reach the
+    /// target, [None] if it's already reached.
+    pub fn required_daily_rate(&self) -> Option<f64> {
+        (!self.is_met()).then(|| (self.target - self.total) as f64 / self.remaining_days() as f64)
+    }
+
+    /// The forecast in words.
+    pub fn text(&self) -> String {
+        let target = self.unit.format_value(self.target);
+        if self.is_met() {
+            return i18n_f("Target of {} reached this month", &[&target]);
+        }
+        let projected = self.unit.format_value(self.projected_total());
+        // Round up, as the rounded down rate wouldn't get there
+        let rate = self
+            .unit
+            .format_value(self.required_daily_rate().unwrap_or(0.0).ceil() as i64);
+        if self.is_on_track() {
+            i18n_f(
+                "On track for {} of {} this month, {} a day gets you there",
+                &[&projected, &target, &rate],
+            )
+        } else {
+            i18n_f(
+                "Heading for {} of {} this month, {} a day is needed to catch up",
+                &[&projected, &target, &rate],
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u8) -> Date {
+        Date::new(2024, 4, day).unwrap()
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::new(
+            Some(DurationKind::Monthly),
+            Some(UnitSystem::Kilometers),
+            Some(100),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_forecast() {
+        // 5 km every other day, and some of March
+        let mut totals = BTreeMap::from([(Date::new(2024, 3, 31).unwrap(), 20)]);
+        for day in (1..=10).step_by(2) {
+            totals.insert(date(day), 5);
+        }
+
+        let forecast = MonthlyForecast::new(&frequency(), &totals, date(10)).unwrap();
+        assert_eq!(forecast.total, 25);
+        assert_eq!(forecast.days_in_month, 30);
+        assert_eq!(forecast.remaining_days(), 21);
+        assert_eq!(forecast.projected_total(), 75);
+        assert!(!forecast.is_on_track());
+        assert_eq!(forecast.required_daily_rate(), Some(75.0 / 21.0));
+        assert_eq!(
+            forecast.text(),
+            "Heading for 75 kilometers of 100 kilometers this month, \
+             4 kilometers a day is needed to catch up"
+        );
+
+        totals.insert(date(11), 80);
+        let forecast = MonthlyForecast::new(&frequency(), &totals, date(11)).unwrap();
+        assert!(forecast.is_met());
+        assert_eq!(forecast.required_daily_rate(), None);
+    }
+
+    #[test]
+    fn test_only_cumulative_monthly_habits() {
+        let daily = Frequency::new(Some(DurationKind::Daily), None, Some(5), None, None);
+        assert_eq!(
+            MonthlyForecast::new(&daily, &BTreeMap::new(), date(1)),
+            None
+        );
+        let abstention = Frequency::new(
+            Some(DurationKind::Monthly),
+            None,
+            Some(0),
+            None,
+            Some(Compare::LessOrEqual),
+        );
+        assert_eq!(
+            MonthlyForecast::new(&abstention, &BTreeMap::new(), date(1)),
+            None
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{i18n, i18n_f, Application},
+    models::{
+        DurationKind, Frequency, Habit, HabitCategory, HabitInfo, HabitName, HabitPreset,
+        ReminderRule, UnitSystem, MAX_OFFSET_MINUTES,
+    },
+};
+
+/// File extension of shared habit definitions.
+pub const HABIT_FILE_EXTENSION: &str = "declutter-habit";
+pub const HABIT_FILE_MIME_TYPE: &str = "application/x-declutter-habit";
+
+const FORMAT_VERSION: u32 = 1;
+
+/// A [HabitDefinition] is the shareable part of a [Habit]: what it is and how
+/// it's tracked, without any of its entries. It's stored as JSON in
+/// `.declutter-habit` files.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HabitDefinition {
+    pub version: u32,
+    /// Id of the [HabitPreset] the habit is made from.
+    pub name: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<HabitCategory>,
+    pub frequency: Frequency,
+    /// When to be reminded. Plain times of day like `"09:00"` remind on
+    /// every day the habit is due.
+    #[serde(default)]
+    pub reminders: Vec<ReminderRule>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl HabitDefinition {
+    /// A definition of a habit made from the preset `name`, prefilled from it.
+    pub fn new(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let info = HabitInfo::for_preset(&name);
+        Self {
+            version: FORMAT_VERSION,
+            name,
+            title: None,
+            description: (!info.description.is_empty()).then_some(info.description),
+            icon: info.icon,
+            categories: info.categories,
+            frequency: info.frequency,
+            reminders: Vec::new(),
+            note: None,
+        }
+    }
+
+    pub fn from_habit(habit: &Habit) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            name: habit.name(),
+            title: habit.title(),
+            description: habit.description(),
+            icon: habit.icon(),
+            categories: habit.categories().unwrap_or_default(),
+            frequency: habit.frequency(),
+            reminders: habit.reminder_rules(),
+            note: habit.note(),
+        }
+    }
+
+    /// Reads and validates a definition from the contents of a habit file.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let definition: Self = serde_json::from_slice(data)
+            .map_err(|e| anyhow!("{}: {}", i18n("Not a valid habit file"), e))?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.version == 0 || self.version > FORMAT_VERSION {
+            bail!(i18n_f(
+                "Habit files of version {} are not supported",
+                &[&self.version.to_string()]
+            ));
+        }
+        // Habits of presets this device doesn't know are custom ones too
+        let is_custom =
+            self.name == HabitName::Custom.as_str() || HabitPreset::find(&self.name).is_none();
+        if is_custom
+            && self
+                .title
+                .as_deref()
+                .map_or(true, |title| title.trim().is_empty())
+        {
+            bail!(i18n("Custom habits need a title"));
+        }
+        if let Some(weekdays) = &self.frequency.weekdays {
+            if self.frequency.duration_kind == DurationKind::Monthly {
+                bail!(i18n("Monthly habits can't be limited to weekdays"));
+            }
+            if weekdays.is_empty() {
+                bail!(i18n("The habit isn't due on any weekday"));
+            }
+        }
+        let minutes_per_day = match self.frequency.unit {
+            UnitSystem::Minutes => self.frequency.target_value,
+            UnitSystem::Hours => self.frequency.target_value.saturating_mul(60),
+            _ => 0,
+        };
+        if self.frequency.duration_kind == DurationKind::Daily && minutes_per_day > 24 * 60 {
+            bail!(i18n("The target doesn't fit in a day"));
+        }
+        for (i, reminder) in self.reminders.iter().enumerate() {
+            if reminder.offset_minutes.abs() > MAX_OFFSET_MINUTES {
+                bail!(i18n("Reminders can't be moved by more than a day"));
+            }
+            if self.reminders[..i].contains(reminder) {
+                bail!(i18n_f(
+                    "There are two reminders at {}",
+                    &[&reminder.describe()]
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn display_name(&self) -> String {
+        self.title
+            .clone()
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| HabitInfo::for_preset(&self.name).name)
+    }
+
+    /// A short, human readable summary to preview the definition with.
+    pub fn summary(&self) -> String {
+        let mut lines = Vec::new();
+        if let Some(description) = self.description.as_ref().filter(|d| !d.is_empty()) {
+            lines.push(description.clone());
+        }
+        lines.push(format!(
+            "{}: {}",
+            i18n("Target"),
+            self.frequency.target_text()
+        ));
+        if let Some(weekdays) = self.frequency.weekdays_text() {
+            lines.push(format!("{}: {}", i18n("Due on"), weekdays));
+        }
+        if !self.categories.is_empty() {
+            let categories = self
+                .categories
+                .iter()
+                .map(HabitCategory::label)
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!("{}: {}", i18n("Categories"), categories));
+        }
+        if !self.reminders.is_empty() {
+            let reminders = self
+                .reminders
+                .iter()
+                .map(ReminderRule::describe)
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format!("{}: {}", i18n("Reminders"), reminders));
+        }
+        lines.join("\n")
+    }
+
+    /// Creates a new habit for the user with `user_id` from this definition.
+    pub fn create(&self, user_id: u32) -> Result<Habit> {
+        let habit = Habit::create(
+            user_id,
+            self.name.clone(),
+            self.title.clone(),
+            self.description.clone(),
+            (!self.categories.is_empty()).then(|| self.categories.clone()),
+            self.icon.clone(),
+            self.frequency.clone(),
+            self.reminders.clone(),
+            self.note.clone(),
+            false,
+            None,
+            None,
+        )
+        .map_err(|e| anyhow!("{e}"))?;
+        Application::notify_habits_changed();
+        Ok(habit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::date::Time,
+        models::{Compare, UnitSystem, Weekday},
+    };
+    use gtk::glib;
+
+    const DEEP_WORK: &str = r#"{
+        "version": 1,
+        "name": "custom",
+        "title": "Deep work",
+        "categories": ["productivity"],
+        "frequency": {
+            "duration_kind": "daily",
+            "unit": "minutes",
+            "target_value": 120,
+            "weekdays": ["monday", "tuesday", "wednesday", "thursday", "friday"],
+            "comparator": "greater_or_equal"
+        },
+        "reminders": ["09:00"]
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let definition = HabitDefinition::parse(DEEP_WORK.as_bytes()).unwrap();
+        assert_eq!(definition.display_name(), "Deep work");
+        assert_eq!(definition.frequency.unit, UnitSystem::Minutes);
+        assert_eq!(definition.frequency.comparator, Compare::GreaterOrEqual);
+        assert_eq!(
+            definition.reminders,
+            vec![ReminderRule::new(Time::new(9, 0, 0).unwrap())]
+        );
+        assert_eq!(
+            definition.summary(),
+            "Target: >= 120 minutes per day\n\
+             Due on: Mon, Tue, Wed, Thu, Fri\n\
+             Categories: Productivity\n\
+             Reminders: 09:00"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let definition = HabitDefinition::parse(DEEP_WORK.as_bytes()).unwrap();
+        let parsed = HabitDefinition::parse(definition.to_json().as_bytes()).unwrap();
+        assert_eq!(definition, parsed);
+    }
+
+    #[test]
+    fn test_from_habit() {
+        let habit = Habit::new(
+            3,
+            1,
+            HabitName::Reading,
+            None,
+            Some("Read a book".to_string()),
+            Some(vec![HabitCategory::Mind]),
+            None,
+            Frequency::default(),
+            glib::DateTime::now_local().unwrap(),
+            None,
+            vec![
+                ReminderRule::new(Time::new(7, 30, 0).unwrap()),
+                ReminderRule::new(Time::new(10, 0, 0).unwrap()).on(vec![Weekday::Saturday]),
+            ],
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        let definition = HabitDefinition::from_habit(&habit);
+        assert_eq!(definition.version, FORMAT_VERSION);
+        assert_eq!(definition.display_name(), "Reading");
+        assert_eq!(definition.reminders, habit.reminder_rules());
+    }
+
+    #[test]
+    fn test_new() {
+        let definition = HabitDefinition::new(HabitName::Reading);
+        let info = HabitName::Reading.info();
+        assert_eq!(definition.frequency, info.frequency);
+        assert_eq!(definition.categories, info.categories);
+        assert!(definition.validate().is_ok());
+        assert!(HabitDefinition::new(HabitName::Custom).validate().is_err());
+
+        let mut unknown = HabitDefinition::new("someone_elses_preset");
+        assert_eq!(unknown.display_name(), "someone_elses_preset");
+        assert!(unknown.validate().is_err());
+        unknown.title = Some("Stretching".to_string());
+        assert!(unknown.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let valid = HabitDefinition::parse(DEEP_WORK.as_bytes()).unwrap();
+
+        let mut definition = valid.clone();
+        definition.version = FORMAT_VERSION + 1;
+        assert!(definition.validate().is_err());
+
+        let mut definition = valid.clone();
+        definition.title = Some(" ".to_string());
+        assert!(definition.validate().is_err());
+
+        let mut definition = valid.clone();
+        definition.frequency.weekdays = Some(vec![]);
+        assert!(definition.validate().is_err());
+
+        let mut definition = valid.clone();
+        definition.frequency.unit = UnitSystem::Hours;
+        definition.frequency.target_value = 25;
+        assert!(definition.validate().is_err());
+        definition.frequency.duration_kind = DurationKind::Monthly;
+        definition.frequency.weekdays = None;
+        assert!(definition.validate().is_ok());
+
+        let mut definition = valid.clone();
+        let reminder = ReminderRule::new(Time::new(9, 0, 0).unwrap());
+        definition.reminders = vec![reminder.clone(), reminder.clone()];
+        assert!(definition.validate().is_err());
+        definition.reminders = vec![reminder.with_offset(-MAX_OFFSET_MINUTES - 1)];
+        assert!(definition.validate().is_err());
+
+        let mut definition = valid;
+        definition.frequency.duration_kind = DurationKind::Monthly;
+        definition.frequency.weekdays = Some(vec![Weekday::Monday]);
+        assert!(definition.validate().is_err());
+
+        assert!(HabitDefinition::parse(b"{\"version\": 1}").is_err());
+        assert!(HabitDefinition::parse(b"not json").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    core::{
+        date::{Date, GDateTimeExt},
+        i18n_f, progress,
+    },
+    models::{DurationKind, Habit, HabitEntry},
+};
+
+/// Fewest days both habits must have been due on to compare them.
+pub const DEFAULT_MIN_DAYS: usize = 14;
+
+/// Fewest days on which a habit must have been done, and not done, for the
+/// rates of the other habit on those days to mean anything.
+const MIN_GROUP_DAYS: usize = 3;
+
+/// How doing one habit goes along with doing another one on the same day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insight {
+    /// Name of the habit whose days are split into done and not done
+    pub habit: String,
+    /// Name of the habit compared on those days
+    pub other: String,
+    /// Number of days both habits were due on
+    pub days: usize,
+    /// Share of the days `habit` was done on that `other` was done too
+    pub with_rate: f64,
+    /// Share of the days `habit` wasn't done on that `other` was done
+    pub without_rate: f64,
+    /// Phi coefficient of the two habits' daily completion, from -1 to 1
+    pub correlation: f64,
+}
+
+impl Insight {
+    /// How much more likely `other` is done on days `habit` is, e.g. 0.4 for
+    /// 40% more likely, negative if less likely. [None] if `other` was never
+    /// done without `habit`.
+    pub fn lift(&self) -> Option<f64> {
+        (self.without_rate > 0.0).then(|| self.with_rate / self.without_rate - 1.0)
+    }
+
+    /// The insight in words.
+    pub fn text(&self) -> String {
+        let percent = |rate: f64| format!("{:.0}", rate * 100.0);
+        match self.lift() {
+            Some(lift) if lift >= 0.0 => i18n_f(
+                "On days you do {}, you're {}% more likely to do {}",
+                &[&self.habit, &percent(lift), &self.other],
+            ),
+            Some(lift) => i18n_f(
+                "On days you do {}, you're {}% less likely to do {}",
+                &[&self.habit, &percent(-lift), &self.other],
+            ),
+            None => i18n_f(
+                "You only do {} on days you do {}",
+                &[&self.other, &self.habit],
+            ),
+        }
+    }
+}
+
+// The days a daily habit was due on from `start` to `end`, and whether it was
+// completed on each
+fn daily_completion(
+    habit: &Habit,
+    entries: &[HabitEntry],
+    start: Date,
+    end: Date,
+) -> BTreeMap<Date, bool> {
+    let habit_entries = entries
+        .iter()
+        .filter(|entry| entry.habit_id() == habit.id())
+        .cloned()
+        .collect::<Vec<_>>();
+    let totals = progress::daily_totals(&habit_entries);
+    let created = habit.created_at().to_local().unwrap().date();
+    progress::periods(&habit.frequency(), &totals, start.max(created), end)
+        .into_iter()
+        .map(|period| (period.start, period.completed))
+        .collect()
+}
+
+/// Compares the daily completion of every pair of daily `habits` from `start`
+/// to `end`, leaving out pairs due together on fewer than `min_days` days.
+/// The strongest relationships come first.
+pub fn insights(
+    habits: &[Habit],
+    entries: &[HabitEntry],
+    start: Date,
+    end: Date,
+    min_days: usize,
+) -> Vec<Insight> {
+    // Monthly habits have no daily completion to compare
+    let completions = habits
+        .iter()
+        .filter(|habit| !habit.archived() && habit.frequency().duration_kind == DurationKind::Daily)
+        .map(|habit| {
+            (
+                habit.display_name(),
+                daily_completion(habit, entries, start, end),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut insights = Vec::new();
+    for (habit, completion) in &completions {
+        for (other, other_completion) in &completions {
+            if std::ptr::eq(completion, other_completion) {
+                continue;
+            }
+            if let Some(insight) = compare(habit, completion, other, other_completion, min_days) {
+                insights.push(insight);
+            }
+        }
+    }
+    insights.sort_by(|a, b| {
+        b.correlation
+            .abs()
+            .total_cmp(&a.correlation.abs())
+            .then_with(|| b.days.cmp(&a.days))
+    });
+    insights
+}
+
+fn compare(
+    habit: &str,
+    completion: &BTreeMap<Date, bool>,
+    other: &str,
+    other_completion: &BTreeMap<Date, bool>,
+    min_days: usize,
+) -> Option<Insight> {
+    let days = completion
+        .keys()
+        .collect::<BTreeSet<_>>()
+        .intersection(&other_completion.keys().collect())
+        .map(|date| (completion[*date], other_completion[*date]))
+        .collect::<Vec<_>>();
+    if days.len() < min_days.max(1) {
+        return None;
+    }
+
+    // Days with both done, only `habit`, only `other` and neither
+    let count = |done: (bool, bool)| days.iter().filter(|day| **day == done).count() as f64;
+    let (both, only_habit, only_other, neither) = (
+        count((true, true)),
+        count((true, false)),
+        count((false, true)),
+        count((false, false)),
+    );
+    let with_days = both + only_habit;
+    let without_days = only_other + neither;
+    if with_days < MIN_GROUP_DAYS as f64 || without_days < MIN_GROUP_DAYS as f64 {
+        return None;
+    }
+
+    let denominator =
+        (with_days * without_days * (both + only_other) * (only_habit + neither)).sqrt();
+    if denominator == 0.0 {
+        return None;
+    }
+    Some(Insight {
+        habit: habit.to_string(),
+        other: other.to_string(),
+        days: days.len(),
+        with_rate: both / with_days,
+        without_rate: only_other / without_days,
+        correlation: (both * neither - only_habit * only_other) / denominator,
+    })
+}
+
+/// The insights into the habits of the user with `user_id` from `start` to `end`.
+pub fn generate(user_id: u32, start: Date, end: Date) -> Result<Vec<Insight>> {
+    let habits = Habit::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+    let entries = HabitEntry::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+    Ok(insights(&habits, &entries, start, end, DEFAULT_MIN_DAYS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::date::Time,
+        models::{Frequency, HabitName},
+    };
+
+    // 2024-05-06 is a Monday
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    fn habit(id: u32, name: HabitName, kind: DurationKind) -> Habit {
+        Habit::new(
+            id,
+            1,
+            name,
+            None,
+            None,
+            None,
+            None,
+            Frequency::new(Some(kind), None, Some(1), None, None),
+            date(1).and_time_local(Time::new(8, 0, 0).unwrap()),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn entries(habit_id: u32, days: impl IntoIterator<Item = u8>) -> Vec<HabitEntry> {
+        days.into_iter()
+            .map(|day| {
+                HabitEntry::new(
+                    habit_id * 100 + u32::from(day),
+                    1,
+                    habit_id,
+                    date(day).and_time_local(Time::new(9, 0, 0).unwrap()),
+                    None,
+                    1,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_insights() {
+        let habits = vec![
+            habit(1, HabitName::Meditation, DurationKind::Daily),
+            habit(2, HabitName::Exercise, DurationKind::Daily),
+            habit(3, HabitName::Reading, DurationKind::Monthly),
+        ];
+        // Meditating on days 1 to 10, exercising on 8 of those and 4 of the
+        // 10 other days
+        let mut all_entries = entries(1, 1..=10);
+        all_entries.extend(entries(2, (1..=8).chain(11..=14)));
+        all_entries.extend(entries(3, 1..=20));
+
+        let insights = insights(&habits, &all_entries, date(1), date(20), 14);
+        assert_eq!(insights.len(), 2);
+        let insight = insights
+            .iter()
+            .find(|insight| insight.habit == "Meditation")
+            .unwrap();
+        assert_eq!(insight.other, "Exercise");
+        assert_eq!(insight.days, 20);
+        assert_eq!(insight.with_rate, 0.8);
+        assert_eq!(insight.without_rate, 0.4);
+        assert!((insight.lift().unwrap() - 1.0).abs() < 1e-9);
+        assert!(insight.correlation > 0.0);
+        assert_eq!(
+            insight.text(),
+            "On days you do Meditation, you're 100% more likely to do Exercise"
+        );
+
+        // Too few days to tell
+        assert!(super::insights(&habits, &all_entries, date(1), date(10), 14).is_empty());
+    }
+}
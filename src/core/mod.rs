@@ -1,13 +1,29 @@
 #[doc(hidden)]
 pub mod application;
+pub mod background;
+pub mod dashboard;
 pub mod database;
 #[doc(hidden)]
 pub mod date;
+pub mod digest;
+pub mod encryption;
+pub mod export;
+pub mod forecast;
+pub mod habit_file;
 pub mod i18n;
+pub mod insights;
+pub mod progress;
+pub mod reminder;
+pub mod report;
+pub mod review;
+pub mod stats;
+pub mod strength;
+pub mod timing;
 pub mod utils;
 
 #[doc(inline)]
 pub use application::Application;
 pub use i18n::*;
 pub mod serialize;
+pub mod sync;
 pub use utils::*;
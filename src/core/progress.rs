@@ -0,0 +1,254 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    core::date::{Date, GDateTimeExt},
+    models::{DurationKind, Frequency, HabitEntry},
+};
+
+/// A single period a habit is tracked over: one due day for daily habits, or
+/// one calendar month for monthly habits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Period {
+    pub start: Date,
+    pub end: Date,
+    pub total: i64,
+    pub completed: bool,
+}
+
+impl Period {
+    pub fn contains(&self, date: Date) -> bool {
+        self.start <= date && date <= self.end
+    }
+}
+
+/// Sums up the entry values per local calendar day.
+pub fn daily_totals(entries: &[HabitEntry]) -> BTreeMap<Date, i64> {
+    let mut totals = BTreeMap::new();
+    for entry in entries {
+        let date = entry.entry_time().to_local().unwrap().date();
+        *totals.entry(date).or_insert(0) += i64::from(entry.value());
+    }
+    totals
+}
+
+/// Splits `start..=end` into the periods of `frequency`, leaving out the days a
+/// daily habit isn't due on. Monthly periods always span their whole month, so
+/// their total also counts days of `totals` outside of the range.
+pub fn periods(
+    frequency: &Frequency,
+    totals: &BTreeMap<Date, i64>,
+    start: Date,
+    end: Date,
+) -> Vec<Period> {
+    match frequency.duration_kind {
+        DurationKind::Daily => Date::range(start, end)
+            .into_iter()
+            .filter(|date| frequency.is_due_on(date.weekday()))
+            .map(|date| {
+                let total = totals.get(&date).copied().unwrap_or(0);
+                Period {
+                    start: date,
+                    end: date,
+                    total,
+                    completed: frequency.is_satisfied(total),
+                }
+            })
+            .collect(),
+        DurationKind::Monthly => {
+            let mut periods = Vec::new();
+            let mut month = start.first_of_month();
+            while month <= end {
+                let last = month.last_of_month();
+                let total = totals.range(month..=last).map(|(_, value)| value).sum();
+                periods.push(Period {
+                    start: month,
+                    end: last,
+                    total,
+                    completed: frequency.is_satisfied(total),
+                });
+                month = last.add_days(1);
+            }
+            periods
+        }
+    }
+}
+
+/// Drops the periods that can't be judged yet: those in the future, and the
+/// one containing `today` unless it's already completed.
+pub fn settled(periods: &[Period], today: Date) -> &[Period] {
+    let mut len = periods
+        .iter()
+        .take_while(|period| period.start <= today)
+        .count();
+    if len > 0 && periods[len - 1].contains(today) && !periods[len - 1].completed {
+        len -= 1;
+    }
+    &periods[..len]
+}
+
+/// Number of completed periods in a row up to `today`.
+pub fn current_streak(periods: &[Period], today: Date) -> u32 {
+    settled(periods, today)
+        .iter()
+        .rev()
+        .take_while(|period| period.completed)
+        .count() as u32
+}
+
+/// Longest run of completed periods.
+pub fn longest_streak(periods: &[Period]) -> u32 {
+    let mut longest = 0;
+    let mut current = 0;
+    for period in periods {
+        if period.completed {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    longest
+}
+
+/// Share of completed periods up to `today`, between 0 and 1. [None] if there
+/// is nothing to judge yet.
+pub fn completion_rate(periods: &[Period], today: Date) -> Option<f64> {
+    let periods = settled(periods, today);
+    if periods.is_empty() {
+        return None;
+    }
+    let completed = periods.iter().filter(|period| period.completed).count();
+    Some(completed as f64 / periods.len() as f64)
+}
+
+/// Where a habit stands on a day.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Status {
+    /// The period containing the day, [None] if a daily habit isn't due on it
+    pub current: Option<Period>,
+    pub streak: u32,
+}
+
+/// The [Status] on `today` of a habit with `frequency` created on `created`,
+/// from its daily `totals`.
+pub fn status(
+    frequency: &Frequency,
+    totals: &BTreeMap<Date, i64>,
+    created: Date,
+    today: Date,
+) -> Status {
+    let periods = periods(frequency, totals, created.min(today), today);
+    Status {
+        current: periods
+            .iter()
+            .find(|period| period.contains(today))
+            .copied(),
+        streak: current_streak(&periods, today),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Compare, UnitSystem, Weekday};
+
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    fn minutes_frequency(weekdays: Option<Vec<Weekday>>) -> Frequency {
+        Frequency::new(
+            Some(DurationKind::Daily),
+            Some(UnitSystem::Minutes),
+            Some(30),
+            weekdays,
+            Some(Compare::GreaterOrEqual),
+        )
+    }
+
+    #[test]
+    fn test_daily_periods() {
+        let totals = BTreeMap::from([(date(1), 30), (date(2), 10), (date(3), 45)]);
+        let periods = periods(&minutes_frequency(None), &totals, date(1), date(4));
+        assert_eq!(periods.len(), 4);
+        assert_eq!(
+            periods.iter().map(|p| p.completed).collect::<Vec<_>>(),
+            vec![true, false, true, false]
+        );
+        assert_eq!(periods[2].total, 45);
+    }
+
+    #[test]
+    fn test_daily_periods_skip_weekdays() {
+        // 2024-05-06 is a Monday
+        let frequency = minutes_frequency(Some(vec![Weekday::Monday, Weekday::Wednesday]));
+        let periods = periods(&frequency, &BTreeMap::new(), date(6), date(12));
+        assert_eq!(
+            periods.iter().map(|p| p.start).collect::<Vec<_>>(),
+            vec![date(6), date(8)]
+        );
+    }
+
+    #[test]
+    fn test_monthly_periods() {
+        let frequency = Frequency::new(
+            Some(DurationKind::Monthly),
+            Some(UnitSystem::Kilometers),
+            Some(100),
+            None,
+            Some(Compare::GreaterOrEqual),
+        );
+        let totals = BTreeMap::from([
+            (date(1), 60),
+            (date(31), 40),
+            (Date::new(2024, 6, 2).unwrap(), 5),
+        ]);
+        let periods = periods(
+            &frequency,
+            &totals,
+            date(15),
+            Date::new(2024, 6, 10).unwrap(),
+        );
+        assert_eq!(periods.len(), 2);
+        assert_eq!(periods[0].start, date(1));
+        assert_eq!(periods[0].end, date(31));
+        assert_eq!(periods[0].total, 100);
+        assert!(periods[0].completed);
+        assert_eq!(periods[1].total, 5);
+        assert!(!periods[1].completed);
+    }
+
+    #[test]
+    fn test_streaks() {
+        let totals = BTreeMap::from([
+            (date(1), 30),
+            (date(2), 30),
+            (date(3), 30),
+            (date(5), 30),
+            (date(6), 30),
+        ]);
+        let periods = periods(&minutes_frequency(None), &totals, date(1), date(7));
+        assert_eq!(longest_streak(&periods), 3);
+        // Today's period isn't done yet, which mustn't break the streak
+        assert_eq!(current_streak(&periods, date(7)), 2);
+        assert_eq!(current_streak(&periods, date(8)), 0);
+        assert_eq!(completion_rate(&periods, date(7)), Some(5.0 / 6.0));
+        assert_eq!(completion_rate(&periods[..0], date(7)), None);
+    }
+
+    #[test]
+    fn test_status() {
+        // 2024-05-06 is a Monday
+        let frequency = minutes_frequency(Some(vec![Weekday::Monday, Weekday::Tuesday]));
+        let totals = BTreeMap::from([(date(6), 30), (date(7), 10)]);
+        let status = status(&frequency, &totals, date(1), date(7));
+        assert_eq!(status.current.unwrap().total, 10);
+        assert!(!status.current.unwrap().completed);
+        // Today isn't over yet
+        assert_eq!(status.streak, 1);
+
+        let not_due = super::status(&frequency, &totals, date(1), date(8));
+        assert_eq!(not_due.current, None);
+        assert_eq!(not_due.streak, 0);
+    }
+}
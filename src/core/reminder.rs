@@ -0,0 +1,704 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use gtk::glib;
+
+use crate::{
+    core::{
+        date::{Date, GDateTimeExt, Time},
+        progress,
+    },
+    models::{Frequency, Habit, HabitEntry},
+};
+
+/// Longest time to sleep before checking again, so that suspending or
+/// changing the system clock can't delay reminders for long.
+const MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Source of the current time, replaceable in tests.
+pub trait Clock {
+    fn now(&self) -> glib::DateTime;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> glib::DateTime {
+        glib::DateTime::local()
+    }
+}
+
+/// Source of how much of a habit was done, replaceable in tests.
+pub trait Progress {
+    /// The total logged in the period of `habit` containing `date`.
+    fn period_total(&self, habit: &Habit, date: Date) -> i64;
+}
+
+/// Reads the [Progress] of habits from their entries in the database.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DatabaseProgress;
+
+impl Progress for DatabaseProgress {
+    fn period_total(&self, habit: &Habit, date: Date) -> i64 {
+        match HabitEntry::find_by_habit(habit.id()) {
+            Ok(entries) => {
+                let totals = progress::daily_totals(&entries);
+                progress::periods(&habit.frequency(), &totals, date, date)
+                    .first()
+                    .map_or(0, |period| period.total)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load entries of habit {}: {}", habit.id(), e);
+                0
+            }
+        }
+    }
+}
+
+/// What a due reminder tells the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderKind {
+    /// The habit still needs doing in its current period.
+    Reminder,
+    /// An abstention habit, like [crate::models::HabitName::NoSugar], is on
+    /// track so far.
+    Encouragement,
+}
+
+impl ReminderKind {
+    /// How to remind of a habit with `frequency` and `total` logged in its
+    /// current period. [None] if there's nothing to say: the target is
+    /// already reached, or an abstention habit was broken.
+    pub fn for_progress(frequency: &Frequency, total: i64) -> Option<Self> {
+        let satisfied = frequency.is_satisfied(total);
+        if frequency.is_abstraction() {
+            satisfied.then_some(Self::Encouragement)
+        } else {
+            (!satisfied).then_some(Self::Reminder)
+        }
+    }
+}
+
+/// The first time after `after` a reminder of `habit` is due. Archived
+/// habits, habits without reminder rules and days neither the habit nor a
+/// rule applies to have none.
+pub fn next_fire_time(habit: &Habit, after: &glib::DateTime) -> Option<glib::DateTime> {
+    if habit.archived() {
+        return None;
+    }
+    let rules = habit.reminder_rules();
+    if rules.is_empty() {
+        return None;
+    }
+
+    let frequency = habit.frequency();
+    let today = after.to_local().unwrap().date();
+    // A week and a day covers every weekday, including today's later times.
+    // Offsets can move yesterday's reminders into today.
+    (-1..=7)
+        .map(|days| today.add_days(days))
+        .filter(|date: &Date| frequency.is_due_on(date.weekday()))
+        .flat_map(|date| {
+            rules
+                .iter()
+                .filter(move |rule| rule.applies_on(date.weekday()))
+                .filter_map(move |rule| rule.fire_time(date))
+        })
+        .filter(|time| time > after)
+        .min()
+}
+
+/// A daily window without notifications, from `start` to `end`. It spans
+/// midnight if `end` is before `start`, e.g. 22:00 to 07:00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: Time,
+    pub end: Time,
+}
+
+impl QuietHours {
+    /// Moves `time` to the end of the quiet hours it falls in, if any.
+    pub fn defer(&self, time: &glib::DateTime) -> glib::DateTime {
+        let local = time.to_local().unwrap();
+        let date = local.date();
+        let time_of_day = Time::new(
+            local.hour() as u8,
+            local.minute() as u8,
+            local.second() as u8,
+        )
+        .unwrap();
+
+        let end_date = if self.start < self.end {
+            (self.start <= time_of_day && time_of_day < self.end).then_some(date)
+        } else if self.start > self.end {
+            if time_of_day >= self.start {
+                Some(date.add_days(1))
+            } else {
+                (time_of_day < self.end).then_some(date)
+            }
+        } else {
+            None
+        };
+        end_date.map_or_else(|| time.clone(), |date| date.and_time_local(self.end))
+    }
+}
+
+/// The first time after `after` a reminder of `habit` is due, once deferred
+/// past `quiet_hours`.
+pub fn next_deferred_fire_time(
+    habit: &Habit,
+    after: &glib::DateTime,
+    quiet_hours: Option<QuietHours>,
+) -> Option<glib::DateTime> {
+    let Some(quiet_hours) = quiet_hours else {
+        return next_fire_time(habit, after);
+    };
+    // Quiet hours are shorter than a day, so a reminder deferred past `after`
+    // was due a day before at the earliest
+    let mut time = after.add_days(-1).unwrap();
+    loop {
+        time = next_fire_time(habit, &time)?;
+        let deferred = quiet_hours.defer(&time);
+        if deferred > *after {
+            return Some(deferred);
+        }
+    }
+}
+
+/// How long "Snooze" delays a reminder.
+pub const SNOOZE_DURATION: Duration = Duration::from_secs(15 * 60);
+
+/// Keeps a timer running for the next reminder of a set of habits and calls
+/// back when reminders are due. Habits already done for their period are
+/// skipped, see [ReminderKind::for_progress].
+///
+/// Snoozed reminders fire once more at the time they were snoozed until,
+/// besides the habit's own reminders. Reminders during the quiet hours are
+/// deferred to their end.
+///
+/// All reminders due at once are passed to the callback together, so they can
+/// be bundled.
+///
+/// The timer only drives [ReminderScheduler::tick], which compares against
+/// the [Clock], so tests can step through time by hand.
+pub struct ReminderScheduler {
+    clock: Box<dyn Clock>,
+    progress: Box<dyn Progress>,
+    habits: RefCell<Vec<Habit>>,
+    snoozes: RefCell<HashMap<u32, glib::DateTime>>,
+    quiet_hours: Cell<Option<QuietHours>>,
+    last_check: RefCell<glib::DateTime>,
+    on_fire: Box<dyn Fn(&[(Habit, ReminderKind)])>,
+    source: RefCell<Option<glib::SourceId>>,
+    running: Cell<bool>,
+}
+
+impl ReminderScheduler {
+    pub fn new(
+        clock: impl Clock + 'static,
+        progress: impl Progress + 'static,
+        on_fire: impl Fn(&[(Habit, ReminderKind)]) + 'static,
+    ) -> Rc<Self> {
+        let now = clock.now();
+        Rc::new(Self {
+            clock: Box::new(clock),
+            progress: Box::new(progress),
+            habits: RefCell::default(),
+            snoozes: RefCell::default(),
+            quiet_hours: Cell::new(None),
+            last_check: RefCell::new(now),
+            on_fire: Box::new(on_fire),
+            source: RefCell::default(),
+            running: Cell::new(false),
+        })
+    }
+
+    /// Replaces the habits to remind of and reschedules.
+    pub fn set_habits(self: &Rc<Self>, habits: Vec<Habit>) {
+        self.habits.replace(habits);
+        self.reschedule();
+    }
+
+    /// Reminds of the habit with `habit_id` again after [SNOOZE_DURATION].
+    pub fn snooze(self: &Rc<Self>, habit_id: u32) {
+        let until = self
+            .clock
+            .now()
+            .add_seconds(SNOOZE_DURATION.as_secs_f64())
+            .unwrap();
+        self.snoozes.borrow_mut().insert(habit_id, until);
+        self.reschedule();
+    }
+
+    /// Restores snoozes, e.g. after a restart. Those that already ran out are
+    /// dropped.
+    pub fn set_snoozes(self: &Rc<Self>, snoozes: impl IntoIterator<Item = (u32, glib::DateTime)>) {
+        let now = self.clock.now();
+        self.snoozes.replace(
+            snoozes
+                .into_iter()
+                .filter(|(_, until)| *until > now)
+                .collect(),
+        );
+        self.reschedule();
+    }
+
+    /// The pending snoozes by habit id, sorted by habit.
+    pub fn snoozes(&self) -> Vec<(u32, glib::DateTime)> {
+        let mut snoozes = self
+            .snoozes
+            .borrow()
+            .iter()
+            .map(|(id, until)| (*id, until.clone()))
+            .collect::<Vec<_>>();
+        snoozes.sort_by_key(|(id, _)| *id);
+        snoozes
+    }
+
+    pub fn set_quiet_hours(self: &Rc<Self>, quiet_hours: Option<QuietHours>) {
+        self.quiet_hours.set(quiet_hours);
+        self.reschedule();
+    }
+
+    // The next reminder of `habit` after `after`, including its snooze
+    fn next_for(&self, habit: &Habit, after: &glib::DateTime) -> Option<glib::DateTime> {
+        let quiet_hours = self.quiet_hours.get();
+        let snooze = self
+            .snoozes
+            .borrow()
+            .get(&habit.id())
+            .map(|until| quiet_hours.map_or_else(|| until.clone(), |quiet| quiet.defer(until)))
+            .filter(|until| until > after);
+        match (next_deferred_fire_time(habit, after, quiet_hours), snooze) {
+            (Some(time), Some(snooze)) => Some(time.min(snooze)),
+            (time, snooze) => time.or(snooze),
+        }
+    }
+
+    /// The next reminder and all habits due at that time.
+    pub fn next(&self) -> Option<(glib::DateTime, Vec<Habit>)> {
+        let now = self.clock.now();
+        let upcoming = self
+            .habits
+            .borrow()
+            .iter()
+            .filter_map(|habit| Some((self.next_for(habit, &now)?, habit.clone())))
+            .collect::<Vec<_>>();
+        let time = upcoming.iter().map(|(time, _)| time.clone()).min()?;
+        let habits = upcoming
+            .into_iter()
+            .filter(|(t, _)| *t == time)
+            .map(|(_, habit)| habit)
+            .collect();
+        Some((time, habits))
+    }
+
+    /// Fires the reminders that came due since the last tick, once per habit.
+    /// Returns the habits reminded of, with what they were reminded of.
+    pub fn tick(&self) -> Vec<(Habit, ReminderKind)> {
+        let now = self.clock.now();
+        let last_check = self.last_check.replace(now.clone());
+        let due = self
+            .habits
+            .borrow()
+            .iter()
+            .filter(|habit| {
+                self.next_for(habit, &last_check)
+                    .map_or(false, |time| time <= now)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        // Snoozes deferred by quiet hours are still pending
+        let quiet_hours = self.quiet_hours.get();
+        self.snoozes.borrow_mut().retain(|_, until| {
+            quiet_hours.map_or_else(|| until.clone(), |quiet| quiet.defer(until)) > now
+        });
+
+        let today = now.to_local().unwrap().date();
+        let fired = due
+            .into_iter()
+            .filter_map(|habit| {
+                let total = self.progress.period_total(&habit, today);
+                let kind = ReminderKind::for_progress(&habit.frequency(), total)?;
+                Some((habit, kind))
+            })
+            .collect::<Vec<_>>();
+        if !fired.is_empty() {
+            (self.on_fire)(&fired);
+        }
+        fired
+    }
+
+    /// Starts the timer. Needs a running main loop.
+    pub fn start(self: &Rc<Self>) {
+        self.running.set(true);
+        self.reschedule();
+    }
+
+    pub fn stop(&self) {
+        self.running.set(false);
+        if let Some(source) = self.source.take() {
+            source.remove();
+        }
+    }
+
+    /// Sets the timer for the next reminder, e.g. after habits changed.
+    pub fn reschedule(self: &Rc<Self>) {
+        if let Some(source) = self.source.take() {
+            source.remove();
+        }
+        if !self.running.get() {
+            return;
+        }
+
+        let wait = self.next().map_or(MAX_WAIT, |(time, _)| {
+            let span = time.difference(&self.clock.now());
+            Duration::from_micros(span.as_microseconds().max(0) as u64).min(MAX_WAIT)
+        });
+        let scheduler = Rc::downgrade(self);
+        let source = glib::timeout_add_local_once(wait, move || {
+            if let Some(scheduler) = Weak::upgrade(&scheduler) {
+                // The source is done, it mustn't be removed again
+                scheduler.source.take();
+                scheduler.tick();
+                scheduler.reschedule();
+            }
+        });
+        self.source.replace(Some(source));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::date::Time,
+        models::{Compare, DurationKind, HabitName, ReminderRule, Weekday},
+    };
+
+    #[derive(Clone)]
+    struct FakeClock(Rc<RefCell<glib::DateTime>>);
+
+    impl FakeClock {
+        fn set(&self, date: Date, time: Time) {
+            self.0.replace(date.and_time_local(time));
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> glib::DateTime {
+            self.0.borrow().clone()
+        }
+    }
+
+    // Period totals by habit id
+    #[derive(Clone, Default)]
+    struct FakeProgress(Rc<RefCell<HashMap<u32, i64>>>);
+
+    impl Progress for FakeProgress {
+        fn period_total(&self, habit: &Habit, _date: Date) -> i64 {
+            self.0.borrow().get(&habit.id()).copied().unwrap_or(0)
+        }
+    }
+
+    // 2024-05-06 is a Monday
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    fn time(hour: u8, minutes: u8) -> Time {
+        Time::new(hour, minutes, 0).unwrap()
+    }
+
+    fn habit(id: u32, weekdays: Vec<Weekday>, times: &[Time], archived: bool) -> Habit {
+        let rules = times.iter().map(|time| ReminderRule::new(*time)).collect();
+        habit_with_rules(id, weekdays, rules, archived)
+    }
+
+    fn habit_with_rules(
+        id: u32,
+        weekdays: Vec<Weekday>,
+        rules: Vec<ReminderRule>,
+        archived: bool,
+    ) -> Habit {
+        Habit::new(
+            id,
+            1,
+            HabitName::Exercise,
+            None,
+            None,
+            None,
+            None,
+            Frequency::new(Some(DurationKind::Daily), None, None, Some(weekdays), None),
+            glib::DateTime::local(),
+            None,
+            rules,
+            None,
+            archived,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_next_fire_time() {
+        let workout = habit(
+            1,
+            vec![Weekday::Monday, Weekday::Wednesday],
+            &[time(18, 0), time(7, 30)],
+            false,
+        );
+        let next = |day, hour, minutes| {
+            next_fire_time(&workout, &date(day).and_time_local(time(hour, minutes)))
+        };
+        assert_eq!(next(6, 7, 0), Some(date(6).and_time_local(time(7, 30))));
+        assert_eq!(next(6, 7, 30), Some(date(6).and_time_local(time(18, 0))));
+        // Skips Tuesday
+        assert_eq!(next(6, 19, 0), Some(date(8).and_time_local(time(7, 30))));
+        // Wraps around the week
+        assert_eq!(next(9, 8, 0), Some(date(13).and_time_local(time(7, 30))));
+
+        let archived = habit(2, vec![Weekday::Monday], &[time(7, 30)], true);
+        assert_eq!(
+            next_fire_time(&archived, &date(6).and_time_local(time(7, 0))),
+            None
+        );
+        let silent = habit(3, vec![Weekday::Monday], &[], false);
+        assert_eq!(
+            next_fire_time(&silent, &date(6).and_time_local(time(7, 0))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_next_fire_time_with_rules() {
+        let every_day = vec![
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+            Weekday::Saturday,
+            Weekday::Sunday,
+        ];
+        let weekdays = every_day[..5].to_vec();
+        let walk = habit_with_rules(
+            1,
+            every_day,
+            vec![
+                ReminderRule::new(time(7, 30)).on(weekdays),
+                ReminderRule::new(time(10, 0)).on(vec![Weekday::Saturday]),
+                // Fifteen minutes ahead of a Sunday morning walk
+                ReminderRule::new(time(0, 10))
+                    .on(vec![Weekday::Sunday])
+                    .with_offset(-15),
+            ],
+            false,
+        );
+        let next = |day, hour, minutes| {
+            next_fire_time(&walk, &date(day).and_time_local(time(hour, minutes)))
+        };
+        assert_eq!(next(6, 7, 0), Some(date(6).and_time_local(time(7, 30))));
+        assert_eq!(next(10, 8, 0), Some(date(11).and_time_local(time(10, 0))));
+        // Saturday evening reminds of Sunday
+        assert_eq!(next(11, 12, 0), Some(date(11).and_time_local(time(23, 55))));
+        assert_eq!(next(11, 23, 56), Some(date(13).and_time_local(time(7, 30))));
+    }
+
+    #[test]
+    fn test_scheduler_fires_with_fake_clock() {
+        let clock = FakeClock(Rc::new(RefCell::new(date(6).and_time_local(time(7, 0)))));
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let scheduler = ReminderScheduler::new(clock.clone(), FakeProgress::default(), {
+            let fired = fired.clone();
+            move |due: &[(Habit, ReminderKind)]| {
+                fired
+                    .borrow_mut()
+                    .extend(due.iter().map(|(habit, _)| habit.id()))
+            }
+        });
+        scheduler.set_habits(vec![
+            habit(
+                1,
+                vec![Weekday::Monday, Weekday::Wednesday],
+                &[time(7, 30)],
+                false,
+            ),
+            habit(2, vec![Weekday::Monday], &[time(7, 30)], true),
+            habit(3, vec![Weekday::Tuesday], &[time(9, 0)], false),
+        ]);
+
+        let (next, habits) = scheduler.next().unwrap();
+        assert_eq!(next, date(6).and_time_local(time(7, 30)));
+        assert_eq!(habits.iter().map(Habit::id).collect::<Vec<_>>(), vec![1]);
+
+        clock.set(date(6), time(7, 29));
+        assert!(scheduler.tick().is_empty());
+        clock.set(date(6), time(7, 31));
+        assert_eq!(scheduler.tick().len(), 1);
+        // Fired only once
+        clock.set(date(6), time(8, 0));
+        assert!(scheduler.tick().is_empty());
+
+        clock.set(date(7), time(10, 0));
+        scheduler.tick();
+        clock.set(date(8), time(7, 30));
+        scheduler.tick();
+        assert_eq!(*fired.borrow(), vec![1, 3, 1]);
+
+        // Rescheduled after habits change
+        scheduler.set_habits(vec![habit(
+            4,
+            vec![Weekday::Thursday],
+            &[time(6, 0)],
+            false,
+        )]);
+        assert_eq!(
+            scheduler.next().unwrap().0,
+            date(9).and_time_local(time(6, 0))
+        );
+    }
+
+    #[test]
+    fn test_snooze() {
+        let clock = FakeClock(Rc::new(RefCell::new(date(6).and_time_local(time(7, 30)))));
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let scheduler = ReminderScheduler::new(clock.clone(), FakeProgress::default(), {
+            let fired = fired.clone();
+            move |due: &[(Habit, ReminderKind)]| {
+                fired
+                    .borrow_mut()
+                    .extend(due.iter().map(|(habit, _)| habit.id()))
+            }
+        });
+        scheduler.set_habits(vec![habit(1, vec![Weekday::Monday], &[time(7, 30)], false)]);
+
+        scheduler.snooze(1);
+        assert_eq!(
+            scheduler.next().unwrap().0,
+            date(6).and_time_local(time(7, 45))
+        );
+        clock.set(date(6), time(7, 40));
+        assert!(scheduler.tick().is_empty());
+        clock.set(date(6), time(7, 46));
+        assert_eq!(scheduler.tick().len(), 1);
+        // Snoozes fire once
+        assert!(scheduler.snoozes().is_empty());
+        clock.set(date(6), time(8, 0));
+        assert!(scheduler.tick().is_empty());
+
+        // Restored snoozes that already ran out are dropped
+        scheduler.set_snoozes([
+            (1, date(6).and_time_local(time(7, 50))),
+            (2, date(6).and_time_local(time(8, 5))),
+        ]);
+        assert_eq!(
+            scheduler.snoozes(),
+            vec![(2, date(6).and_time_local(time(8, 5)))]
+        );
+        assert_eq!(*fired.borrow(), vec![1]);
+    }
+
+    #[test]
+    fn test_reminder_kind() {
+        let reading = Frequency::new(Some(DurationKind::Daily), None, Some(2), None, None);
+        assert_eq!(
+            ReminderKind::for_progress(&reading, 1),
+            Some(ReminderKind::Reminder)
+        );
+        assert_eq!(ReminderKind::for_progress(&reading, 2), None);
+
+        let no_sugar = Frequency::new(
+            Some(DurationKind::Daily),
+            None,
+            Some(0),
+            None,
+            Some(Compare::LessOrEqual),
+        );
+        assert_eq!(
+            ReminderKind::for_progress(&no_sugar, 0),
+            Some(ReminderKind::Encouragement)
+        );
+        assert_eq!(ReminderKind::for_progress(&no_sugar, 1), None);
+    }
+
+    #[test]
+    fn test_scheduler_skips_completed_habits() {
+        let clock = FakeClock(Rc::new(RefCell::new(date(6).and_time_local(time(7, 0)))));
+        let progress = FakeProgress::default();
+        let scheduler = ReminderScheduler::new(clock.clone(), progress.clone(), |_| {});
+        scheduler.set_habits(vec![
+            habit(1, vec![Weekday::Monday], &[time(7, 30)], false),
+            habit(2, vec![Weekday::Monday], &[time(7, 30)], false),
+        ]);
+
+        progress.0.borrow_mut().insert(1, 1);
+        clock.set(date(6), time(7, 31));
+        let fired = scheduler.tick();
+        assert_eq!(
+            fired
+                .iter()
+                .map(|(habit, kind)| (habit.id(), *kind))
+                .collect::<Vec<_>>(),
+            vec![(2, ReminderKind::Reminder)]
+        );
+    }
+
+    #[test]
+    fn test_quiet_hours_defer() {
+        let night = QuietHours {
+            start: time(22, 0),
+            end: time(7, 0),
+        };
+        let at = |day, hour, minutes| date(day).and_time_local(time(hour, minutes));
+        assert_eq!(night.defer(&at(6, 21, 59)), at(6, 21, 59));
+        assert_eq!(night.defer(&at(6, 22, 30)), at(7, 7, 0));
+        assert_eq!(night.defer(&at(7, 6, 0)), at(7, 7, 0));
+        assert_eq!(night.defer(&at(7, 7, 0)), at(7, 7, 0));
+
+        let lunch = QuietHours {
+            start: time(12, 0),
+            end: time(13, 0),
+        };
+        assert_eq!(lunch.defer(&at(6, 12, 15)), at(6, 13, 0));
+        assert_eq!(lunch.defer(&at(6, 13, 15)), at(6, 13, 15));
+    }
+
+    #[test]
+    fn test_scheduler_defers_quiet_hours() {
+        let clock = FakeClock(Rc::new(RefCell::new(date(6).and_time_local(time(21, 0)))));
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let scheduler = ReminderScheduler::new(clock.clone(), FakeProgress::default(), {
+            let fired = fired.clone();
+            move |due: &[(Habit, ReminderKind)]| fired.borrow_mut().push(due.len())
+        });
+        scheduler.set_habits(vec![
+            habit(1, vec![Weekday::Monday], &[time(23, 0)], false),
+            habit(2, vec![Weekday::Tuesday], &[time(6, 30)], false),
+        ]);
+        scheduler.set_quiet_hours(Some(QuietHours {
+            start: time(22, 0),
+            end: time(7, 0),
+        }));
+
+        let (next, habits) = scheduler.next().unwrap();
+        assert_eq!(next, date(7).and_time_local(time(7, 0)));
+        assert_eq!(habits.len(), 2);
+
+        // Not dropped when the original time passes
+        clock.set(date(6), time(23, 30));
+        assert!(scheduler.tick().is_empty());
+        clock.set(date(7), time(6, 45));
+        assert!(scheduler.tick().is_empty());
+        clock.set(date(7), time(7, 0));
+        assert_eq!(scheduler.tick().len(), 2);
+        // Both were passed on at once
+        assert_eq!(*fired.borrow(), vec![2]);
+    }
+}
@@ -0,0 +1,507 @@
+use std::{collections::BTreeMap, fmt::Write, path::Path};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    core::{
+        date::{Date, GDateTimeExt},
+        i18n, i18n_f, progress,
+    },
+    models::{Frequency, Habit, HabitCategory, HabitEntry},
+};
+
+/// The output format of a [Report].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, strum::EnumString, strum::AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+
+    /// Guesses the format from the extension of `path`, falling back to Markdown.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("html") | Some("htm") => ReportFormat::Html,
+            _ => ReportFormat::Markdown,
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HabitNote {
+    pub date: Date,
+    pub text: String,
+}
+
+/// The summary of a single habit over the range of a [Report].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HabitReport {
+    pub name: String,
+    pub categories: Vec<HabitCategory>,
+    pub frequency: Frequency,
+    pub total: i64,
+    pub periods_completed: usize,
+    pub periods_total: usize,
+    pub completion_rate: Option<f64>,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub notes: Vec<HabitNote>,
+}
+
+impl HabitReport {
+    /// Summarises `habit` between `start` and `end`. `entries` should hold all
+    /// entries of the habit, so that streaks and months reaching over the range
+    /// boundaries are computed correctly.
+    pub fn new(habit: &Habit, entries: &[HabitEntry], start: Date, end: Date, today: Date) -> Self {
+        let frequency = habit.frequency();
+        let end = end.min(today);
+        // Nothing can be missed before the habit existed
+        let created = habit.created_at().to_local().unwrap().date();
+        let first = start.max(created);
+
+        let totals = progress::daily_totals(entries);
+        let all_periods = progress::periods(&frequency, &totals, created, end);
+        let periods: Vec<_> = all_periods
+            .iter()
+            .filter(|period| period.end >= first)
+            .copied()
+            .collect();
+        let settled = progress::settled(&periods, today);
+
+        let mut notes = entries
+            .iter()
+            .filter_map(|entry| {
+                let date = entry.entry_time().to_local().unwrap().date();
+                let note = entry.note().filter(|note| !note.trim().is_empty())?;
+                (start <= date && date <= end).then_some(HabitNote { date, text: note })
+            })
+            .collect::<Vec<_>>();
+        notes.sort_by_key(|note| note.date);
+
+        Self {
+            name: habit.display_name(),
+            categories: habit
+                .categories()
+                .filter(|categories| !categories.is_empty())
+                .unwrap_or_else(|| vec![HabitCategory::Other]),
+            total: totals
+                .iter()
+                .filter(|(date, _)| start <= **date && **date <= end)
+                .map(|(_, value)| value)
+                .sum(),
+            periods_completed: settled.iter().filter(|period| period.completed).count(),
+            periods_total: settled.len(),
+            completion_rate: progress::completion_rate(&periods, today),
+            current_streak: progress::current_streak(&all_periods, today),
+            longest_streak: progress::longest_streak(&periods),
+            frequency,
+            notes,
+        }
+    }
+
+    fn completion_text(&self) -> String {
+        match self.completion_rate {
+            Some(rate) => i18n_f(
+                "{}% ({} of {})",
+                &[
+                    &format!("{:.0}", rate * 100.0),
+                    &self.periods_completed.to_string(),
+                    &self.periods_total.to_string(),
+                ],
+            ),
+            None => i18n("Not enough data"),
+        }
+    }
+}
+
+/// A progress report over a date range, meant to be shared as Markdown or HTML.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub start: Date,
+    pub end: Date,
+    pub habits: Vec<HabitReport>,
+}
+
+impl Report {
+    pub fn new(
+        habits: &[Habit],
+        entries: &[HabitEntry],
+        start: Date,
+        end: Date,
+        today: Date,
+    ) -> Self {
+        let habits = habits
+            .iter()
+            .map(|habit| {
+                let entries = entries
+                    .iter()
+                    .filter(|entry| entry.habit_id() == habit.id())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                HabitReport::new(habit, &entries, start, end, today)
+            })
+            .collect();
+
+        Self { start, end, habits }
+    }
+
+    /// Builds the report for all habits of the user with `user_id`.
+    pub fn generate(user_id: u32, start: Date, end: Date) -> Result<Self> {
+        let habits = Habit::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        let entries = HabitEntry::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        Ok(Self::new(&habits, &entries, start, end, Date::today()))
+    }
+
+    /// Habits grouped by their categories. A habit with several categories shows
+    /// up in each of them.
+    pub fn by_category(&self) -> BTreeMap<HabitCategory, Vec<&HabitReport>> {
+        let mut groups: BTreeMap<HabitCategory, Vec<&HabitReport>> = BTreeMap::new();
+        for habit in &self.habits {
+            for category in &habit.categories {
+                groups.entry(*category).or_default().push(habit);
+            }
+        }
+        groups
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    fn title(&self) -> String {
+        i18n("Declutter Progress Report")
+    }
+
+    fn range_text(&self) -> String {
+        format!("{} – {}", format_date(self.start), format_date(self.end))
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# {}\n", self.title()).unwrap();
+        writeln!(out, "_{}_\n", self.range_text()).unwrap();
+
+        if self.habits.is_empty() {
+            writeln!(out, "{}", i18n("No habits to report on.")).unwrap();
+            return out;
+        }
+
+        for (category, habits) in self.by_category() {
+            writeln!(out, "## {}\n", category.label()).unwrap();
+            for habit in habits {
+                writeln!(out, "### {}\n", escape_markdown(&habit.name)).unwrap();
+                writeln!(
+                    out,
+                    "- {}: {}",
+                    i18n("Target"),
+                    habit.frequency.target_text()
+                )
+                .unwrap();
+                writeln!(out, "- {}: {}", i18n("Completion"), habit.completion_text()).unwrap();
+                writeln!(
+                    out,
+                    "- {}: {}",
+                    i18n("Total"),
+                    habit.frequency.unit.format_value(habit.total)
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "- {}: {} · {}: {}",
+                    i18n("Current streak"),
+                    habit.current_streak,
+                    i18n("Longest streak"),
+                    habit.longest_streak
+                )
+                .unwrap();
+                if !habit.notes.is_empty() {
+                    writeln!(out, "\n**{}**\n", i18n("Notes")).unwrap();
+                    for note in &habit.notes {
+                        writeln!(
+                            out,
+                            "- {}: {}",
+                            format_date(note.date),
+                            escape_markdown(&note.text)
+                        )
+                        .unwrap();
+                    }
+                }
+                writeln!(out).unwrap();
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "<!DOCTYPE html>\n<html>\n<head>").unwrap();
+        writeln!(out, "<meta charset=\"utf-8\">").unwrap();
+        writeln!(out, "<title>{}</title>", escape_html(&self.title())).unwrap();
+        writeln!(out, "<style>{}</style>", REPORT_CSS).unwrap();
+        writeln!(out, "</head>\n<body>").unwrap();
+        writeln!(out, "<h1>{}</h1>", escape_html(&self.title())).unwrap();
+        writeln!(
+            out,
+            "<p class=\"range\">{}</p>",
+            escape_html(&self.range_text())
+        )
+        .unwrap();
+
+        if self.habits.is_empty() {
+            writeln!(
+                out,
+                "<p>{}</p>",
+                escape_html(&i18n("No habits to report on."))
+            )
+            .unwrap();
+        }
+
+        for (category, habits) in self.by_category() {
+            writeln!(out, "<h2>{}</h2>", escape_html(&category.label())).unwrap();
+            for habit in habits {
+                writeln!(
+                    out,
+                    "<section>\n<h3>{}</h3>\n<dl>",
+                    escape_html(&habit.name)
+                )
+                .unwrap();
+                for (label, value) in [
+                    (i18n("Target"), habit.frequency.target_text()),
+                    (i18n("Completion"), habit.completion_text()),
+                    (
+                        i18n("Total"),
+                        habit.frequency.unit.format_value(habit.total),
+                    ),
+                    (i18n("Current streak"), habit.current_streak.to_string()),
+                    (i18n("Longest streak"), habit.longest_streak.to_string()),
+                ] {
+                    writeln!(
+                        out,
+                        "<dt>{}</dt><dd>{}</dd>",
+                        escape_html(&label),
+                        escape_html(&value)
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "</dl>").unwrap();
+                if !habit.notes.is_empty() {
+                    writeln!(out, "<h4>{}</h4>\n<ul>", escape_html(&i18n("Notes"))).unwrap();
+                    for note in &habit.notes {
+                        writeln!(
+                            out,
+                            "<li><time>{}</time> {}</li>",
+                            format_date(note.date),
+                            escape_html(&note.text)
+                        )
+                        .unwrap();
+                    }
+                    writeln!(out, "</ul>").unwrap();
+                }
+                writeln!(out, "</section>").unwrap();
+            }
+        }
+        writeln!(out, "</body>\n</html>").unwrap();
+        out
+    }
+}
+
+pub const REPORT_CSS: &str =
+    "body{font-family:sans-serif;max-width:48em;margin:2em auto;padding:0 1em;color:#241f31}\
+.range{color:#5e5c64}\
+section{border:1px solid #deddda;border-radius:8px;padding:0 1em 1em;margin-bottom:1em}\
+dl{display:grid;grid-template-columns:max-content auto;gap:.25em 1em}\
+dt{font-weight:bold}dd{margin:0}";
+
+/// Formats a [Date] as `YYYY-MM-DD`.
+pub fn format_date(date: Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.year(), date.month(), date.day())
+}
+
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escapes `text` to show as is within a line of Markdown, so names and
+/// notes can't start a heading, table or emphasis. Line breaks become spaces.
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::date::Time,
+        models::{Compare, HabitName, UnitSystem},
+    };
+
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    fn habit() -> Habit {
+        Habit::new(
+            1,
+            1,
+            HabitName::Meditation,
+            None,
+            None,
+            Some(vec![HabitCategory::Mind, HabitCategory::Health]),
+            None,
+            Frequency::new(
+                None,
+                Some(UnitSystem::Minutes),
+                Some(10),
+                None,
+                Some(Compare::GreaterOrEqual),
+            ),
+            date(1).and_time_local(Time::new(8, 0, 0).unwrap()),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn entry(id: u32, day: u8, value: i32, note: Option<&str>) -> HabitEntry {
+        HabitEntry::new(
+            id,
+            1,
+            1,
+            date(day).and_time_local(Time::new(9, 0, 0).unwrap()),
+            note.map(str::to_string),
+            value,
+        )
+        .unwrap()
+    }
+
+    fn report() -> Report {
+        let entries = vec![
+            entry(1, 1, 10, None),
+            entry(2, 2, 15, Some("Calm <morning>")),
+            entry(3, 4, 10, None),
+            entry(4, 5, 5, Some("  ")),
+        ];
+        Report::new(&[habit()], &entries, date(1), date(5), date(6))
+    }
+
+    #[test]
+    fn test_habit_report() {
+        let report = report();
+        let habit = &report.habits[0];
+        assert_eq!(habit.name, "Meditation");
+        assert_eq!(habit.total, 40);
+        assert_eq!(habit.periods_total, 5);
+        assert_eq!(habit.periods_completed, 3);
+        assert_eq!(habit.longest_streak, 2);
+        assert_eq!(habit.current_streak, 0);
+        assert_eq!(
+            habit.notes,
+            vec![HabitNote {
+                date: date(2),
+                text: "Calm <morning>".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_by_category() {
+        let report = report();
+        let groups = report.by_category();
+        assert_eq!(
+            groups.keys().copied().collect::<Vec<_>>(),
+            vec![HabitCategory::Mind, HabitCategory::Health]
+        );
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = report().to_markdown();
+        assert!(markdown.contains("_2024-05-01 – 2024-05-05_"));
+        assert!(markdown.contains("## Mind"));
+        assert!(markdown.contains("- Completion: 60% (3 of 5)"));
+        assert!(markdown.contains("- Total: 40 minutes"));
+        assert!(markdown.contains("- 2024-05-02: Calm \\<morning\\>"));
+    }
+
+    #[test]
+    fn test_markdown_escaping() {
+        let habit = habit();
+        habit.set_title(Some("Read | *write* #1\n# [link]".to_string()));
+        let entries = vec![HabitEntry::new(
+            1,
+            1,
+            1,
+            date(2).and_time_local(Time::new(9, 0, 0).unwrap()),
+            Some("a_b\n- c".to_string()),
+            10,
+        )
+        .unwrap()];
+        let markdown = Report::new(&[habit], &entries, date(1), date(5), date(6)).to_markdown();
+        assert!(markdown.contains("### Read \\| \\*write\\* \\#1 \\# \\[link\\]\n"));
+        assert!(markdown.contains("- 2024-05-02: a\\_b - c\n"));
+        assert!(!markdown.contains("\n# ["));
+        assert_eq!(escape_markdown("plain text"), "plain text");
+    }
+
+    #[test]
+    fn test_html() {
+        let html = report().to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h2>Health</h2>"));
+        assert!(html.contains("Calm &lt;morning&gt;"));
+        assert!(!html.contains("<morning>"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.html")),
+            ReportFormat::Html
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.md")),
+            ReportFormat::Markdown
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::{
+    core::{
+        dashboard,
+        date::{Date, GDateTimeExt},
+        i18n, i18n_f, progress,
+        report::{escape_html, escape_markdown, ReportFormat, REPORT_CSS},
+    },
+    models::{DurationKind, Habit, HabitCategory, HabitCategoryMap, HabitEntry, UnitSystem},
+};
+
+/// Number of habits listed with their longest streak.
+pub const MAX_STREAKS: usize = 3;
+
+/// The year the review is offered for on `today`: this one in December,
+/// the one before otherwise.
+pub fn default_year(today: Date) -> u16 {
+    if today.month() == 12 {
+        today.year()
+    } else {
+        today.year() - 1
+    }
+}
+
+/// The last year that is over on `today`, counting the last day of the year
+/// as over already, so the review can be sent on New Year's Eve.
+pub fn finished_year(today: Date) -> u16 {
+    if today.month() == 12 && today.day() == 31 {
+        today.year()
+    } else {
+        today.year() - 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreakHighlight {
+    pub habit: String,
+    pub streak: u32,
+    pub duration_kind: DurationKind,
+}
+
+impl StreakHighlight {
+    pub fn streak_text(&self) -> String {
+        match self.duration_kind {
+            DurationKind::Daily => i18n_f("{} days", &[&self.streak.to_string()]),
+            DurationKind::Monthly => i18n_f("{} months", &[&self.streak.to_string()]),
+        }
+    }
+}
+
+/// A habit's completion rate in the first and the second half of the year.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Improvement {
+    pub habit: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl Improvement {
+    pub fn text(&self) -> String {
+        i18n_f(
+            "From {}% in the first half of the year to {}% in the second",
+            &[
+                &format!("{:.0}", self.before * 100.0),
+                &format!("{:.0}", self.after * 100.0),
+            ],
+        )
+    }
+}
+
+/// Everything logged in one unit, like the kilometers run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitTotal {
+    pub unit: UnitSystem,
+    pub total: i64,
+    /// Names of the habits measured in the unit
+    pub habits: Vec<String>,
+}
+
+impl UnitTotal {
+    /// The total for display, in hours rather than minutes past two hours.
+    pub fn total_text(&self) -> String {
+        if self.unit == UnitSystem::Minutes && self.total >= 120 {
+            i18n_f("{} hours", &[&format!("{:.1}", self.total as f64 / 60.0)])
+        } else {
+            self.unit.format_value(self.total)
+        }
+    }
+}
+
+/// How much of the year's effort went into a category.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryShare {
+    pub category: HabitCategory,
+    /// Share of the entries, between 0 and 1. Entries of a habit in several
+    /// categories are split evenly between them, like on the dashboard.
+    pub share: f64,
+}
+
+/// The review of a user's year: how much was logged, the highlights and
+/// where the effort went.
+#[derive(Debug, Clone, PartialEq)]
+pub struct YearReview {
+    pub year: u16,
+    /// Number of habits with entries in the year
+    pub habits: usize,
+    pub total_entries: usize,
+    /// Number of days with at least one entry
+    pub active_days: usize,
+    /// The longest streaks of the year, longest first
+    pub longest_streaks: Vec<StreakHighlight>,
+    pub most_improved: Option<Improvement>,
+    /// Totals of the habits measured in amounts, per unit
+    pub unit_totals: Vec<UnitTotal>,
+    /// Largest share first
+    pub categories: Vec<CategoryShare>,
+}
+
+impl YearReview {
+    pub fn new(
+        year: u16,
+        habits: &[Habit],
+        entries: &[HabitEntry],
+        map: &HabitCategoryMap,
+        today: Date,
+    ) -> Self {
+        let start = Date::new(year, 1, 1).unwrap();
+        let end = Date::new(year, 12, 31).unwrap().min(today);
+        let second_half = Date::new(year, 7, 1).unwrap();
+
+        let mut review = Self {
+            year,
+            habits: 0,
+            total_entries: 0,
+            active_days: 0,
+            longest_streaks: Vec::new(),
+            most_improved: None,
+            unit_totals: Vec::new(),
+            categories: Vec::new(),
+        };
+        let mut active_days = BTreeSet::new();
+        let mut unit_totals = BTreeMap::<UnitSystem, UnitTotal>::new();
+        let mut categories = BTreeMap::<HabitCategory, f64>::new();
+
+        for habit in habits {
+            let habit_entries = entries
+                .iter()
+                .filter(|entry| entry.habit_id() == habit.id())
+                .filter(|entry| {
+                    let date = entry.entry_time().to_local().unwrap().date();
+                    start <= date && date <= end
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            if habit_entries.is_empty() {
+                continue;
+            }
+            let name = habit.display_name();
+            let frequency = habit.frequency();
+            let totals = progress::daily_totals(&habit_entries);
+            review.habits += 1;
+            review.total_entries += habit_entries.len();
+            active_days.extend(totals.keys().copied());
+
+            let created = habit.created_at().to_local().unwrap().date();
+            let first = start.max(created).min(end);
+            let periods = progress::periods(&frequency, &totals, first, end);
+            let streak = progress::longest_streak(progress::settled(&periods, today));
+            if streak > 0 {
+                review.longest_streaks.push(StreakHighlight {
+                    habit: name.clone(),
+                    streak,
+                    duration_kind: frequency.duration_kind,
+                });
+            }
+
+            let half_rate = |from: Date, to: Date| {
+                let (from, to) = (from.max(first), to.min(end));
+                (from <= to)
+                    .then(|| progress::periods(&frequency, &totals, from, to))
+                    .and_then(|periods| progress::completion_rate(&periods, today))
+            };
+            if let (Some(before), Some(after)) = (
+                half_rate(start, second_half.add_days(-1)),
+                half_rate(second_half, end),
+            ) {
+                let improved = review
+                    .most_improved
+                    .as_ref()
+                    .map_or(true, |best| after - before > best.after - best.before);
+                if after > before && improved {
+                    review.most_improved = Some(Improvement {
+                        habit: name.clone(),
+                        before,
+                        after,
+                    });
+                }
+            }
+
+            if frequency.unit.is_quantity() {
+                let unit_total = unit_totals
+                    .entry(frequency.unit)
+                    .or_insert_with(|| UnitTotal {
+                        unit: frequency.unit,
+                        total: 0,
+                        habits: Vec::new(),
+                    });
+                unit_total.total += totals.values().sum::<i64>();
+                unit_total.habits.push(name);
+            }
+
+            let habit_categories = dashboard::habit_categories(habit, map);
+            let weight = habit_entries.len() as f64 / habit_categories.len() as f64;
+            for category in habit_categories {
+                *categories.entry(category).or_default() += weight;
+            }
+        }
+
+        review.active_days = active_days.len();
+        review
+            .longest_streaks
+            .sort_by(|a, b| b.streak.cmp(&a.streak).then_with(|| a.habit.cmp(&b.habit)));
+        review.longest_streaks.truncate(MAX_STREAKS);
+        review.unit_totals = unit_totals.into_values().collect();
+        review.categories = categories
+            .into_iter()
+            .map(|(category, entries)| CategoryShare {
+                category,
+                share: entries / review.total_entries as f64,
+            })
+            .collect();
+        review
+            .categories
+            .sort_by(|a, b| b.share.total_cmp(&a.share));
+        review
+    }
+
+    /// Builds the review of `year` for all habits of the user with `user_id`.
+    pub fn generate(user_id: u32, year: u16) -> Result<Self> {
+        let habits = Habit::find_all()
+            .map_err(|e| anyhow!("{e}"))?
+            .into_iter()
+            .filter(|habit| habit.user_id() == user_id)
+            .collect::<Vec<_>>();
+        let entries = HabitEntry::find_by_user(user_id).map_err(|e| anyhow!("{e}"))?;
+        Ok(Self::new(
+            year,
+            &habits,
+            &entries,
+            &HabitCategoryMap::default(),
+            Date::today(),
+        ))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_entries == 0
+    }
+
+    pub fn title(&self) -> String {
+        i18n_f("Your {} in Review", &[&self.year.to_string()])
+    }
+
+    /// The headline numbers, as label and value.
+    pub fn overview(&self) -> Vec<(String, String)> {
+        vec![
+            (i18n("Entries"), self.total_entries.to_string()),
+            (i18n("Active days"), self.active_days.to_string()),
+            (i18n("Habits"), self.habits.to_string()),
+        ]
+    }
+
+    fn share_text(share: f64) -> String {
+        i18n_f("{}%", &[&format!("{:.0}", share * 100.0)])
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "# {}\n", self.title()).unwrap();
+        if self.is_empty() {
+            writeln!(out, "{}", i18n("Nothing was logged this year.")).unwrap();
+            return out;
+        }
+
+        for (label, value) in self.overview() {
+            writeln!(out, "- {}: {}", label, value).unwrap();
+        }
+        if !self.longest_streaks.is_empty() {
+            writeln!(out, "\n## {}\n", i18n("Longest Streaks")).unwrap();
+            for highlight in &self.longest_streaks {
+                writeln!(
+                    out,
+                    "- {}: {}",
+                    escape_markdown(&highlight.habit),
+                    highlight.streak_text()
+                )
+                .unwrap();
+            }
+        }
+        if let Some(improvement) = &self.most_improved {
+            writeln!(out, "\n## {}\n", i18n("Most Improved")).unwrap();
+            writeln!(
+                out,
+                "**{}**: {}",
+                escape_markdown(&improvement.habit),
+                improvement.text()
+            )
+            .unwrap();
+        }
+        if !self.unit_totals.is_empty() {
+            writeln!(out, "\n## {}\n", i18n("Totals")).unwrap();
+            for total in &self.unit_totals {
+                writeln!(
+                    out,
+                    "- {} ({})",
+                    total.total_text(),
+                    escape_markdown(&total.habits.join(", "))
+                )
+                .unwrap();
+            }
+        }
+        writeln!(out, "\n## {}\n", i18n("Category Balance")).unwrap();
+        for share in &self.categories {
+            writeln!(
+                out,
+                "- {}: {}",
+                share.category.label(),
+                Self::share_text(share.share)
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        writeln!(out, "<!DOCTYPE html>\n<html>\n<head>").unwrap();
+        writeln!(out, "<meta charset=\"utf-8\">").unwrap();
+        writeln!(out, "<title>{}</title>", escape_html(&self.title())).unwrap();
+        writeln!(out, "<style>{}{}</style>", REPORT_CSS, REVIEW_CSS).unwrap();
+        writeln!(out, "</head>\n<body>").unwrap();
+        writeln!(out, "<h1>{}</h1>", escape_html(&self.title())).unwrap();
+        if self.is_empty() {
+            writeln!(
+                out,
+                "<p>{}</p>",
+                escape_html(&i18n("Nothing was logged this year."))
+            )
+            .unwrap();
+            writeln!(out, "</body>\n</html>").unwrap();
+            return out;
+        }
+
+        writeln!(out, "<dl>").unwrap();
+        for (label, value) in self.overview() {
+            writeln!(
+                out,
+                "<dt>{}</dt><dd>{}</dd>",
+                escape_html(&label),
+                escape_html(&value)
+            )
+            .unwrap();
+        }
+        writeln!(out, "</dl>").unwrap();
+
+        if !self.longest_streaks.is_empty() {
+            writeln!(
+                out,
+                "<h2>{}</h2>\n<ol>",
+                escape_html(&i18n("Longest Streaks"))
+            )
+            .unwrap();
+            for highlight in &self.longest_streaks {
+                writeln!(
+                    out,
+                    "<li>{}: {}</li>",
+                    escape_html(&highlight.habit),
+                    escape_html(&highlight.streak_text())
+                )
+                .unwrap();
+            }
+            writeln!(out, "</ol>").unwrap();
+        }
+        if let Some(improvement) = &self.most_improved {
+            writeln!(out, "<h2>{}</h2>", escape_html(&i18n("Most Improved"))).unwrap();
+            writeln!(
+                out,
+                "<p><strong>{}</strong>: {}</p>",
+                escape_html(&improvement.habit),
+                escape_html(&improvement.text())
+            )
+            .unwrap();
+        }
+        if !self.unit_totals.is_empty() {
+            writeln!(out, "<h2>{}</h2>\n<ul>", escape_html(&i18n("Totals"))).unwrap();
+            for total in &self.unit_totals {
+                writeln!(
+                    out,
+                    "<li>{} ({})</li>",
+                    escape_html(&total.total_text()),
+                    escape_html(&total.habits.join(", "))
+                )
+                .unwrap();
+            }
+            writeln!(out, "</ul>").unwrap();
+        }
+        writeln!(
+            out,
+            "<h2>{}</h2>\n<dl>",
+            escape_html(&i18n("Category Balance"))
+        )
+        .unwrap();
+        for share in &self.categories {
+            writeln!(
+                out,
+                "<dt>{}</dt><dd><meter value=\"{:.2}\"></meter> {}</dd>",
+                escape_html(&share.category.label()),
+                share.share,
+                escape_html(&Self::share_text(share.share))
+            )
+            .unwrap();
+        }
+        writeln!(out, "</dl>\n</body>\n</html>").unwrap();
+        out
+    }
+}
+
+const REVIEW_CSS: &str = "meter{width:10em;vertical-align:middle}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::date::Time,
+        models::{Frequency, HabitName},
+    };
+
+    fn date(month: u8, day: u8) -> Date {
+        Date::new(2024, month, day).unwrap()
+    }
+
+    fn habit(id: u32, name: HabitName, unit: UnitSystem, categories: Vec<HabitCategory>) -> Habit {
+        Habit::new(
+            id,
+            1,
+            name,
+            None,
+            None,
+            Some(categories),
+            None,
+            Frequency::new(Some(DurationKind::Daily), Some(unit), Some(1), None, None),
+            Date::new(2023, 6, 1)
+                .unwrap()
+                .and_time_local(Time::new(8, 0, 0).unwrap()),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn entries(habit_id: u32, days: impl IntoIterator<Item = Date>, value: i32) -> Vec<HabitEntry> {
+        days.into_iter()
+            .map(|day| {
+                HabitEntry::new(
+                    0,
+                    1,
+                    habit_id,
+                    day.and_time_local(Time::new(9, 0, 0).unwrap()),
+                    None,
+                    value,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_default_year() {
+        assert_eq!(default_year(date(12, 1)), 2024);
+        assert_eq!(default_year(date(1, 5)), 2023);
+        assert_eq!(finished_year(date(12, 30)), 2023);
+        assert_eq!(finished_year(date(12, 31)), 2024);
+    }
+
+    #[test]
+    fn test_year_review() {
+        let habits = vec![
+            habit(
+                1,
+                HabitName::Running,
+                UnitSystem::Kilometers,
+                vec![HabitCategory::Body],
+            ),
+            habit(
+                2,
+                HabitName::Meditation,
+                UnitSystem::Minutes,
+                vec![HabitCategory::Mind, HabitCategory::Health],
+            ),
+        ];
+        // Running 5 km a day for the first ten days of the year, and the
+        // same days of the year before
+        let mut all_entries = entries(1, Date::range(date(1, 1), date(1, 10)), 5);
+        all_entries.extend(entries(
+            1,
+            Date::range(
+                Date::new(2023, 12, 1).unwrap(),
+                Date::new(2023, 12, 10).unwrap(),
+            ),
+            5,
+        ));
+        // Meditating 20 minutes on ten days of the first half of the year and
+        // 30 in a row in the second
+        all_entries.extend(entries(2, (1..=10).map(|day| date(3, day * 2)), 20));
+        all_entries.extend(entries(2, Date::range(date(9, 1), date(9, 30)), 20));
+
+        let review = YearReview::new(
+            2024,
+            &habits,
+            &all_entries,
+            &HabitCategoryMap::default(),
+            date(12, 31),
+        );
+        assert_eq!(review.habits, 2);
+        assert_eq!(review.total_entries, 50);
+        assert_eq!(review.active_days, 50);
+        assert_eq!(
+            review.longest_streaks,
+            vec![
+                StreakHighlight {
+                    habit: "Meditation".to_string(),
+                    streak: 30,
+                    duration_kind: DurationKind::Daily,
+                },
+                StreakHighlight {
+                    habit: "Running".to_string(),
+                    streak: 10,
+                    duration_kind: DurationKind::Daily,
+                },
+            ]
+        );
+        let improvement = review.most_improved.as_ref().unwrap();
+        assert_eq!(improvement.habit, "Meditation");
+        assert!(improvement.after > improvement.before);
+
+        assert_eq!(review.unit_totals.len(), 2);
+        assert_eq!(review.unit_totals[0].unit, UnitSystem::Minutes);
+        assert_eq!(review.unit_totals[0].total_text(), "13.3 hours");
+        assert_eq!(review.unit_totals[1].total, 50);
+
+        // 40 meditation entries split between two categories, 10 running
+        assert_eq!(review.categories[0].share, 0.4);
+        assert_eq!(review.categories[2].category, HabitCategory::Body);
+        assert_eq!(review.categories[2].share, 0.2);
+
+        let markdown = review.to_markdown();
+        assert!(markdown.starts_with("# Your 2024 in Review\n"));
+        assert!(markdown.contains("- Meditation: 30 days\n"));
+        assert!(markdown.contains("- 50 kilometers (Running)\n"));
+        assert!(review.to_html().contains("<li>Meditation: 30 days</li>"));
+    }
+
+    #[test]
+    fn test_empty_year() {
+        let review = YearReview::new(2024, &[], &[], &HabitCategoryMap::default(), date(6, 1));
+        assert!(review.is_empty());
+        assert!(review.categories.is_empty());
+        assert!(review.to_markdown().contains("Nothing was logged"));
+    }
+}
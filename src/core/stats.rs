@@ -0,0 +1,477 @@
+use std::collections::BTreeMap;
+
+use diesel::{
+    prelude::*,
+    sql_types::{BigInt, Integer, Text},
+};
+use gtk::glib;
+
+use crate::{
+    core::{
+        date::{Date, GDateTimeExt},
+        i18n, progress,
+    },
+    models::{Frequency, Habit, HabitEntry, UnitSystem, Weekday},
+};
+
+/// A span of time statistics are computed over, ending today.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StatsRange {
+    /// The last 7 days
+    Week,
+    /// The last 30 days
+    Month,
+    /// The last 365 days
+    Year,
+    AllTime,
+}
+
+impl StatsRange {
+    pub const ALL: [StatsRange; 4] = [
+        StatsRange::Week,
+        StatsRange::Month,
+        StatsRange::Year,
+        StatsRange::AllTime,
+    ];
+
+    pub fn label(&self) -> String {
+        match self {
+            StatsRange::Week => i18n("Week"),
+            StatsRange::Month => i18n("Month"),
+            StatsRange::Year => i18n("Year"),
+            StatsRange::AllTime => i18n("All Time"),
+        }
+    }
+
+    /// The first day of the range ending on `today`. Nothing before
+    /// `first_day`, e.g. the day the habit was created, is covered.
+    pub fn start(&self, today: Date, first_day: Date) -> Date {
+        let start = match self {
+            StatsRange::Week => today.add_days(-6),
+            StatsRange::Month => today.add_days(-29),
+            StatsRange::Year => today.add_days(-364),
+            StatsRange::AllTime => first_day,
+        };
+        start.max(first_day).min(today)
+    }
+}
+
+/// The total and averages of the values logged over a range of days.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ValueSummary {
+    pub unit: UnitSystem,
+    pub total: i64,
+    pub entries: usize,
+    pub days: usize,
+}
+
+impl ValueSummary {
+    pub fn average_per_entry(&self) -> Option<f64> {
+        (self.entries > 0).then(|| self.total as f64 / self.entries as f64)
+    }
+
+    pub fn average_per_day(&self) -> Option<f64> {
+        (self.days > 0).then(|| self.total as f64 / self.days as f64)
+    }
+
+    pub fn total_text(&self) -> String {
+        self.unit.format_value(self.total)
+    }
+
+    /// The average per day in the unit, rounded, e.g. `25 minutes`.
+    pub fn average_per_day_text(&self) -> Option<String> {
+        self.average_per_day()
+            .map(|average| self.unit.format_value(average.round() as i64))
+    }
+}
+
+/// Share of completed periods of `frequency` from `start` to `today`, see
+/// [progress::completion_rate].
+pub fn completion_rate(
+    frequency: &Frequency,
+    totals: &BTreeMap<Date, i64>,
+    start: Date,
+    today: Date,
+) -> Option<f64> {
+    let periods = progress::periods(frequency, totals, start, today);
+    progress::completion_rate(&periods, today)
+}
+
+/// The completion rate of a habit created on `created` over each [StatsRange].
+pub fn completion_rates(
+    frequency: &Frequency,
+    totals: &BTreeMap<Date, i64>,
+    created: Date,
+    today: Date,
+) -> BTreeMap<StatsRange, Option<f64>> {
+    StatsRange::ALL
+        .into_iter()
+        .map(|range| {
+            let start = range.start(today, created);
+            (range, completion_rate(frequency, totals, start, today))
+        })
+        .collect()
+}
+
+/// Sums up the values of the entries made from `start` to `end`.
+pub fn value_summary(
+    entries: &[HabitEntry],
+    unit: UnitSystem,
+    start: Date,
+    end: Date,
+) -> ValueSummary {
+    let in_range = entries
+        .iter()
+        .map(|entry| (entry_date(entry), entry.value()))
+        .filter(|(date, _)| start <= *date && *date <= end)
+        .collect::<Vec<_>>();
+    ValueSummary {
+        unit,
+        total: in_range.iter().map(|(_, value)| i64::from(*value)).sum(),
+        entries: in_range.len(),
+        days: (start.days_until(&end) + 1).max(0) as usize,
+    }
+}
+
+/// Sums up the entry values per weekday.
+pub fn weekday_totals(entries: &[HabitEntry]) -> BTreeMap<Weekday, i64> {
+    let mut totals = BTreeMap::new();
+    for entry in entries {
+        let weekday = Weekday::from(entry.entry_time().to_local().unwrap());
+        *totals.entry(weekday).or_insert(0) += i64::from(entry.value());
+    }
+    totals
+}
+
+// The weekdays a habit is due on, with their totals, missing ones being zero
+fn due_weekday_totals(
+    frequency: &Frequency,
+    totals: &BTreeMap<Weekday, i64>,
+) -> Vec<(Weekday, i64)> {
+    Weekday::range(Weekday::Monday, Weekday::Sunday)
+        .into_iter()
+        .filter(|weekday| frequency.is_due_on(*weekday))
+        .map(|weekday| (weekday, totals.get(&weekday).copied().unwrap_or(0)))
+        .collect()
+}
+
+/// The due weekday with the most logged, the earliest in the week on ties.
+/// [None] if nothing was logged.
+pub fn best_weekday(frequency: &Frequency, totals: &BTreeMap<Weekday, i64>) -> Option<Weekday> {
+    if totals.values().all(|total| *total == 0) {
+        return None;
+    }
+    due_weekday_totals(frequency, totals)
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, total)| *total)
+        .map(|(weekday, _)| weekday)
+}
+
+/// The due weekday with the least logged, the earliest in the week on ties.
+/// [None] if nothing was logged.
+pub fn worst_weekday(frequency: &Frequency, totals: &BTreeMap<Weekday, i64>) -> Option<Weekday> {
+    if totals.values().all(|total| *total == 0) {
+        return None;
+    }
+    due_weekday_totals(frequency, totals)
+        .into_iter()
+        .min_by_key(|(_, total)| *total)
+        .map(|(weekday, _)| weekday)
+}
+
+fn entry_date(entry: &HabitEntry) -> Date {
+    entry.entry_time().to_local().unwrap().date()
+}
+
+/// All statistics of a single habit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HabitStats {
+    pub completion_rates: BTreeMap<StatsRange, Option<f64>>,
+    /// Values over all time
+    pub values: ValueSummary,
+    pub best_weekday: Option<Weekday>,
+    pub worst_weekday: Option<Weekday>,
+}
+
+impl HabitStats {
+    /// Computes the statistics from all `entries` of `habit`.
+    pub fn new(habit: &Habit, entries: &[HabitEntry], today: Date) -> Self {
+        let frequency = habit.frequency();
+        let created = created_date(habit, today);
+        let totals = progress::daily_totals(entries);
+        let weekdays = weekday_totals(entries);
+        Self {
+            completion_rates: completion_rates(&frequency, &totals, created, today),
+            values: value_summary(entries, frequency.unit, created, today),
+            best_weekday: best_weekday(&frequency, &weekdays),
+            worst_weekday: worst_weekday(&frequency, &weekdays),
+        }
+    }
+
+    /// Like [HabitStats::new], but lets the database add up the entries
+    /// instead of loading them, for habits with long histories.
+    pub fn load(conn: &mut SqliteConnection, habit: &Habit, today: Date) -> QueryResult<Self> {
+        let frequency = habit.frequency();
+        let created = created_date(habit, today);
+        let totals = sql::daily_totals(conn, habit.id())?;
+        let weekdays = sql::weekday_totals(conn, habit.id())?;
+        Ok(Self {
+            completion_rates: completion_rates(&frequency, &totals, created, today),
+            values: sql::value_summary(conn, habit.id(), frequency.unit, created, today)?,
+            best_weekday: best_weekday(&frequency, &weekdays),
+            worst_weekday: worst_weekday(&frequency, &weekdays),
+        })
+    }
+}
+
+// Entries may predate the habit, e.g. after an import, but not today
+fn created_date(habit: &Habit, today: Date) -> Date {
+    let created: glib::DateTime = habit.created_at().to_local().unwrap();
+    created.date().min(today)
+}
+
+/// SQL versions of the statistics above, aggregating in the database.
+///
+/// Entry times are stored in the local time of when they were made, so their
+/// first ten characters are the local date.
+pub mod sql {
+    use super::*;
+
+    #[derive(QueryableByName)]
+    struct DayTotal {
+        #[diesel(sql_type = Text)]
+        day: String,
+        #[diesel(sql_type = BigInt)]
+        total: i64,
+    }
+
+    #[derive(QueryableByName)]
+    struct WeekdayTotal {
+        /// 0 is Sunday, as in SQLite's `strftime('%w')`
+        #[diesel(sql_type = Integer)]
+        weekday: i32,
+        #[diesel(sql_type = BigInt)]
+        total: i64,
+    }
+
+    #[derive(QueryableByName)]
+    struct Sum {
+        #[diesel(sql_type = BigInt)]
+        total: i64,
+        #[diesel(sql_type = BigInt)]
+        entries: i64,
+    }
+
+    /// Like [progress::daily_totals], for the entries of the habit with `habit_id`.
+    pub fn daily_totals(
+        conn: &mut SqliteConnection,
+        habit_id: u32,
+    ) -> QueryResult<BTreeMap<Date, i64>> {
+        let rows = diesel::sql_query(
+            "SELECT substr(entry_time, 1, 10) AS day, SUM(value) AS total \
+             FROM habit_entry WHERE habit_id = ? GROUP BY day",
+        )
+        .bind::<Integer, _>(habit_id as i32)
+        .load::<DayTotal>(conn)?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| Some((Date::parse(&row.day).ok()?, row.total)))
+            .collect())
+    }
+
+    /// Like [super::weekday_totals], for the entries of the habit with `habit_id`.
+    pub fn weekday_totals(
+        conn: &mut SqliteConnection,
+        habit_id: u32,
+    ) -> QueryResult<BTreeMap<Weekday, i64>> {
+        let rows = diesel::sql_query(
+            "SELECT CAST(strftime('%w', substr(entry_time, 1, 10)) AS INTEGER) AS weekday, \
+             SUM(value) AS total \
+             FROM habit_entry WHERE habit_id = ? GROUP BY weekday",
+        )
+        .bind::<Integer, _>(habit_id as i32)
+        .load::<WeekdayTotal>(conn)?;
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let weekday = Weekday::Sunday.next_n(row.weekday.clamp(0, 6) as u32);
+                (weekday, row.total)
+            })
+            .collect())
+    }
+
+    /// Like [super::value_summary], for the entries of the habit with `habit_id`.
+    pub fn value_summary(
+        conn: &mut SqliteConnection,
+        habit_id: u32,
+        unit: UnitSystem,
+        start: Date,
+        end: Date,
+    ) -> QueryResult<ValueSummary> {
+        let sum = diesel::sql_query(
+            "SELECT COALESCE(SUM(value), 0) AS total, COUNT(*) AS entries \
+             FROM habit_entry WHERE habit_id = ? \
+             AND substr(entry_time, 1, 10) BETWEEN ? AND ?",
+        )
+        .bind::<Integer, _>(habit_id as i32)
+        .bind::<Text, _>(start.to_iso8601())
+        .bind::<Text, _>(end.to_iso8601())
+        .get_result::<Sum>(conn)?;
+        Ok(ValueSummary {
+            unit,
+            total: sum.total,
+            entries: sum.entries as usize,
+            days: (start.days_until(&end) + 1).max(0) as usize,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{database, date::Time},
+        models::{DurationKind, HabitName},
+        schema::{habit, habit_entry},
+    };
+
+    // 2024-05-06 is a Monday
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::new(
+            Some(DurationKind::Daily),
+            Some(UnitSystem::Minutes),
+            Some(20),
+            Some(vec![
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+            ]),
+            None,
+        )
+    }
+
+    fn habit() -> Habit {
+        Habit::new(
+            1,
+            1,
+            HabitName::Running,
+            None,
+            None,
+            None,
+            None,
+            frequency(),
+            date(1).and_time_local(Time::new(8, 0, 0).unwrap()),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    // Minutes run per day of May 2024
+    const RUNS: [(u8, i32); 6] = [(1, 30), (2, 10), (6, 25), (7, 20), (8, 40), (13, 30)];
+
+    fn entries() -> Vec<HabitEntry> {
+        RUNS.iter()
+            .enumerate()
+            .map(|(id, (day, value))| {
+                HabitEntry::new(
+                    id as u32,
+                    1,
+                    1,
+                    date(*day).and_time_local(Time::new(7, 0, 0).unwrap()),
+                    None,
+                    *value,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(StatsRange::Week.start(date(14), date(1)), date(8));
+        assert_eq!(StatsRange::Month.start(date(14), date(1)), date(1));
+        assert_eq!(StatsRange::AllTime.start(date(14), date(1)), date(1));
+    }
+
+    #[test]
+    fn test_habit_stats() {
+        let stats = HabitStats::new(&habit(), &entries(), date(14));
+        // Week: 8th to 14th, due on 8th, 9th, 10th, 13th and 14th; the 14th
+        // isn't over yet
+        assert_eq!(stats.completion_rates[&StatsRange::Week], Some(0.5));
+        // All time: due on 1st-3rd, 6th-10th and 13th, completed on 1st, 6th,
+        // 7th, 8th and 13th
+        assert_eq!(
+            stats.completion_rates[&StatsRange::AllTime],
+            Some(5.0 / 9.0)
+        );
+
+        assert_eq!(stats.values.total, 155);
+        assert_eq!(stats.values.entries, 6);
+        assert_eq!(stats.values.days, 14);
+        assert_eq!(stats.values.total_text(), "155 minutes");
+        assert_eq!(
+            stats.values.average_per_day_text().as_deref(),
+            Some("11 minutes")
+        );
+
+        // Wednesdays: 30 + 40, Mondays: 25 + 30, Tuesdays: 20, Thursdays: 10
+        assert_eq!(stats.best_weekday, Some(Weekday::Wednesday));
+        assert_eq!(stats.worst_weekday, Some(Weekday::Friday));
+    }
+
+    #[test]
+    fn test_weekdays_without_entries() {
+        assert_eq!(best_weekday(&frequency(), &BTreeMap::new()), None);
+        assert_eq!(worst_weekday(&frequency(), &BTreeMap::new()), None);
+    }
+
+    #[test]
+    fn test_sql_matches_pure() {
+        let dir = tempdir::TempDir::new("stats").unwrap();
+        let mut conn = database::open(&dir.path().join("declutter.db")).unwrap();
+        diesel::insert_into(habit::table)
+            .values((
+                habit::id.eq(1),
+                habit::user_id.eq(1),
+                habit::name.eq("running"),
+                habit::frequency.eq(serde_json::to_string(&frequency()).unwrap()),
+                habit::created_at.eq("2024-05-01T08:00:00Z"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        for entry in entries() {
+            diesel::insert_into(habit_entry::table)
+                .values((
+                    habit_entry::user_id.eq(1),
+                    habit_entry::habit_id.eq(1),
+                    habit_entry::entry_time.eq(entry
+                        .entry_time()
+                        .format_iso8601()
+                        .unwrap()
+                        .to_string()),
+                    habit_entry::value.eq(entry.value()),
+                ))
+                .execute(&mut conn)
+                .unwrap();
+        }
+
+        let habit = habit();
+        assert_eq!(
+            HabitStats::load(&mut conn, &habit, date(14)).unwrap(),
+            HabitStats::new(&habit, &entries(), date(14))
+        );
+    }
+}
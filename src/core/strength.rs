@@ -0,0 +1,249 @@
+//! The strength of a habit: an exponentially weighted moving average of how
+//! well its periods went, so that a single miss weakens a habit instead of
+//! resetting it like a streak. Works like the score of Loop Habit Tracker.
+
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use diesel::prelude::*;
+
+use crate::{
+    core::{
+        database,
+        date::{Date, GDateTimeExt},
+        progress::{self, Period},
+        stats,
+    },
+    models::{Compare, DurationKind, Frequency, Habit},
+    schema::habit_strength,
+};
+
+/// Number of daily periods after which a period counts half as much.
+const DAILY_HALF_LIFE: f64 = 13.0;
+
+/// How much of the previous strength is kept per period of `kind`.
+///
+/// The half-life grows with the square root of the period length, so a
+/// monthly habit needs fewer periods than a daily one to grow strong, but
+/// more time.
+pub fn multiplier(kind: DurationKind) -> f64 {
+    let days = match kind {
+        DurationKind::Daily => 1.0_f64,
+        DurationKind::Monthly => 30.0,
+    };
+    0.5_f64.powf(days.sqrt() / DAILY_HALF_LIFE)
+}
+
+/// How well `period` went, from 0 to 1. Habits with a minimum to reach get
+/// partial credit for getting part of the way.
+pub fn period_score(frequency: &Frequency, period: &Period) -> f64 {
+    if period.completed {
+        return 1.0;
+    }
+    match frequency.comparator {
+        Compare::Greater | Compare::GreaterOrEqual if frequency.target_value > 0 => {
+            (period.total as f64 / f64::from(frequency.target_value)).clamp(0.0, 1.0)
+        }
+        _ => 0.0,
+    }
+}
+
+/// The strength after `periods`, starting from `score`.
+pub fn advance(score: f64, frequency: &Frequency, periods: &[Period]) -> f64 {
+    let multiplier = multiplier(frequency.duration_kind);
+    periods.iter().fold(score, |score, period| {
+        score * multiplier + period_score(frequency, period) * (1.0 - multiplier)
+    })
+}
+
+/// The periods up to `today` split into the closed ones and the one
+/// containing `today`, which only counts once it's completed.
+fn split_periods(periods: Vec<Period>, today: Date) -> (Vec<Period>, Option<Period>) {
+    let (closed, open): (Vec<_>, Vec<_>) = periods
+        .into_iter()
+        .filter(|period| period.start <= today)
+        .partition(|period| period.end < today);
+    (closed, open.into_iter().find(|period| period.completed))
+}
+
+/// The strength from 0 to 1 of a habit created on `created`, from its daily
+/// `totals`.
+pub fn strength(
+    frequency: &Frequency,
+    totals: &BTreeMap<Date, i64>,
+    created: Date,
+    today: Date,
+) -> f64 {
+    let periods = progress::periods(frequency, totals, created.min(today), today);
+    let (closed, current) = split_periods(periods, today);
+    advance(
+        advance(0.0, frequency, &closed),
+        frequency,
+        current.as_slice(),
+    )
+}
+
+/// Like [strength], but starts from the strength cached in the database and
+/// caches it again as of the last closed period. Entries changed on or before
+/// the cached day clear the cache.
+pub fn load(conn: &mut SqliteConnection, habit: &Habit, today: Date) -> QueryResult<f64> {
+    let frequency = habit.frequency();
+    let created = habit.created_at().to_local().unwrap().date().min(today);
+    let cached = habit_strength::table
+        .find(habit.id() as i32)
+        .select((habit_strength::day, habit_strength::score))
+        .first::<(String, f64)>(conn)
+        .optional()?
+        .and_then(|(day, score)| Some((Date::parse(&day).ok()?, score)));
+    let (start, score) = match cached {
+        Some((day, score)) if day < today => (day.add_days(1), score),
+        _ => (created, 0.0),
+    };
+
+    let totals = stats::sql::daily_totals(conn, habit.id())?
+        .into_iter()
+        .filter(|(date, _)| *date >= start.first_of_month())
+        .collect();
+    let periods = progress::periods(&frequency, &totals, start, today);
+    let (closed, current) = split_periods(periods, today);
+    let score = advance(score, &frequency, &closed);
+    if let Some(last) = closed.last() {
+        diesel::replace_into(habit_strength::table)
+            .values((
+                habit_strength::habit_id.eq(habit.id() as i32),
+                habit_strength::day.eq(last.end.to_iso8601()),
+                habit_strength::score.eq(score),
+            ))
+            .execute(conn)?;
+    }
+    Ok(advance(score, &frequency, current.as_slice()))
+}
+
+/// The strength of `habit` today, using the cache of the database.
+pub fn habit_strength(habit: &Habit) -> Result<f64> {
+    let mut conn = database::connection().get()?;
+    load(&mut conn, habit, Date::today()).map_err(|e| anyhow!("{e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::date::Time,
+        models::{HabitName, UnitSystem},
+        schema::habit_entry,
+    };
+
+    // 2024-05-06 is a Monday
+    fn date(day: u8) -> Date {
+        Date::new(2024, 5, day).unwrap()
+    }
+
+    fn frequency() -> Frequency {
+        Frequency::new(
+            Some(DurationKind::Daily),
+            Some(UnitSystem::Minutes),
+            Some(20),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_multiplier() {
+        let daily = multiplier(DurationKind::Daily);
+        assert!((daily.powf(DAILY_HALF_LIFE) - 0.5).abs() < 1e-9);
+        assert!(multiplier(DurationKind::Monthly) < daily);
+    }
+
+    #[test]
+    fn test_strength() {
+        let frequency = frequency();
+        let every_day = Date::range(date(1), date(30))
+            .into_iter()
+            .map(|date| (date, 20))
+            .collect::<BTreeMap<_, _>>();
+        let strong = strength(&frequency, &every_day, date(1), date(31));
+        assert!(strong > 0.75);
+        // Not having done it yet today doesn't count against the habit
+        assert_eq!(strong, strength(&frequency, &every_day, date(1), date(30)));
+
+        // A single miss only weakens the habit a bit
+        let mut one_miss = every_day.clone();
+        one_miss.remove(&date(29));
+        let weakened = strength(&frequency, &one_miss, date(1), date(31));
+        assert!(weakened < strong);
+        assert!(weakened > 0.7);
+
+        // Half the target is worth half a completion
+        let halves = every_day
+            .keys()
+            .map(|date| (*date, 10))
+            .collect::<BTreeMap<_, _>>();
+        let half = strength(&frequency, &halves, date(1), date(31));
+        assert!((half - strong / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_load_caches() {
+        let dir = tempdir::TempDir::new("strength").unwrap();
+        let mut conn = database::open(&dir.path().join("declutter.db")).unwrap();
+        let habit = Habit::new(
+            1,
+            1,
+            HabitName::Running,
+            None,
+            None,
+            None,
+            None,
+            frequency(),
+            date(1).and_time_local(Time::new(8, 0, 0).unwrap()),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut totals = BTreeMap::new();
+        let mut add_entry = |conn: &mut SqliteConnection, day: u8, value: i32| {
+            let entry_time = date(day).and_time_local(Time::new(7, 0, 0).unwrap());
+            diesel::insert_into(habit_entry::table)
+                .values((
+                    habit_entry::user_id.eq(1),
+                    habit_entry::habit_id.eq(1),
+                    habit_entry::entry_time.eq(entry_time.format_iso8601().unwrap().to_string()),
+                    habit_entry::value.eq(value),
+                ))
+                .execute(conn)
+                .unwrap();
+            *totals.entry(date(day)).or_insert(0) += i64::from(value);
+            totals.clone()
+        };
+        let cached_day = |conn: &mut SqliteConnection| {
+            habit_strength::table
+                .select(habit_strength::day)
+                .first::<String>(conn)
+                .optional()
+                .unwrap()
+        };
+
+        for day in 1..=9 {
+            add_entry(&mut conn, day, 20);
+        }
+        let totals = add_entry(&mut conn, 12, 20);
+        let expected = strength(&frequency(), &totals, date(1), date(12));
+        assert_eq!(load(&mut conn, &habit, date(12)).unwrap(), expected);
+        assert_eq!(cached_day(&mut conn).as_deref(), Some("2024-05-11"));
+        // Served from the cache
+        assert!((load(&mut conn, &habit, date(12)).unwrap() - expected).abs() < 1e-12);
+
+        // Logging a past day clears the cache
+        let totals = add_entry(&mut conn, 10, 20);
+        assert_eq!(cached_day(&mut conn), None);
+        let expected = strength(&frequency(), &totals, date(1), date(13));
+        assert!((load(&mut conn, &habit, date(13)).unwrap() - expected).abs() < 1e-12);
+        assert_eq!(cached_day(&mut conn).as_deref(), Some("2024-05-12"));
+    }
+}
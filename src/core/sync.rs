@@ -0,0 +1,1029 @@
+//! Syncing between devices through a shared folder.
+//!
+//! Every device appends the records it changed to its own change log,
+//! `<device id>.jsonl`, in the sync folder, and merges the new lines of the
+//! other devices' logs into its database. Records are matched by their UUIDs.
+//! When both sides changed a record, the change with the later `updated_at`
+//! wins, with ties going to the device with the greater id so that all devices
+//! agree. Deletions are synced as tombstones.
+//!
+//! Only the folder is shared, the syncing itself (e.g. by Syncthing or a cloud
+//! drive) is left to other tools.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use anyhow::{anyhow, Result};
+use diesel::prelude::*;
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::date::GDateTimeExt,
+    schema::{habit, habit_entry, sync_cursor, sync_deletion, sync_state, user},
+};
+
+const LOG_EXTENSION: &str = "jsonl";
+const DEVICE_ID_KEY: &str = "device_id";
+const LAST_EXPORT_KEY: &str = "last_export";
+
+pub fn new_uuid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// The current time, formatted as it's stored in the database.
+pub fn now() -> String {
+    glib::DateTime::local()
+        .format_iso8601()
+        .unwrap()
+        .to_string()
+}
+
+/// Leaves a tombstone for the record with `uuid` in `table`, so that the
+/// deletion reaches the other devices.
+pub fn record_deletion(
+    conn: &mut SqliteConnection,
+    table: &str,
+    uuid: Option<String>,
+) -> QueryResult<()> {
+    let Some(uuid) = uuid else {
+        return Ok(());
+    };
+    diesel::replace_into(sync_deletion::table)
+        .values((
+            sync_deletion::uuid.eq(uuid),
+            sync_deletion::table_name.eq(table),
+            sync_deletion::deleted_at.eq(now()),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub uuid: String,
+    pub name: String,
+    pub date_of_birth: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HabitRecord {
+    pub uuid: String,
+    pub user_uuid: String,
+    pub name: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub categories: Option<String>,
+    pub icon: Option<String>,
+    pub frequency: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub reminder_rules: Option<String>,
+    pub note: Option<String>,
+    pub archived: bool,
+    pub archived_date: Option<String>,
+    pub archived_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HabitEntryRecord {
+    pub uuid: String,
+    pub user_uuid: String,
+    pub habit_uuid: String,
+    pub entry_time: String,
+    pub note: Option<String>,
+    pub value: i32,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deletion {
+    pub uuid: String,
+    pub table: String,
+    pub deleted_at: String,
+}
+
+/// A line of a change log: the state of a record after it changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Change {
+    User(UserRecord),
+    Habit(HabitRecord),
+    HabitEntry(HabitEntryRecord),
+    Deletion(Deletion),
+}
+
+impl Change {
+    pub fn uuid(&self) -> &str {
+        match self {
+            Change::User(record) => &record.uuid,
+            Change::Habit(record) => &record.uuid,
+            Change::HabitEntry(record) => &record.uuid,
+            Change::Deletion(deletion) => &deletion.uuid,
+        }
+    }
+
+    pub fn timestamp(&self) -> &str {
+        match self {
+            Change::User(record) => &record.updated_at,
+            Change::Habit(record) => &record.updated_at,
+            Change::HabitEntry(record) => &record.updated_at,
+            Change::Deletion(deletion) => &deletion.deleted_at,
+        }
+    }
+
+    // Records must be merged after the records they refer to
+    fn merge_order(&self) -> u8 {
+        match self {
+            Change::User(_) => 0,
+            Change::Habit(_) => 1,
+            Change::HabitEntry(_) => 2,
+            Change::Deletion(_) => 3,
+        }
+    }
+}
+
+/// What a [sync] did.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncSummary {
+    /// Local changes written to this device's log
+    pub exported: usize,
+    /// Changes of other devices applied to the database
+    pub merged: usize,
+}
+
+#[derive(Queryable)]
+struct UserRow {
+    id: i32,
+    name: String,
+    date_of_birth: String,
+    created_at: String,
+    uuid: Option<String>,
+    updated_at: Option<String>,
+}
+
+#[derive(Queryable)]
+struct HabitRow {
+    id: i32,
+    user_id: i32,
+    name: String,
+    description: Option<String>,
+    categories: Option<String>,
+    icon: Option<String>,
+    frequency: String,
+    created_at: String,
+    updated_at: Option<String>,
+    reminder_rules: Option<String>,
+    note: Option<String>,
+    archived: i32,
+    archived_date: Option<String>,
+    archived_reason: Option<String>,
+    title: Option<String>,
+    uuid: Option<String>,
+}
+
+#[derive(Queryable)]
+struct HabitEntryRow {
+    #[allow(dead_code)]
+    id: i32,
+    user_id: i32,
+    habit_id: i32,
+    entry_time: String,
+    note: Option<String>,
+    value: i32,
+    uuid: Option<String>,
+    updated_at: Option<String>,
+}
+
+/// Writes the local changes since the last sync to this device's log in `dir`
+/// and merges the changes of all other devices into the database.
+pub fn sync(conn: &mut SqliteConnection, dir: &Path) -> Result<SyncSummary> {
+    fs::create_dir_all(dir)?;
+    let device_id = device_id(conn)?;
+    ensure_uuids(conn)?;
+    let last_export = state(conn, LAST_EXPORT_KEY)?;
+
+    let mut remote_changes = Vec::new();
+    let mut cursors = Vec::new();
+    for path in fs::read_dir(dir)?.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().map_or(true, |ext| ext != LOG_EXTENSION) {
+            continue;
+        }
+        let Some(remote_device) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        if remote_device == device_id {
+            continue;
+        }
+        let position = cursor(conn, remote_device)?;
+        let (changes, new_position) = read_log(&path, position)?;
+        remote_changes.extend(
+            changes
+                .into_iter()
+                .map(|change| (remote_device.to_string(), change)),
+        );
+        cursors.push((remote_device.to_string(), new_position));
+    }
+
+    if last_export.is_none() {
+        adopt_user(conn, &remote_changes)?;
+    }
+
+    // Export before merging, so that merged changes aren't echoed back
+    let export_time = now();
+    let changes = local_changes(conn, last_export.as_deref())?;
+    append_log(&dir.join(format!("{device_id}.{LOG_EXTENSION}")), &changes)?;
+    set_state(conn, LAST_EXPORT_KEY, &export_time)?;
+
+    remote_changes.sort_by_key(|(_, change)| change.merge_order());
+    let merged = conn.transaction(|conn| {
+        let mut merged = 0;
+        for (remote_device, change) in &remote_changes {
+            let wins_ties = remote_device.as_str() > device_id.as_str();
+            if merge(conn, change, wins_ties)? {
+                merged += 1;
+            }
+        }
+        for (remote_device, position) in &cursors {
+            diesel::replace_into(sync_cursor::table)
+                .values((
+                    sync_cursor::device_id.eq(remote_device),
+                    sync_cursor::position.eq(*position as i32),
+                ))
+                .execute(conn)?;
+        }
+        Ok::<_, anyhow::Error>(merged)
+    })?;
+
+    Ok(SyncSummary {
+        exported: changes.len(),
+        merged,
+    })
+}
+
+fn state(conn: &mut SqliteConnection, key: &str) -> QueryResult<Option<String>> {
+    sync_state::table
+        .filter(sync_state::key.eq(key))
+        .select(sync_state::value)
+        .first(conn)
+        .optional()
+}
+
+fn set_state(conn: &mut SqliteConnection, key: &str, value: &str) -> QueryResult<()> {
+    diesel::replace_into(sync_state::table)
+        .values((sync_state::key.eq(key), sync_state::value.eq(value)))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// The id of this database in sync folders, created on first use.
+pub fn device_id(conn: &mut SqliteConnection) -> QueryResult<String> {
+    if let Some(device_id) = state(conn, DEVICE_ID_KEY)? {
+        return Ok(device_id);
+    }
+    let device_id = new_uuid();
+    set_state(conn, DEVICE_ID_KEY, &device_id)?;
+    Ok(device_id)
+}
+
+fn cursor(conn: &mut SqliteConnection, device_id: &str) -> QueryResult<usize> {
+    sync_cursor::table
+        .filter(sync_cursor::device_id.eq(device_id))
+        .select(sync_cursor::position)
+        .first::<i32>(conn)
+        .optional()
+        .map(|position| position.unwrap_or(0) as usize)
+}
+
+// Records created before the UUID columns existed, or outside of the models
+fn ensure_uuids(conn: &mut SqliteConnection) -> QueryResult<()> {
+    let ids: Vec<i32> = user::table
+        .filter(user::uuid.is_null())
+        .select(user::id)
+        .load(conn)?;
+    for id in ids {
+        diesel::update(user::table.find(id))
+            .set(user::uuid.eq(new_uuid()))
+            .execute(conn)?;
+    }
+    let ids: Vec<i32> = habit::table
+        .filter(habit::uuid.is_null())
+        .select(habit::id)
+        .load(conn)?;
+    for id in ids {
+        diesel::update(habit::table.find(id))
+            .set(habit::uuid.eq(new_uuid()))
+            .execute(conn)?;
+    }
+    let ids: Vec<i32> = habit_entry::table
+        .filter(habit_entry::uuid.is_null())
+        .select(habit_entry::id)
+        .load(conn)?;
+    for id in ids {
+        diesel::update(habit_entry::table.find(id))
+            .set(habit_entry::uuid.eq(new_uuid()))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// On the first sync of a device, its user is taken to be the same person as
+/// the user of the other devices if both sides have exactly one.
+fn adopt_user(conn: &mut SqliteConnection, remote_changes: &[(String, Change)]) -> Result<()> {
+    let local_users: Vec<(i32, Option<String>)> =
+        user::table.select((user::id, user::uuid)).load(conn)?;
+    let remote_users = remote_changes
+        .iter()
+        .filter_map(|(_, change)| match change {
+            Change::User(record) => Some(record.uuid.as_str()),
+            _ => None,
+        })
+        .collect::<BTreeSet<_>>();
+
+    if let ([(id, uuid)], [remote_uuid]) = (
+        local_users.as_slice(),
+        remote_users.into_iter().collect::<Vec<_>>().as_slice(),
+    ) {
+        if uuid.as_deref() != Some(*remote_uuid) {
+            tracing::info!("Linking local user {} to synced user {}", id, remote_uuid);
+            diesel::update(user::table.find(*id))
+                .set(user::uuid.eq(*remote_uuid))
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_timestamp(timestamp: &str) -> Option<glib::DateTime> {
+    glib::DateTime::from_iso8601(timestamp, None).ok()
+}
+
+fn compare_timestamps(a: &str, b: &str) -> Ordering {
+    match (parse_timestamp(a), parse_timestamp(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+fn is_newer(remote: &str, local: &str, wins_ties: bool) -> bool {
+    match compare_timestamps(remote, local) {
+        Ordering::Greater => true,
+        Ordering::Equal => wins_ties,
+        Ordering::Less => false,
+    }
+}
+
+/// All records changed at or after `since`, or all records if [None].
+fn local_changes(conn: &mut SqliteConnection, since: Option<&str>) -> Result<Vec<Change>> {
+    let since = since.and_then(parse_timestamp);
+    let changed = |timestamp: &str| match (&since, parse_timestamp(timestamp)) {
+        (Some(since), Some(timestamp)) => timestamp >= *since,
+        _ => true,
+    };
+
+    let users = user::table.load::<UserRow>(conn)?;
+    let habits = habit::table.load::<HabitRow>(conn)?;
+    let entries = habit_entry::table.load::<HabitEntryRow>(conn)?;
+    let deletions = sync_deletion::table.load::<(String, String, String)>(conn)?;
+
+    let user_uuids: HashMap<i32, String> = users
+        .iter()
+        .filter_map(|row| Some((row.id, row.uuid.clone()?)))
+        .collect();
+    let habit_uuids: HashMap<i32, String> = habits
+        .iter()
+        .filter_map(|row| Some((row.id, row.uuid.clone()?)))
+        .collect();
+
+    let mut changes = Vec::new();
+    for row in users {
+        let (Some(uuid), updated_at) = (row.uuid, row.updated_at.unwrap_or(row.created_at.clone()))
+        else {
+            continue;
+        };
+        if changed(&updated_at) {
+            changes.push(Change::User(UserRecord {
+                uuid,
+                name: row.name,
+                date_of_birth: row.date_of_birth,
+                created_at: row.created_at,
+                updated_at,
+            }));
+        }
+    }
+    for row in habits {
+        let updated_at = row.updated_at.unwrap_or(row.created_at.clone());
+        let (Some(uuid), Some(user_uuid)) = (row.uuid, user_uuids.get(&row.user_id)) else {
+            continue;
+        };
+        if changed(&updated_at) {
+            changes.push(Change::Habit(HabitRecord {
+                uuid,
+                user_uuid: user_uuid.clone(),
+                name: row.name,
+                title: row.title,
+                description: row.description,
+                categories: row.categories,
+                icon: row.icon,
+                frequency: row.frequency,
+                created_at: row.created_at,
+                updated_at,
+                reminder_rules: row.reminder_rules,
+                note: row.note,
+                archived: row.archived != 0,
+                archived_date: row.archived_date,
+                archived_reason: row.archived_reason,
+            }));
+        }
+    }
+    for row in entries {
+        let updated_at = row.updated_at.unwrap_or(row.entry_time.clone());
+        let (Some(uuid), Some(user_uuid), Some(habit_uuid)) = (
+            row.uuid,
+            user_uuids.get(&row.user_id),
+            habit_uuids.get(&row.habit_id),
+        ) else {
+            continue;
+        };
+        if changed(&updated_at) {
+            changes.push(Change::HabitEntry(HabitEntryRecord {
+                uuid,
+                user_uuid: user_uuid.clone(),
+                habit_uuid: habit_uuid.clone(),
+                entry_time: row.entry_time,
+                note: row.note,
+                value: row.value,
+                updated_at,
+            }));
+        }
+    }
+    for (uuid, table, deleted_at) in deletions {
+        if changed(&deleted_at) {
+            changes.push(Change::Deletion(Deletion {
+                uuid,
+                table,
+                deleted_at,
+            }));
+        }
+    }
+    Ok(changes)
+}
+
+fn append_log(path: &Path, changes: &[Change]) -> Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let mut lines = String::new();
+    for change in changes {
+        lines.push_str(&serde_json::to_string(change)?);
+        lines.push('\n');
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Reads the changes after the first `position` lines of the log at `path`.
+/// Returns them with the position to continue from next time.
+fn read_log(path: &Path, position: usize) -> Result<(Vec<Change>, usize)> {
+    let content = fs::read_to_string(path)?;
+    let mut changes = Vec::new();
+    let mut lines = 0;
+    // A line without a newline may still be in transfer, so it's left for later
+    for line in content
+        .split_inclusive('\n')
+        .filter(|line| line.ends_with('\n'))
+    {
+        lines += 1;
+        if lines <= position || line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(change) => changes.push(change),
+            Err(e) => tracing::warn!("Skipping change {} of {}: {}", lines, path.display(), e),
+        }
+    }
+    Ok((changes, lines.max(position)))
+}
+
+fn is_deleted_since(conn: &mut SqliteConnection, uuid: &str, timestamp: &str) -> Result<bool> {
+    let deleted_at = sync_deletion::table
+        .filter(sync_deletion::uuid.eq(uuid))
+        .select(sync_deletion::deleted_at)
+        .first::<String>(conn)
+        .optional()?;
+    Ok(deleted_at.map_or(false, |deleted_at| {
+        compare_timestamps(&deleted_at, timestamp) != Ordering::Less
+    }))
+}
+
+fn local_user_id(conn: &mut SqliteConnection, uuid: &str) -> QueryResult<Option<i32>> {
+    user::table
+        .filter(user::uuid.eq(uuid))
+        .select(user::id)
+        .first(conn)
+        .optional()
+}
+
+fn local_habit_id(conn: &mut SqliteConnection, uuid: &str) -> QueryResult<Option<i32>> {
+    habit::table
+        .filter(habit::uuid.eq(uuid))
+        .select(habit::id)
+        .first(conn)
+        .optional()
+}
+
+/// Applies a change of another device if it's newer than the local state.
+/// Returns whether the database changed.
+fn merge(conn: &mut SqliteConnection, change: &Change, wins_ties: bool) -> Result<bool> {
+    if let Change::Deletion(deletion) = change {
+        return merge_deletion(conn, deletion, wins_ties);
+    }
+    if is_deleted_since(conn, change.uuid(), change.timestamp())? {
+        return Ok(false);
+    }
+
+    match change {
+        Change::User(record) => {
+            let local = user::table
+                .filter(user::uuid.eq(&record.uuid))
+                .select((user::id, user::updated_at, user::created_at))
+                .first::<(i32, Option<String>, String)>(conn)
+                .optional()?;
+            let values = (
+                user::name.eq(&record.name),
+                user::date_of_birth.eq(&record.date_of_birth),
+                user::created_at.eq(&record.created_at),
+                user::updated_at.eq(&record.updated_at),
+            );
+            match local {
+                Some((id, updated_at, created_at)) => {
+                    let local_updated_at = updated_at.unwrap_or(created_at);
+                    if !is_newer(&record.updated_at, &local_updated_at, wins_ties) {
+                        return Ok(false);
+                    }
+                    diesel::update(user::table.find(id))
+                        .set(values)
+                        .execute(conn)?;
+                }
+                None => {
+                    diesel::insert_into(user::table)
+                        .values((values, user::uuid.eq(&record.uuid)))
+                        .execute(conn)?;
+                }
+            }
+        }
+        Change::Habit(record) => {
+            let Some(user_id) = local_user_id(conn, &record.user_uuid)? else {
+                tracing::warn!("Skipping habit {} of unknown user", record.uuid);
+                return Ok(false);
+            };
+            let local = habit::table
+                .filter(habit::uuid.eq(&record.uuid))
+                .select((habit::id, habit::updated_at, habit::created_at))
+                .first::<(i32, Option<String>, String)>(conn)
+                .optional()?;
+            let values = (
+                habit::user_id.eq(user_id),
+                habit::name.eq(&record.name),
+                habit::title.eq(&record.title),
+                habit::description.eq(&record.description),
+                habit::categories.eq(&record.categories),
+                habit::icon.eq(&record.icon),
+                habit::frequency.eq(&record.frequency),
+                habit::created_at.eq(&record.created_at),
+                habit::updated_at.eq(&record.updated_at),
+                habit::reminder_rules.eq(&record.reminder_rules),
+                habit::note.eq(&record.note),
+                habit::archived.eq(i32::from(record.archived)),
+                habit::archived_date.eq(&record.archived_date),
+                habit::archived_reason.eq(&record.archived_reason),
+            );
+            match local {
+                Some((id, updated_at, created_at)) => {
+                    let local_updated_at = updated_at.unwrap_or(created_at);
+                    if !is_newer(&record.updated_at, &local_updated_at, wins_ties) {
+                        return Ok(false);
+                    }
+                    diesel::update(habit::table.find(id))
+                        .set(values)
+                        .execute(conn)?;
+                }
+                None => {
+                    diesel::insert_into(habit::table)
+                        .values((values, habit::uuid.eq(&record.uuid)))
+                        .execute(conn)?;
+                }
+            }
+        }
+        Change::HabitEntry(record) => {
+            let (Some(user_id), Some(habit_id)) = (
+                local_user_id(conn, &record.user_uuid)?,
+                local_habit_id(conn, &record.habit_uuid)?,
+            ) else {
+                tracing::warn!("Skipping entry {} of unknown habit", record.uuid);
+                return Ok(false);
+            };
+            // Stored in local time, like the entries made on this device
+            let Some(entry_time) = glib::DateTime::from_iso8601(&record.entry_time, None)
+                .ok()
+                .and_then(|entry_time| entry_time.format_iso8601_local().ok())
+            else {
+                tracing::warn!("Skipping entry {} with invalid time", record.uuid);
+                return Ok(false);
+            };
+            let local = habit_entry::table
+                .filter(habit_entry::uuid.eq(&record.uuid))
+                .select((
+                    habit_entry::id,
+                    habit_entry::updated_at,
+                    habit_entry::entry_time,
+                ))
+                .first::<(i32, Option<String>, String)>(conn)
+                .optional()?;
+            let values = (
+                habit_entry::user_id.eq(user_id),
+                habit_entry::habit_id.eq(habit_id),
+                habit_entry::entry_time.eq(entry_time.as_str()),
+                habit_entry::note.eq(&record.note),
+                habit_entry::value.eq(record.value),
+                habit_entry::updated_at.eq(&record.updated_at),
+            );
+            match local {
+                Some((id, updated_at, entry_time)) => {
+                    let local_updated_at = updated_at.unwrap_or(entry_time);
+                    if !is_newer(&record.updated_at, &local_updated_at, wins_ties) {
+                        return Ok(false);
+                    }
+                    diesel::update(habit_entry::table.find(id))
+                        .set(values)
+                        .execute(conn)?;
+                }
+                None => {
+                    diesel::insert_into(habit_entry::table)
+                        .values((values, habit_entry::uuid.eq(&record.uuid)))
+                        .execute(conn)?;
+                }
+            }
+        }
+        Change::Deletion(_) => unreachable!(),
+    }
+    Ok(true)
+}
+
+fn merge_deletion(
+    conn: &mut SqliteConnection,
+    deletion: &Deletion,
+    wins_ties: bool,
+) -> Result<bool> {
+    let uuid = deletion.uuid.as_str();
+    let local = match deletion.table.as_str() {
+        "user" => user::table
+            .filter(user::uuid.eq(uuid))
+            .select((user::id, user::updated_at, user::created_at))
+            .first::<(i32, Option<String>, String)>(conn)
+            .optional()?,
+        "habit" => habit::table
+            .filter(habit::uuid.eq(uuid))
+            .select((habit::id, habit::updated_at, habit::created_at))
+            .first::<(i32, Option<String>, String)>(conn)
+            .optional()?,
+        "habit_entry" => habit_entry::table
+            .filter(habit_entry::uuid.eq(uuid))
+            .select((
+                habit_entry::id,
+                habit_entry::updated_at,
+                habit_entry::entry_time,
+            ))
+            .first::<(i32, Option<String>, String)>(conn)
+            .optional()?,
+        table => return Err(anyhow!("Deletion in unknown table {}", table)),
+    };
+
+    let mut changed = false;
+    if let Some((id, updated_at, created_at)) = local {
+        // A record changed after it was deleted elsewhere is kept
+        if !is_newer(
+            &deletion.deleted_at,
+            &updated_at.unwrap_or(created_at),
+            wins_ties,
+        ) {
+            return Ok(false);
+        }
+        match deletion.table.as_str() {
+            "user" => diesel::delete(user::table.find(id)).execute(conn)?,
+            "habit" => diesel::delete(habit::table.find(id)).execute(conn)?,
+            _ => diesel::delete(habit_entry::table.find(id)).execute(conn)?,
+        };
+        changed = true;
+    }
+
+    // Keep the tombstone, so that older changes of the record can't bring it back
+    diesel::replace_into(sync_deletion::table)
+        .values((
+            sync_deletion::uuid.eq(uuid),
+            sync_deletion::table_name.eq(&deletion.table),
+            sync_deletion::deleted_at.eq(&deletion.deleted_at),
+        ))
+        .execute(conn)?;
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::{
+            database,
+            export::{DataExport, ExportedEntry, ExportedHabit, ExportedUser},
+        },
+        models::{Frequency, HabitName},
+    };
+
+    struct Device {
+        conn: SqliteConnection,
+        _dir: tempdir::TempDir,
+    }
+
+    impl Device {
+        fn new() -> Self {
+            let dir = tempdir::TempDir::new("device").unwrap();
+            let conn = database::open(&dir.path().join("declutter.db")).unwrap();
+            Self { conn, _dir: dir }
+        }
+
+        fn add_user(&mut self, created_at: &str) -> i32 {
+            diesel::insert_into(user::table)
+                .values((
+                    user::name.eq("User"),
+                    user::date_of_birth.eq("2000-01-01T00:00:00Z"),
+                    user::created_at.eq(created_at),
+                    user::uuid.eq(new_uuid()),
+                ))
+                .execute(&mut self.conn)
+                .unwrap();
+            user::table
+                .select(user::id)
+                .order(user::id.desc())
+                .first(&mut self.conn)
+                .unwrap()
+        }
+
+        fn add_habit(&mut self, user_id: i32, title: &str) -> i32 {
+            diesel::insert_into(habit::table)
+                .values((
+                    habit::user_id.eq(user_id),
+                    habit::name.eq("custom"),
+                    habit::title.eq(title),
+                    habit::frequency.eq("{}"),
+                    habit::created_at.eq("2024-01-01T00:00:00Z"),
+                    habit::uuid.eq(new_uuid()),
+                ))
+                .execute(&mut self.conn)
+                .unwrap();
+            habit::table
+                .select(habit::id)
+                .order(habit::id.desc())
+                .first(&mut self.conn)
+                .unwrap()
+        }
+
+        fn add_entry(&mut self, user_id: i32, habit_id: i32, entry_time: &str, value: i32) {
+            diesel::insert_into(habit_entry::table)
+                .values((
+                    habit_entry::user_id.eq(user_id),
+                    habit_entry::habit_id.eq(habit_id),
+                    habit_entry::entry_time.eq(entry_time),
+                    habit_entry::value.eq(value),
+                    habit_entry::uuid.eq(new_uuid()),
+                ))
+                .execute(&mut self.conn)
+                .unwrap();
+        }
+
+        fn rename_habit(&mut self, title: &str, updated_at: &str) {
+            diesel::update(habit::table)
+                .set((habit::title.eq(title), habit::updated_at.eq(updated_at)))
+                .execute(&mut self.conn)
+                .unwrap();
+        }
+
+        fn titles(&mut self) -> Vec<Option<String>> {
+            habit::table
+                .select(habit::title)
+                .order(habit::title)
+                .load(&mut self.conn)
+                .unwrap()
+        }
+
+        fn entry_times(&mut self) -> Vec<String> {
+            habit_entry::table
+                .select(habit_entry::entry_time)
+                .order(habit_entry::entry_time)
+                .load(&mut self.conn)
+                .unwrap()
+        }
+
+        fn entry_values(&mut self) -> Vec<i32> {
+            habit_entry::table
+                .select(habit_entry::value)
+                .order(habit_entry::value)
+                .load(&mut self.conn)
+                .unwrap()
+        }
+
+        fn sync(&mut self, dir: &Path) -> SyncSummary {
+            sync(&mut self.conn, dir).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_sync_two_devices() {
+        let folder = tempdir::TempDir::new("sync").unwrap();
+        let mut desktop = Device::new();
+        let mut laptop = Device::new();
+
+        let user_id = desktop.add_user("2024-01-01T00:00:00Z");
+        let habit_id = desktop.add_habit(user_id, "Deep work");
+        desktop.add_entry(user_id, habit_id, "2024-05-01T09:00:00Z", 30);
+        desktop.add_entry(user_id, habit_id, "2024-05-02T09:00:00Z", 45);
+        // Set up before the first sync, the desktop's user is taken over
+        let laptop_user_id = laptop.add_user("2023-06-01T00:00:00Z");
+        let laptop_habit_id = laptop.add_habit(laptop_user_id, "Reading");
+        laptop.add_entry(laptop_user_id, laptop_habit_id, "2024-05-02T21:00:00Z", 20);
+
+        assert_eq!(desktop.sync(folder.path()).exported, 4);
+        let summary = laptop.sync(folder.path());
+        assert_eq!(summary.exported, 3);
+        assert_eq!(summary.merged, 4);
+        assert_eq!(desktop.sync(folder.path()).merged, 2);
+
+        // Both devices share one user
+        for device in [&mut desktop, &mut laptop] {
+            assert_eq!(
+                user::table.count().get_result::<i64>(&mut device.conn),
+                Ok(1)
+            );
+            assert_eq!(
+                device.titles(),
+                vec![Some("Deep work".to_string()), Some("Reading".to_string())]
+            );
+            assert_eq!(device.entry_values(), vec![20, 30, 45]);
+        }
+
+        // Nothing new, nothing to do
+        assert_eq!(laptop.sync(folder.path()).merged, 0);
+        assert_eq!(desktop.sync(folder.path()).merged, 0);
+    }
+
+    #[test]
+    fn test_last_writer_wins() {
+        let folder = tempdir::TempDir::new("sync").unwrap();
+        let mut desktop = Device::new();
+        let mut laptop = Device::new();
+        let user_id = desktop.add_user("2024-01-01T00:00:00Z");
+        desktop.add_habit(user_id, "Deep work");
+        desktop.sync(folder.path());
+        laptop.sync(folder.path());
+
+        // Synced out of order: the desktop's older change comes in last
+        laptop.rename_habit("Focus", "2030-01-02T00:00:00Z");
+        desktop.rename_habit("Concentration", "2030-01-01T00:00:00Z");
+        laptop.sync(folder.path());
+        desktop.sync(folder.path());
+        laptop.sync(folder.path());
+
+        assert_eq!(desktop.titles(), vec![Some("Focus".to_string())]);
+        assert_eq!(laptop.titles(), vec![Some("Focus".to_string())]);
+    }
+
+    #[test]
+    fn test_sync_deletions() {
+        let folder = tempdir::TempDir::new("sync").unwrap();
+        let mut desktop = Device::new();
+        let mut laptop = Device::new();
+        let user_id = desktop.add_user("2024-01-01T00:00:00Z");
+        let habit_id = desktop.add_habit(user_id, "Deep work");
+        desktop.add_entry(user_id, habit_id, "2024-05-01T09:00:00Z", 30);
+        desktop.add_entry(user_id, habit_id, "2024-05-02T09:00:00Z", 45);
+        desktop.sync(folder.path());
+        laptop.sync(folder.path());
+
+        let (id, uuid) = habit_entry::table
+            .filter(habit_entry::value.eq(30))
+            .select((habit_entry::id, habit_entry::uuid))
+            .first::<(i32, Option<String>)>(&mut laptop.conn)
+            .unwrap();
+        record_deletion(&mut laptop.conn, "habit_entry", uuid).unwrap();
+        diesel::delete(habit_entry::table.find(id))
+            .execute(&mut laptop.conn)
+            .unwrap();
+        laptop.sync(folder.path());
+        desktop.sync(folder.path());
+
+        assert_eq!(desktop.entry_values(), vec![45]);
+        // The deleted entry doesn't come back from the desktop's older log lines
+        laptop.sync(folder.path());
+        assert_eq!(laptop.entry_values(), vec![45]);
+    }
+
+    #[test]
+    fn test_sync_imported_habits() {
+        let folder = tempdir::TempDir::new("sync").unwrap();
+        let mut desktop = Device::new();
+        let mut laptop = Device::new();
+        let user_id = desktop.add_user("2024-01-01T00:00:00Z");
+        desktop.sync(folder.path());
+        laptop.sync(folder.path());
+
+        // Imported after the first sync, but made long before it
+        let export = DataExport {
+            version: 1,
+            exported_at: "2024-06-01T00:00:00Z".to_string(),
+            user: ExportedUser {
+                name: "User".to_string(),
+                date_of_birth: "2000-01-01T00:00:00Z".to_string(),
+            },
+            habits: vec![ExportedHabit {
+                id: 7,
+                name: HabitName::Custom.into(),
+                title: Some("Journaling".to_string()),
+                description: None,
+                categories: Vec::new(),
+                icon: None,
+                frequency: Frequency::default(),
+                created_at: "2024-01-01T00:00:00Z".to_string(),
+                updated_at: Some("2024-02-01T00:00:00Z".to_string()),
+                reminder_rules: Vec::new(),
+                note: None,
+                archived: false,
+                archived_date: None,
+                archived_reason: None,
+            }],
+            entries: vec![ExportedEntry {
+                habit_id: 7,
+                entry_time: "2024-05-03T09:00:00Z".to_string(),
+                note: None,
+                value: 25,
+            }],
+        };
+        export
+            .import_into(&mut desktop.conn, user_id as u32)
+            .unwrap();
+        assert_eq!(desktop.sync(folder.path()).exported, 2);
+        assert_eq!(laptop.sync(folder.path()).merged, 2);
+
+        assert_eq!(laptop.titles(), vec![Some("Journaling".to_string())]);
+        assert_eq!(laptop.entry_values(), vec![25]);
+    }
+
+    #[test]
+    fn test_sync_stores_local_entry_times() {
+        let folder = tempdir::TempDir::new("sync").unwrap();
+        let mut desktop = Device::new();
+        let mut laptop = Device::new();
+
+        // The desktop is on the other side of the world
+        let entry_time = "2024-05-02T23:30:00+14:00";
+        let user_id = desktop.add_user("2024-01-01T00:00:00Z");
+        let habit_id = desktop.add_habit(user_id, "Deep work");
+        desktop.add_entry(user_id, habit_id, entry_time, 30);
+        desktop.add_entry(user_id, habit_id, "not a time", 45);
+        desktop.sync(folder.path());
+        assert_eq!(laptop.sync(folder.path()).merged, 3);
+
+        let local = glib::DateTime::from_iso8601(entry_time, None)
+            .unwrap()
+            .to_local()
+            .unwrap();
+        assert_eq!(
+            laptop.entry_times(),
+            vec![local.format_iso8601().unwrap().to_string()]
+        );
+    }
+
+    #[test]
+    fn test_read_log_skips_partial_lines() {
+        let dir = tempdir::TempDir::new("sync").unwrap();
+        let path = dir.path().join("device.jsonl");
+        let deletion = Change::Deletion(Deletion {
+            uuid: new_uuid(),
+            table: "habit".to_string(),
+            deleted_at: now(),
+        });
+        let line = serde_json::to_string(&deletion).unwrap();
+        fs::write(&path, format!("{line}\n{}", &line[..10])).unwrap();
+
+        let (changes, position) = read_log(&path, 0).unwrap();
+        assert_eq!(changes, vec![deletion]);
+        assert_eq!(position, 1);
+        assert!(read_log(&path, 1).unwrap().0.is_empty());
+    }
+}
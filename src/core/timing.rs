@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    core::date::Time,
+    models::{HabitEntry, ReminderRule, Weekday},
+};
+
+/// Fewest entries to suggest reminders from.
+pub const MIN_ENTRIES: usize = 5;
+
+/// Fewest entries on a weekday for it to get its own reminder time, the
+/// others use the time of all entries.
+const MIN_WEEKDAY_ENTRIES: usize = 3;
+
+/// How long before the habit is usually done to remind of it.
+const REMINDER_LEAD_MINUTES: i32 = 30;
+
+/// Suggested times are rounded to this many minutes.
+const ROUND_MINUTES: i32 = 15;
+
+/// A weekday whose time is at most this far from the time of all entries
+/// uses the latter, so that small differences don't split up the reminders.
+const SNAP_MINUTES: i32 = 45;
+
+const MINUTES_PER_DAY: i32 = 24 * 60;
+
+/// When the entries of a habit were logged.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct TimeOfDay {
+    /// Number of entries per local hour, from midnight
+    pub by_hour: [usize; 24],
+    /// Number of entries per weekday, from Monday
+    pub by_weekday: [usize; 7],
+}
+
+impl TimeOfDay {
+    pub fn new(entries: &[HabitEntry]) -> Self {
+        let mut time_of_day = Self::default();
+        for entry in entries {
+            let time = local_time(entry);
+            time_of_day.by_hour[usize::from(time.hour())] += 1;
+            time_of_day.by_weekday[weekday(entry) as usize] += 1;
+        }
+        time_of_day
+    }
+
+    /// Number of entries counted.
+    pub fn total(&self) -> usize {
+        self.by_hour.iter().sum()
+    }
+
+    /// The hour most entries were logged in, the earliest of equally busy ones.
+    pub fn peak_hour(&self) -> Option<u8> {
+        let max = *self.by_hour.iter().max()?;
+        (max > 0).then(|| self.by_hour.iter().position(|count| *count == max).unwrap() as u8)
+    }
+}
+
+fn local_time(entry: &HabitEntry) -> Time {
+    let time = entry.entry_time().to_local().unwrap();
+    Time::new(time.hour() as u8, time.minute() as u8, 0).unwrap()
+}
+
+fn weekday(entry: &HabitEntry) -> Weekday {
+    Weekday::from(entry.entry_time().to_local().unwrap())
+}
+
+fn minute_of_day(time: Time) -> i32 {
+    i32::from(time.hour()) * 60 + i32::from(time.minutes())
+}
+
+fn median(minutes: &mut [i32]) -> i32 {
+    minutes.sort_unstable();
+    let middle = minutes.len() / 2;
+    if minutes.len() % 2 == 0 {
+        (minutes[middle - 1] + minutes[middle]) / 2
+    } else {
+        minutes[middle]
+    }
+}
+
+// A reminder a bit ahead of `minutes`, on a round time of the same day
+fn reminder_minutes(minutes: i32) -> i32 {
+    let rounded = (f64::from(minutes - REMINDER_LEAD_MINUTES) / f64::from(ROUND_MINUTES)).round()
+        as i32
+        * ROUND_MINUTES;
+    rounded.clamp(0, MINUTES_PER_DAY - ROUND_MINUTES)
+}
+
+/// Reminder rules for when the habit with `entries` is usually done: a bit
+/// before the median time it's logged at, per weekday where that differs
+/// much. Empty if there are fewer than [MIN_ENTRIES] entries.
+pub fn suggest_reminders(entries: &[HabitEntry]) -> Vec<ReminderRule> {
+    if entries.len() < MIN_ENTRIES {
+        return Vec::new();
+    }
+    let mut all_minutes = Vec::new();
+    let mut weekday_minutes = BTreeMap::<Weekday, Vec<i32>>::new();
+    for entry in entries {
+        let minutes = minute_of_day(local_time(entry));
+        all_minutes.push(minutes);
+        weekday_minutes
+            .entry(weekday(entry))
+            .or_default()
+            .push(minutes);
+    }
+    let overall = reminder_minutes(median(&mut all_minutes));
+
+    // The weekdays reminded at each time
+    let mut times = BTreeMap::<i32, Vec<Weekday>>::new();
+    for weekday in Weekday::range(Weekday::Monday, Weekday::Sunday) {
+        let minutes = match weekday_minutes.get_mut(&weekday) {
+            Some(minutes) if minutes.len() >= MIN_WEEKDAY_ENTRIES => {
+                let own = reminder_minutes(median(minutes));
+                if (own - overall).abs() <= SNAP_MINUTES {
+                    overall
+                } else {
+                    own
+                }
+            }
+            _ => overall,
+        };
+        times.entry(minutes).or_default().push(weekday);
+    }
+
+    times
+        .into_iter()
+        .map(|(minutes, weekdays)| {
+            let rule = ReminderRule::new(
+                Time::new((minutes / 60) as u8, (minutes % 60) as u8, 0).unwrap(),
+            );
+            if weekdays.len() == 7 {
+                rule
+            } else {
+                rule.on(weekdays)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::date::Date;
+
+    // 2024-05-06 is a Monday
+    fn entry(day: u8, hour: u8, minutes: u8) -> HabitEntry {
+        HabitEntry::new(
+            0,
+            1,
+            1,
+            Date::new(2024, 5, day)
+                .unwrap()
+                .and_time_local(Time::new(hour, minutes, 0).unwrap()),
+            None,
+            1,
+        )
+        .unwrap()
+    }
+
+    fn time(hour: u8, minutes: u8) -> Time {
+        Time::new(hour, minutes, 0).unwrap()
+    }
+
+    #[test]
+    fn test_time_of_day() {
+        let entries = vec![entry(6, 7, 10), entry(7, 7, 50), entry(12, 18, 0)];
+        let time_of_day = TimeOfDay::new(&entries);
+        assert_eq!(time_of_day.total(), 3);
+        assert_eq!(time_of_day.by_hour[7], 2);
+        assert_eq!(time_of_day.by_hour[18], 1);
+        assert_eq!(time_of_day.by_weekday[Weekday::Monday as usize], 1);
+        assert_eq!(time_of_day.by_weekday[Weekday::Sunday as usize], 1);
+        assert_eq!(time_of_day.peak_hour(), Some(7));
+        assert_eq!(TimeOfDay::default().peak_hour(), None);
+    }
+
+    #[test]
+    fn test_suggest_reminders() {
+        // Too few to tell
+        assert!(suggest_reminders(&[entry(6, 7, 0)]).is_empty());
+
+        // Around 7:00 on weekdays, a few minutes either way
+        let mut entries = Vec::new();
+        for week in [0, 7, 14] {
+            for day in 6..=10 {
+                entries.push(entry(day + week, 7, 5 * (day - 5)));
+            }
+        }
+        assert_eq!(
+            suggest_reminders(&entries),
+            vec![ReminderRule::new(time(6, 45))]
+        );
+
+        // And at 10:00 on the weekends
+        for week in [0, 7, 14] {
+            entries.push(entry(11 + week, 10, 0));
+            entries.push(entry(12 + week, 10, 10));
+        }
+        assert_eq!(
+            suggest_reminders(&entries),
+            vec![
+                ReminderRule::new(time(6, 45)).on(vec![
+                    Weekday::Monday,
+                    Weekday::Tuesday,
+                    Weekday::Wednesday,
+                    Weekday::Thursday,
+                    Weekday::Friday,
+                ]),
+                ReminderRule::new(time(9, 30)).on(vec![Weekday::Saturday]),
+                ReminderRule::new(time(9, 45)).on(vec![Weekday::Sunday]),
+            ]
+        );
+    }
+}
@@ -16,7 +16,7 @@ pub mod core;
 pub mod models;
 pub mod schema;
 // pub mod views;
-// pub mod widgets;
+pub mod widgets;
 pub mod windows;
 
 pub mod prelude {
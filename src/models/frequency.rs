@@ -1,4 +1,7 @@
-use crate::models::{Compare, DurationKind, UnitSystem, Weekday};
+use crate::{
+    core::i18n,
+    models::{Compare, DurationKind, UnitSystem, Weekday},
+};
 use gtk::glib;
 
 #[derive(Clone, glib::Boxed, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
@@ -78,6 +81,61 @@ impl Frequency {
     pub fn is_abstraction(&self) -> bool {
         self.target_value == 0
     }
+
+    /// Whether a period falling on `weekday` counts towards this frequency.
+    pub fn is_due_on(&self, weekday: Weekday) -> bool {
+        match self.duration_kind {
+            DurationKind::Daily => self
+                .weekdays
+                .as_ref()
+                .map_or(true, |weekdays| weekdays.contains(&weekday)),
+            DurationKind::Monthly => true,
+        }
+    }
+
+    /// Describes the target, e.g. `>= 30 minutes per day`.
+    pub fn target_text(&self) -> String {
+        let period = match self.duration_kind {
+            DurationKind::Daily => i18n("per day"),
+            DurationKind::Monthly => i18n("per month"),
+        };
+        format!(
+            "{} {} {}",
+            self.comparator.to_symbol_str(),
+            self.unit.format_value(i64::from(self.target_value)),
+            period
+        )
+    }
+
+    /// Lists the weekdays a daily habit is due on, or [None] if it's due every day.
+    pub fn weekdays_text(&self) -> Option<String> {
+        let weekdays = self.weekdays.as_ref()?;
+        if self.duration_kind != DurationKind::Daily || weekdays.len() == 7 {
+            return None;
+        }
+        Some(
+            weekdays
+                .iter()
+                .map(|weekday| weekday.as_short_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
+    /// The value logged by marking the habit done in one go: the whole target
+    /// of a daily habit, a single unit towards a monthly one.
+    pub fn default_entry_value(&self) -> i32 {
+        let value = match self.duration_kind {
+            DurationKind::Daily => self.target_value,
+            DurationKind::Monthly => self.target_value.min(1),
+        };
+        i32::try_from(value).unwrap_or(i32::MAX)
+    }
+
+    /// Whether `value`, the total logged over one period, reaches the target.
+    pub fn is_satisfied(&self, value: i64) -> bool {
+        self.comparator.compare(value, i64::from(self.target_value))
+    }
 }
 
 #[cfg(test)]
@@ -256,4 +314,71 @@ mod tests {
         );
         assert!(!freq.is_abstraction());
     }
+
+    #[test]
+    fn test_is_due_on() {
+        let freq = Frequency::new(
+            Some(DurationKind::Daily),
+            None,
+            None,
+            Some(vec![Weekday::Monday, Weekday::Friday]),
+            None,
+        );
+        assert!(freq.is_due_on(Weekday::Monday));
+        assert!(!freq.is_due_on(Weekday::Tuesday));
+        let freq = Frequency::new(Some(DurationKind::Monthly), None, None, None, None);
+        assert!(freq.is_due_on(Weekday::Tuesday));
+    }
+
+    #[test]
+    fn test_target_text() {
+        let freq = Frequency::new(
+            Some(DurationKind::Daily),
+            Some(UnitSystem::Minutes),
+            Some(120),
+            Some(vec![Weekday::Monday, Weekday::Tuesday]),
+            Some(Compare::GreaterOrEqual),
+        );
+        assert_eq!(freq.target_text(), ">= 120 minutes per day");
+        assert_eq!(freq.weekdays_text(), Some("Mon, Tue".to_string()));
+        assert_eq!(Frequency::default().weekdays_text(), None);
+    }
+
+    #[test]
+    fn test_is_satisfied() {
+        let freq = Frequency::new(
+            None,
+            Some(UnitSystem::Minutes),
+            Some(30),
+            None,
+            Some(Compare::GreaterOrEqual),
+        );
+        assert!(freq.is_satisfied(30));
+        assert!(!freq.is_satisfied(29));
+        let freq = Frequency::new(None, None, Some(0), None, Some(Compare::LessOrEqual));
+        assert!(freq.is_satisfied(0));
+        assert!(!freq.is_satisfied(1));
+    }
+
+    #[test]
+    fn test_default_entry_value() {
+        let daily = Frequency::new(
+            Some(DurationKind::Daily),
+            Some(UnitSystem::Minutes),
+            Some(30),
+            None,
+            None,
+        );
+        assert_eq!(daily.default_entry_value(), 30);
+        let monthly = Frequency::new(Some(DurationKind::Monthly), None, Some(4), None, None);
+        assert_eq!(monthly.default_entry_value(), 1);
+        let abstention = Frequency::new(
+            Some(DurationKind::Daily),
+            None,
+            Some(0),
+            None,
+            Some(Compare::LessOrEqual),
+        );
+        assert_eq!(abstention.default_entry_value(), 0);
+    }
 }
@@ -1,25 +1,200 @@
 use crate::{
-    core::{database, serialize},
-    models::{Frequency, HabitCategory, HabitName},
+    core::{database, serialize, sync},
+    models::{Frequency, HabitCategory, HabitInfo, HabitName, ReminderRule, ReminderRules},
     schema::habit,
 };
 use diesel::prelude::*;
 use gtk::{glib, prelude::*, subclass::prelude::*};
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
 
+/// Changes to make to a [Habit] with [Habit::update]. Only the fields that
+/// are set are changed, e.g.
+/// `UpdateHabit::new().title(Some("Tidy up".into())).archived(true)`.
+#[derive(Debug, Clone, Default)]
 pub struct UpdateHabit {
-    user_id: i32,
-    name: String,
-    description: Option<String>,
-    categories: Option<String>,
-    icon: Option<String>,
-    frequency: String,
-    reminder_times: Option<String>,
-    note: Option<String>,
-    archived: i32,
-    archived_date: Option<String>,
-    archived_reason: Option<String>,
+    user_id: Option<u32>,
+    name: Option<String>,
+    title: Option<Option<String>>,
+    description: Option<Option<String>>,
+    categories: Option<Option<Vec<HabitCategory>>>,
+    icon: Option<Option<String>>,
+    frequency: Option<Frequency>,
+    reminder_rules: Option<Vec<ReminderRule>>,
+    note: Option<Option<String>>,
+    archived: Option<bool>,
+    archived_date: Option<Option<glib::DateTime>>,
+    archived_reason: Option<Option<String>>,
+}
+
+impl UpdateHabit {
+    /// An update that doesn't change anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether no field is changed.
+    pub fn is_empty(&self) -> bool {
+        self.user_id.is_none()
+            && self.name.is_none()
+            && self.title.is_none()
+            && self.description.is_none()
+            && self.categories.is_none()
+            && self.icon.is_none()
+            && self.frequency.is_none()
+            && self.reminder_rules.is_none()
+            && self.note.is_none()
+            && self.archived.is_none()
+            && self.archived_date.is_none()
+            && self.archived_reason.is_none()
+    }
+
+    #[must_use]
+    pub fn user_id(mut self, user_id: u32) -> Self {
+        self.user_id = Some(user_id);
+        self
+    }
+
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    #[must_use]
+    pub fn title(mut self, title: Option<String>) -> Self {
+        self.title = Some(title);
+        self
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: Option<String>) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    #[must_use]
+    pub fn categories(mut self, categories: Option<Vec<HabitCategory>>) -> Self {
+        self.categories = Some(categories);
+        self
+    }
+
+    #[must_use]
+    pub fn icon(mut self, icon: Option<String>) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    #[must_use]
+    pub fn frequency(mut self, frequency: Frequency) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    #[must_use]
+    pub fn reminder_rules(mut self, reminder_rules: Vec<ReminderRule>) -> Self {
+        self.reminder_rules = Some(reminder_rules);
+        self
+    }
+
+    #[must_use]
+    pub fn note(mut self, note: Option<String>) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    #[must_use]
+    pub fn archived(mut self, archived: bool) -> Self {
+        self.archived = Some(archived);
+        self
+    }
+
+    #[must_use]
+    pub fn archived_date(mut self, archived_date: Option<glib::DateTime>) -> Self {
+        self.archived_date = Some(archived_date);
+        self
+    }
+
+    #[must_use]
+    pub fn archived_reason(mut self, archived_reason: Option<String>) -> Self {
+        self.archived_reason = Some(archived_reason);
+        self
+    }
+
+    /// Archives the habit now, for `reason`.
+    #[must_use]
+    pub fn archive(self, reason: Option<String>) -> Self {
+        self.archived(true)
+            .archived_date(Some(glib::DateTime::now_local().unwrap()))
+            .archived_reason(reason)
+    }
+
+    /// Brings an archived habit back.
+    #[must_use]
+    pub fn unarchive(self) -> Self {
+        self.archived(false)
+            .archived_date(None)
+            .archived_reason(None)
+    }
+}
+
+/// The columns an [UpdateHabit] changes, serialized as they are stored.
+/// `None` leaves a column as is, `Some(None)` clears it.
+#[derive(AsChangeset)]
+#[diesel(table_name = habit)]
+struct HabitChangeset {
+    user_id: Option<i32>,
+    name: Option<String>,
+    title: Option<Option<String>>,
+    description: Option<Option<String>>,
+    categories: Option<Option<String>>,
+    icon: Option<Option<String>>,
+    frequency: Option<String>,
+    updated_at: Option<String>,
+    reminder_rules: Option<Option<String>>,
+    note: Option<Option<String>>,
+    archived: Option<i32>,
+    archived_date: Option<Option<String>>,
+    archived_reason: Option<Option<String>>,
+}
+
+impl HabitChangeset {
+    fn new(update: &UpdateHabit, updated_at: String) -> Result<Self, Box<dyn std::error::Error>> {
+        let format_date = |date: &Option<glib::DateTime>| -> Result<_, glib::BoolError> {
+            date.as_ref()
+                .map(|date| date.format_iso8601().map(String::from))
+                .transpose()
+        };
+        Ok(Self {
+            user_id: update.user_id.map(|user_id| user_id as i32),
+            name: update.name,
+            title: update.title.clone(),
+            description: update.description.clone(),
+            categories: update
+                .categories
+                .as_ref()
+                .map(|categories| categories.as_ref().map(serde_json::to_string).transpose())
+                .transpose()?,
+            icon: update.icon.clone(),
+            frequency: update
+                .frequency
+                .as_ref()
+                .map(serialize::serialize_struct_to_string),
+            updated_at: Some(updated_at),
+            reminder_rules: update
+                .reminder_rules
+                .as_ref()
+                .map(|rules| {
+                    (!rules.is_empty())
+                        .then(|| serde_json::to_string(rules))
+                        .transpose()
+                })
+                .transpose()?,
+            note: update.note.clone(),
+            archived: update.archived.map(i32::from),
+            archived_date: update.archived_date.as_ref().map(format_date).transpose()?,
+            archived_reason: update.archived_reason.clone(),
+        })
+    }
 }
 
 #[derive(Insertable)]
@@ -33,11 +208,13 @@ struct NewHabit {
     frequency: String,
     created_at: String,
     updated_at: Option<String>,
-    reminder_times: Option<String>,
+    reminder_rules: Option<String>,
     note: Option<String>,
     archived: i32,
     archived_date: Option<String>,
     archived_reason: Option<String>,
+    title: Option<String>,
+    uuid: Option<String>,
 }
 
 #[derive(Queryable, Identifiable)]
@@ -52,11 +229,14 @@ struct DieselHabit {
     frequency: String,
     created_at: String,
     updated_at: Option<String>,
-    reminder_times: Option<String>,
+    reminder_rules: Option<String>,
     note: Option<String>,
     archived: i32,
     archived_date: Option<String>,
     archived_reason: Option<String>,
+    title: Option<String>,
+    #[allow(dead_code)]
+    uuid: Option<String>,
 }
 
 #[derive(Clone, glib::Boxed, PartialEq, Eq, Deserialize, Serialize)]
@@ -69,20 +249,6 @@ impl Default for Categories {
     }
 }
 
-#[derive(Clone, glib::Boxed, PartialEq, Eq, Deserialize, Serialize)]
-#[boxed_type(name = "reminder_times")]
-pub struct ReminderTimes(
-    #[serde(serialize_with = "serialize::serialize_option_vec_datetime")]
-    #[serde(deserialize_with = "serialize::deserialize_option_vec_datetime")]
-    Option<Vec<glib::DateTime>>,
-);
-
-impl Default for ReminderTimes {
-    fn default() -> Self {
-        Self(None)
-    }
-}
-
 #[doc(hidden)]
 mod imp {
     use glib::ParamSpecBoxed;
@@ -98,8 +264,12 @@ mod imp {
         pub id: Cell<u32>,
         #[property(get, set)]
         pub user_id: Cell<u32>,
+        /// Id of the [HabitPreset](crate::models::HabitPreset) the habit was made from
         #[property(get, set)]
-        pub name: RefCell<HabitName>,
+        pub name: RefCell<String>,
+        /// Display name of custom habits
+        #[property(get, set, nullable)]
+        pub title: RefCell<Option<String>>,
         #[property(get, set, nullable)]
         pub description: RefCell<Option<String>>,
         pub categories: RefCell<Option<Vec<HabitCategory>>>,
@@ -114,9 +284,7 @@ mod imp {
         #[serde(serialize_with = "serialize::serialize_refcell_option_datetime")]
         #[serde(deserialize_with = "serialize::deserialize_refcell_option_datetime")]
         pub updated_at: RefCell<Option<glib::DateTime>>,
-        #[serde(serialize_with = "serialize::serialize_refcell_option_vec_datetime")]
-        #[serde(deserialize_with = "serialize::deserialize_refcell_option_vec_datetime")]
-        pub reminder_times: RefCell<Option<Vec<glib::DateTime>>>,
+        pub reminder_rules: RefCell<Vec<ReminderRule>>,
         #[property(get, set, nullable)]
         pub note: RefCell<Option<String>>,
         #[property(get, set)]
@@ -138,14 +306,15 @@ mod imp {
             Self {
                 id: Cell::default(),
                 user_id: Cell::new(0),
-                name: RefCell::new(HabitName::default()),
+                name: RefCell::new(HabitName::default().into()),
+                title: RefCell::new(None),
                 description: RefCell::new(None),
                 categories: RefCell::new(None),
                 icon: RefCell::default(),
                 frequency: RefCell::new(Frequency::default()),
                 created_at: RefCell::new(glib::DateTime::now_local().unwrap()),
                 updated_at: RefCell::new(None),
-                reminder_times: RefCell::new(None),
+                reminder_rules: RefCell::default(),
                 note: RefCell::default(),
                 archived: Cell::new(false),
                 archived_date: RefCell::new(None),
@@ -158,10 +327,9 @@ mod imp {
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
                 let mut props = Habit::derived_properties().to_vec();
-                // props.push(ParamSpecBoxed::builder::<HabitName>("name").build());
                 props.push(ParamSpecBoxed::builder::<Categories>("categories").build());
                 props.push(ParamSpecBoxed::builder::<Frequency>("frequency").build());
-                props.push(ParamSpecBoxed::builder::<ReminderTimes>("reminder-times").build());
+                props.push(ParamSpecBoxed::builder::<ReminderRules>("reminder-rules").build());
                 props
             });
             PROPERTIES.as_ref()
@@ -181,11 +349,11 @@ mod imp {
                         .expect("type conformity checked by `Object::set_property`");
                     self.frequency.replace(frequency);
                 }
-                "reminder-times" => {
-                    let reminder_times = value
-                        .get::<ReminderTimes>()
+                "reminder-rules" => {
+                    let reminder_rules = value
+                        .get::<ReminderRules>()
                         .expect("type conformity checked by `Object::set_property`");
-                    self.reminder_times.replace(reminder_times.0);
+                    self.reminder_rules.replace(reminder_rules.0);
                 }
                 _ => self.derived_set_property(id, value, pspec),
             }
@@ -193,10 +361,9 @@ mod imp {
 
         fn property(&self, id: usize, pspec: &glib::ParamSpec) -> glib::Value {
             match pspec.name() {
-                "name" => self.name.borrow().to_value(),
                 "categories" => Categories(self.categories.borrow().clone()).to_value(),
                 "frequency" => self.frequency.borrow().to_value(),
-                "reminder-times" => ReminderTimes(self.reminder_times.borrow().clone()).to_value(),
+                "reminder-rules" => ReminderRules(self.reminder_rules.borrow().clone()).to_value(),
                 _ => self.derived_property(id, pspec),
             }
         }
@@ -211,14 +378,15 @@ impl Habit {
     pub fn new(
         id: u32,
         user_id: u32,
-        name: HabitName,
+        name: impl Into<String>,
+        title: Option<String>,
         description: Option<String>,
         categories: Option<Vec<HabitCategory>>,
         icon: Option<String>,
         frequency: Frequency,
         created_at: glib::DateTime,
         updated_at: Option<glib::DateTime>,
-        reminder_times: Option<Vec<glib::DateTime>>,
+        reminder_rules: Vec<ReminderRule>,
         note: Option<String>,
         archived: bool,
         archived_date: Option<glib::DateTime>,
@@ -227,14 +395,15 @@ impl Habit {
         let habit = glib::Object::builder::<Self>()
             .property("id", id)
             .property("user-id", user_id)
-            .property("name", name)
+            .property("name", name.into())
+            .property("title", title)
             .property("description", description)
             .property("categories", Categories(categories))
             .property("icon", icon)
             .property("frequency", frequency)
             .property("created-at", created_at)
             .property("updated-at", updated_at)
-            .property("reminder-times", ReminderTimes(reminder_times))
+            .property("reminder-rules", ReminderRules(reminder_rules))
             .property("note", note)
             .property("archived", archived)
             .property("archived-date", archived_date)
@@ -244,6 +413,18 @@ impl Habit {
         Ok(habit)
     }
 
+    /// The details of the preset the habit was made from.
+    pub fn info(&self) -> HabitInfo {
+        HabitInfo::for_preset(&self.imp().name.borrow())
+    }
+
+    /// The name to show for this habit: its title, or the name of its preset.
+    pub fn display_name(&self) -> String {
+        self.title()
+            .filter(|title| !title.trim().is_empty())
+            .unwrap_or_else(|| self.info().name)
+    }
+
     pub fn categories(&self) -> Option<Vec<HabitCategory>> {
         self.imp().categories.borrow().clone()
     }
@@ -252,30 +433,34 @@ impl Habit {
         self.imp().frequency.borrow().clone()
     }
 
-    pub fn reminder_times(&self) -> Option<Vec<glib::DateTime>> {
-        self.imp().reminder_times.borrow().clone()
+    pub fn reminder_rules(&self) -> Vec<ReminderRule> {
+        self.imp().reminder_rules.borrow().clone()
     }
 
     pub fn set_categories(&self, categories: Option<Vec<HabitCategory>>) {
         self.imp().categories.replace(categories);
+        self.notify("categories");
     }
 
     pub fn set_frequency(&self, frequency: Frequency) {
         self.imp().frequency.replace(frequency);
+        self.notify("frequency");
     }
 
-    pub fn set_reminder_times(&self, reminder_times: Option<Vec<glib::DateTime>>) {
-        self.imp().reminder_times.replace(reminder_times);
+    pub fn set_reminder_rules(&self, reminder_rules: Vec<ReminderRule>) {
+        self.imp().reminder_rules.replace(reminder_rules);
+        self.notify("reminder-rules");
     }
 
     pub fn create(
         user_id: u32,
-        name: HabitName,
+        name: impl Into<String>,
+        title: Option<String>,
         description: Option<String>,
         categories: Option<Vec<HabitCategory>>,
         icon: Option<String>,
         frequency: Frequency,
-        reminder_times: Option<Vec<glib::DateTime>>,
+        reminder_rules: Vec<ReminderRule>,
         note: Option<String>,
         archived: bool,
         archived_date: Option<glib::DateTime>,
@@ -286,7 +471,8 @@ impl Habit {
 
         let new_habit = NewHabit {
             user_id: user_id as i32,
-            name: name.as_str().to_string(),
+            name: name.into(),
+            title,
             description,
             categories: match categories {
                 Some(c) => Some(serde_json::to_string(&c).unwrap()),
@@ -300,10 +486,8 @@ impl Habit {
                 .unwrap()
                 .to_string(),
             updated_at: None,
-            reminder_times: match reminder_times {
-                Some(times) => Some(serialize::serialize_vec_datetime_to_string(&times)),
-                None => None,
-            },
+            reminder_rules: (!reminder_rules.is_empty())
+                .then(|| serde_json::to_string(&reminder_rules).unwrap()),
             note,
             archived: archived as i32,
             archived_date: match archived_date {
@@ -311,106 +495,84 @@ impl Habit {
                 None => None,
             },
             archived_reason,
+            uuid: Some(sync::new_uuid()),
         };
 
         diesel::insert_into(habit::table)
             .values(&new_habit)
             .execute(&mut conn)?;
 
-        habit::table
+        let habit = habit::table
             .order(habit::columns::id.desc())
             .first::<DieselHabit>(&mut conn)
-            .map_err(From::from)
-            .map(|habit| {
-                Self::new(
-                    habit.id as u32,
-                    habit.user_id as u32,
-                    HabitName::from_str(&habit.name).unwrap(),
-                    habit.description,
-                    habit.categories.map(|c| serde_json::from_str(&c).unwrap()),
-                    habit.icon,
-                    serialize::deserialize_string_to_struct(&habit.frequency),
-                    glib::DateTime::from_iso8601(habit.created_at.as_str(), None).unwrap(),
-                    match habit.updated_at {
-                        Some(date) => {
-                            Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap())
-                        }
-                        None => None,
-                    },
-                    match habit.reminder_times {
-                        Some(times) => Some(serialize::deserialize_string_to_vec_datetime(&times)),
-                        None => None,
-                    },
-                    habit.note,
-                    habit.archived == 1,
-                    match habit.archived_date {
-                        Some(date) => {
-                            Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap())
-                        }
-                        None => None,
-                    },
-                    habit.archived_reason,
-                )
-                .unwrap()
-            })
+            .map(Self::from_diesel)?;
+        Ok(habit)
     }
 
-    pub fn update(&self, updated_habit: &UpdateHabit) -> Result<(), Box<dyn std::error::Error>> {
+    /// Saves the changes of `update` and applies them to this habit.
+    pub fn update(&self, update: &UpdateHabit) -> Result<(), Box<dyn std::error::Error>> {
+        if update.is_empty() {
+            return Ok(());
+        }
         let db = database::connection();
         let mut conn = db.get()?;
-        let target = habit::table.filter(habit::columns::id.eq(self.id() as i32));
-        let curr_time = glib::DateTime::now_local()
-            .unwrap()
-            .format_iso8601()
-            .unwrap()
-            .to_string();
-        diesel::update(target)
-            .set((
-                habit::columns::user_id.eq(updated_habit.user_id as i32),
-                habit::columns::name.eq(updated_habit.name.clone()),
-                habit::columns::description.eq(updated_habit.description.clone()),
-                habit::columns::categories.eq(updated_habit.categories.clone()),
-                habit::columns::icon.eq(updated_habit.icon.clone()),
-                habit::columns::frequency.eq(updated_habit.frequency.clone()),
-                habit::columns::updated_at.eq(curr_time.clone()),
-                habit::columns::reminder_times.eq(updated_habit.reminder_times.clone()),
-                habit::columns::note.eq(updated_habit.note.clone()),
-                habit::columns::archived.eq(updated_habit.archived.clone() as i32),
-                habit::columns::archived_date.eq(updated_habit.archived_date.clone()),
-                habit::columns::archived_reason.eq(updated_habit.archived_reason.clone()),
-            ))
+        let updated_at = glib::DateTime::now_local()?;
+        let changeset = HabitChangeset::new(update, updated_at.format_iso8601()?.to_string())?;
+        diesel::update(habit::table.filter(habit::columns::id.eq(self.id() as i32)))
+            .set(&changeset)
             .execute(&mut conn)?;
 
-        self.set_user_id(updated_habit.user_id as u32);
-        self.set_name(HabitName::from_str(&updated_habit.name.as_str()).unwrap());
-        self.set_description(updated_habit.description.clone());
-        self.set_categories(match updated_habit.categories.clone() {
-            Some(c) => Some(serde_json::from_str(&c).unwrap()),
-            None => None,
-        });
-        self.set_icon(updated_habit.icon.clone());
-        self.set_frequency(serialize::deserialize_string_to_struct(
-            &updated_habit.frequency.clone(),
-        ));
-        self.set_updated_at(Some(glib::DateTime::from_iso8601(
-            curr_time.as_str(),
-            None,
-        )?));
-        self.set_reminder_times(match updated_habit.reminder_times.clone() {
-            Some(times) => Some(serialize::deserialize_string_to_vec_datetime(&times)),
-            None => None,
-        });
-        self.set_note(updated_habit.note.clone());
-        self.set_archived(updated_habit.archived.clone() == 1);
-        self.set_archived_date(match updated_habit.archived_date.clone() {
-            Some(date) => Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap()),
-            None => None,
-        });
-        self.set_archived_reason(updated_habit.archived_reason.clone());
+        // Notify once all properties are set, not with the habit half updated
+        let _guard = self.freeze_notify();
+        if let Some(user_id) = update.user_id {
+            self.set_user_id(user_id);
+        }
+        if let Some(name) = &update.name {
+            self.set_name(name.clone());
+        }
+        if let Some(title) = &update.title {
+            self.set_title(title.clone());
+        }
+        if let Some(description) = &update.description {
+            self.set_description(description.clone());
+        }
+        if let Some(categories) = &update.categories {
+            self.set_categories(categories.clone());
+        }
+        if let Some(icon) = &update.icon {
+            self.set_icon(icon.clone());
+        }
+        if let Some(frequency) = &update.frequency {
+            self.set_frequency(frequency.clone());
+        }
+        self.set_updated_at(Some(updated_at));
+        if let Some(reminder_rules) = &update.reminder_rules {
+            self.set_reminder_rules(reminder_rules.clone());
+        }
+        if let Some(note) = &update.note {
+            self.set_note(note.clone());
+        }
+        if let Some(archived) = update.archived {
+            self.set_archived(archived);
+        }
+        if let Some(archived_date) = &update.archived_date {
+            self.set_archived_date(archived_date.clone());
+        }
+        if let Some(archived_reason) = &update.archived_reason {
+            self.set_archived_reason(archived_reason.clone());
+        }
 
         Ok(())
     }
 
+    /// Replaces the reminder rules of the habit, leaving the rest as is.
+    pub fn update_reminder_rules(
+        &self,
+        reminder_rules: Vec<ReminderRule>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.update(&UpdateHabit::new().reminder_rules(reminder_rules))
+    }
+
     pub fn find(id: u32) -> Result<Self, Box<dyn std::error::Error>> {
         let db = database::connection();
         let mut conn = db.get()?;
@@ -418,38 +580,7 @@ impl Habit {
             .filter(habit::columns::id.eq(id as i32))
             .first::<DieselHabit>(&mut conn)
             .map_err(From::from)
-            .map(|habit| {
-                Self::new(
-                    habit.id as u32,
-                    habit.user_id as u32,
-                    HabitName::from_str(&habit.name).unwrap(),
-                    habit.description,
-                    habit.categories.map(|c| serde_json::from_str(&c).unwrap()),
-                    habit.icon,
-                    serialize::deserialize_string_to_struct(&habit.frequency),
-                    glib::DateTime::from_iso8601(habit.created_at.as_str(), None).unwrap(),
-                    match habit.updated_at {
-                        Some(date) => {
-                            Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap())
-                        }
-                        None => None,
-                    },
-                    match habit.reminder_times {
-                        Some(times) => Some(serialize::deserialize_string_to_vec_datetime(&times)),
-                        None => None,
-                    },
-                    habit.note,
-                    habit.archived == 1,
-                    match habit.archived_date {
-                        Some(date) => {
-                            Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap())
-                        }
-                        None => None,
-                    },
-                    habit.archived_reason,
-                )
-                .unwrap()
-            })
+            .map(Self::from_diesel)
     }
 
     pub fn find_all() -> Result<Vec<Self>, Box<dyn std::error::Error>> {
@@ -459,71 +590,82 @@ impl Habit {
             .order(habit::columns::id.desc())
             .load::<DieselHabit>(&mut conn)
             .map_err(From::from)
-            .map(|habits| {
-                habits
-                    .into_iter()
-                    .map(|habit| {
-                        Self::new(
-                            habit.id as u32,
-                            habit.user_id as u32,
-                            HabitName::from_str(&habit.name).unwrap(),
-                            habit.description,
-                            habit.categories.map(|c| serde_json::from_str(&c).unwrap()),
-                            habit.icon,
-                            serialize::deserialize_string_to_struct(&habit.frequency),
-                            glib::DateTime::from_iso8601(habit.created_at.as_str(), None).unwrap(),
-                            match habit.updated_at {
-                                Some(date) => {
-                                    Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap())
-                                }
-                                None => None,
-                            },
-                            match habit.reminder_times {
-                                Some(times) => {
-                                    Some(serialize::deserialize_string_to_vec_datetime(&times))
-                                }
-                                None => None,
-                            },
-                            habit.note,
-                            habit.archived == 1,
-                            match habit.archived_date {
-                                Some(date) => {
-                                    Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap())
-                                }
-                                None => None,
-                            },
-                            habit.archived_reason,
-                        )
-                        .unwrap()
-                    })
-                    .collect()
-            })
+            .map(|habits| habits.into_iter().map(Self::from_diesel).collect())
+    }
+
+    pub fn find_by_user(user_id: u32) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        let db = database::connection();
+        let mut conn = db.get()?;
+        habit::table
+            .filter(habit::columns::user_id.eq(user_id as i32))
+            .order(habit::columns::id.asc())
+            .load::<DieselHabit>(&mut conn)
+            .map_err(From::from)
+            .map(|habits| habits.into_iter().map(Self::from_diesel).collect())
     }
 
     pub fn delete(&self) -> Result<(), Box<dyn std::error::Error>> {
         let db = database::connection();
         let mut conn = db.get()?;
-        diesel::delete(habit::table.filter(habit::columns::id.eq(self.id() as i32)))
-            .execute(&mut conn)?;
+        conn.transaction(|conn| {
+            let target = habit::table.filter(habit::columns::id.eq(self.id() as i32));
+            let uuid = target.select(habit::columns::uuid).first(conn)?;
+            sync::record_deletion(conn, "habit", uuid)?;
+            diesel::delete(target).execute(conn)
+        })?;
         Ok(())
     }
+
+    fn from_diesel(habit: DieselHabit) -> Self {
+        Self::new(
+            habit.id as u32,
+            habit.user_id as u32,
+            habit.name,
+            habit.title,
+            habit.description,
+            habit.categories.map(|c| serde_json::from_str(&c).unwrap()),
+            habit.icon,
+            serialize::deserialize_string_to_struct(&habit.frequency),
+            glib::DateTime::from_iso8601(habit.created_at.as_str(), None).unwrap(),
+            match habit.updated_at {
+                Some(date) => Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap()),
+                None => None,
+            },
+            habit
+                .reminder_rules
+                .map(|rules| serde_json::from_str(&rules).unwrap())
+                .unwrap_or_default(),
+            habit.note,
+            habit.archived == 1,
+            match habit.archived_date {
+                Some(date) => Some(glib::DateTime::from_iso8601(date.as_str(), None).unwrap()),
+                None => None,
+            },
+            habit.archived_reason,
+        )
+        .unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::HabitName;
+    use crate::{
+        core::date::Time,
+        models::{HabitName, Weekday},
+    };
 
     #[test]
     fn test_habit() {
         let habit = Habit::create(
             1,
             HabitName::Cleaning,
+            None,
             Some("Cleaning your room".to_string()),
             Some(vec![HabitCategory::Body, HabitCategory::Mind]),
             None,
             Frequency::default(),
-            None,
+            vec![ReminderRule::new(Time::new(7, 30, 0).unwrap()).on(vec![Weekday::Monday])],
             None,
             false,
             None,
@@ -535,6 +677,7 @@ mod tests {
 
         assert_eq!(habit.user_id(), habit_from_db.as_ref().unwrap().user_id());
         assert_eq!(habit.name(), habit_from_db.as_ref().unwrap().name());
+        assert_eq!(habit.title(), habit_from_db.as_ref().unwrap().title());
         assert_eq!(
             habit.description(),
             habit_from_db.as_ref().unwrap().description()
@@ -549,8 +692,8 @@ mod tests {
             habit_from_db.as_ref().unwrap().frequency()
         );
         assert_eq!(
-            habit.reminder_times(),
-            habit_from_db.as_ref().unwrap().reminder_times()
+            habit.reminder_rules(),
+            habit_from_db.as_ref().unwrap().reminder_rules()
         );
         assert_eq!(habit.note(), habit_from_db.as_ref().unwrap().note());
         assert_eq!(habit.archived(), habit_from_db.as_ref().unwrap().archived());
@@ -566,34 +709,61 @@ mod tests {
         // let habit_count = Habit::find_all().unwrap().len();
         // assert_eq!(habit_count, 1);
 
-        let updated_habit = UpdateHabit {
-            user_id: 2,
-            name: "cleaning".to_string(),
-            description: None,
-            categories: None,
-            icon: None,
-            frequency: serialize::serialize_struct_to_string(&Frequency::default()),
-            reminder_times: None,
-            note: None,
-            archived: 1,
-            archived_date: None,
-            archived_reason: None,
-        };
+        let updated_habit = UpdateHabit::new()
+            .user_id(2)
+            .name(HabitName::Cleaning)
+            .title(Some("Tidy up".to_string()))
+            .description(None)
+            .categories(None)
+            .icon(None)
+            .frequency(Frequency::default())
+            .reminder_rules(Vec::new())
+            .note(None)
+            .archived(true)
+            .archived_date(None)
+            .archived_reason(None);
 
         habit.update(&updated_habit).unwrap();
 
         assert_eq!(habit.user_id(), 2);
-        assert_eq!(habit.name(), HabitName::Cleaning);
+        assert_eq!(habit.name(), HabitName::Cleaning.as_str());
+        assert_eq!(habit.title(), Some("Tidy up".to_string()));
+        assert_eq!(habit.display_name(), "Tidy up");
         assert_eq!(habit.description(), None);
         assert_eq!(habit.categories(), None);
         assert_eq!(habit.icon(), None);
         assert_eq!(habit.frequency(), Frequency::default());
-        assert_eq!(habit.reminder_times(), None);
+        assert!(habit.reminder_rules().is_empty());
         assert_eq!(habit.note(), None);
         assert_eq!(habit.archived(), true);
         assert_eq!(habit.archived_date(), None);
         assert_eq!(habit.archived_reason(), None);
 
+        let rules = vec![ReminderRule::new(Time::new(18, 15, 0).unwrap())];
+        habit.update_reminder_rules(rules.clone()).unwrap();
+        assert_eq!(habit.reminder_rules(), rules);
+        assert_eq!(Habit::find(habit.id()).unwrap().reminder_rules(), rules);
+        assert_eq!(Habit::find(habit.id()).unwrap().archived(), true);
+
+        // Fields that aren't set are left as they are
+        habit
+            .update(&UpdateHabit::new().note(Some("Weekends too".to_string())))
+            .unwrap();
+        let habit_from_db = Habit::find(habit.id()).unwrap();
+        assert_eq!(habit_from_db.note(), Some("Weekends too".to_string()));
+        assert_eq!(habit_from_db.title(), Some("Tidy up".to_string()));
+        assert_eq!(habit_from_db.reminder_rules(), rules);
+
+        habit
+            .update(&UpdateHabit::new().archive(Some("Moved out".to_string())))
+            .unwrap();
+        assert!(habit.archived_date().is_some());
+        habit.update(&UpdateHabit::new().unarchive()).unwrap();
+        let habit_from_db = Habit::find(habit.id()).unwrap();
+        assert_eq!(habit_from_db.archived(), false);
+        assert_eq!(habit_from_db.archived_date(), None);
+        assert_eq!(habit_from_db.archived_reason(), None);
+
         let _ = habit.delete();
     }
 }
@@ -2,6 +2,8 @@ use std::str::FromStr;
 
 use gtk::glib;
 
+use crate::core::i18n;
+
 /// A [HabitCategory] is a type of habit.
 #[derive(
     Debug,
@@ -40,6 +42,36 @@ impl HabitCategory {
     pub fn as_str(&self) -> &str {
         self.as_ref()
     }
+
+    pub fn iter() -> impl Iterator<Item = HabitCategory> {
+        vec![
+            HabitCategory::Body,
+            HabitCategory::Mind,
+            HabitCategory::Health,
+            HabitCategory::Study,
+            HabitCategory::Productivity,
+            HabitCategory::Finance,
+            HabitCategory::Social,
+            HabitCategory::Abstraction,
+            HabitCategory::Other,
+        ]
+        .into_iter()
+    }
+
+    /// Human readable, translated name of the category.
+    pub fn label(&self) -> String {
+        match self {
+            HabitCategory::Body => i18n("Body"),
+            HabitCategory::Mind => i18n("Mind"),
+            HabitCategory::Health => i18n("Health"),
+            HabitCategory::Study => i18n("Study"),
+            HabitCategory::Productivity => i18n("Productivity"),
+            HabitCategory::Finance => i18n("Finance"),
+            HabitCategory::Social => i18n("Social"),
+            HabitCategory::Abstraction => i18n("Abstraction"),
+            HabitCategory::Other => i18n("Other"),
+        }
+    }
 }
 
 impl serde::Serialize for HabitCategory {
@@ -71,6 +103,11 @@ mod tests {
         assert_eq!(HabitCategory::default(), HabitCategory::Other);
     }
 
+    #[test]
+    fn test_habit_categories_count() {
+        assert_eq!(9, HabitCategory::iter().count());
+    }
+
     #[test]
     fn deserialize_habit_category() {
         assert_eq!(HabitCategory::from_str("body"), Ok(HabitCategory::Body));
@@ -1,9 +1,11 @@
-use crate::models::{habit_category::HabitCategory, habit_name::HabitName};
+use crate::models::{habit_category::HabitCategory, HabitPreset};
 use std::collections::HashMap;
 
+/// Maps habits, by the id of the [HabitPreset] they were made from, to their
+/// categories and back.
 pub struct HabitCategoryMap {
-    pub categories_per_habit: HashMap<HabitName, Vec<HabitCategory>>,
-    pub habits_per_category: HashMap<HabitCategory, Vec<HabitName>>,
+    pub categories_per_habit: HashMap<String, Vec<HabitCategory>>,
+    pub habits_per_category: HashMap<HabitCategory, Vec<String>>,
 }
 
 impl Default for HabitCategoryMap {
@@ -23,238 +25,90 @@ impl HabitCategoryMap {
         }
     }
 
+    /// Builds the map from the categories of all [HabitPreset]s.
     pub fn default() -> Self {
-        let mut categories_per_habit = HashMap::new();
-        let mut habits_per_category = HashMap::new();
-        let body_habits = vec![HabitName::Exercise, HabitName::Walking, HabitName::Running];
-        let mind_habits = vec![HabitName::Meditation, HabitName::Reading];
-        let health_habits = vec![
-            HabitName::NoSmoking,
-            HabitName::NoDrinking,
-            HabitName::NoSugar,
-            HabitName::NoFastFood,
-        ];
-        let study_habits = vec![
-            HabitName::Learning,
-            HabitName::OnlineCourse,
-            HabitName::LearningLanguage,
-        ];
-        let productivity_habits = vec![HabitName::Programming, HabitName::Writing];
-        let finance_habits = vec![HabitName::Investing, HabitName::SavingMoney];
-        let social_habits = vec![HabitName::Socializing];
-        let abstraction_habits = vec![HabitName::Drawing, HabitName::Music, HabitName::Journaling];
-        let other_habits = vec![
-            HabitName::Cooking,
-            HabitName::Cleaning,
-            HabitName::Gardening,
-            HabitName::Swimming,
-            HabitName::Yoga,
-        ];
-
-        for &category in &[
-            HabitCategory::Body,
-            HabitCategory::Mind,
-            HabitCategory::Health,
-            HabitCategory::Study,
-            HabitCategory::Productivity,
-            HabitCategory::Finance,
-            HabitCategory::Social,
-            HabitCategory::Abstraction,
-            HabitCategory::Other,
-        ] {
-            habits_per_category.insert(category, Vec::new());
-        }
-
-        for habit_name in body_habits
-            .iter()
-            .chain(mind_habits.iter())
-            .chain(health_habits.iter())
-            .chain(study_habits.iter())
-            .chain(productivity_habits.iter())
-            .chain(finance_habits.iter())
-            .chain(social_habits.iter())
-            .chain(abstraction_habits.iter())
-            .chain(other_habits.iter())
-        {
-            categories_per_habit
-                .entry(*habit_name)
-                .or_insert_with(Vec::new);
-        }
-
-        for habit_name in body_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Body);
-            habits_per_category
-                .entry(HabitCategory::Body)
-                .or_default()
-                .push(habit_name);
-        }
-
-        for habit_name in mind_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Mind);
-            habits_per_category
-                .entry(HabitCategory::Mind)
-                .or_default()
-                .push(habit_name);
-        }
-
-        for habit_name in health_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Health);
-            habits_per_category
-                .entry(HabitCategory::Health)
-                .or_default()
-                .push(habit_name);
-        }
-
-        for habit_name in study_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Study);
-            habits_per_category
-                .entry(HabitCategory::Study)
-                .or_default()
-                .push(habit_name);
-        }
-
-        for habit_name in productivity_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Productivity);
-            habits_per_category
-                .entry(HabitCategory::Productivity)
-                .or_default()
-                .push(habit_name);
-        }
-
-        for habit_name in finance_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Finance);
-            habits_per_category
-                .entry(HabitCategory::Finance)
-                .or_default()
-                .push(habit_name);
-        }
-
-        for habit_name in social_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Social);
-            habits_per_category
-                .entry(HabitCategory::Social)
-                .or_default()
-                .push(habit_name);
-        }
+        Self::from_presets(HabitPreset::all())
+    }
 
-        for habit_name in abstraction_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Abstraction);
-            habits_per_category
-                .entry(HabitCategory::Abstraction)
-                .or_default()
-                .push(habit_name);
+    pub fn from_presets(presets: &[HabitPreset]) -> Self {
+        let mut map = Self::new();
+        for category in HabitCategory::iter() {
+            map.habits_per_category.insert(category, Vec::new());
         }
 
-        for habit_name in other_habits {
-            categories_per_habit
-                .entry(habit_name)
-                .or_default()
-                .push(HabitCategory::Other);
-            habits_per_category
-                .entry(HabitCategory::Other)
-                .or_default()
-                .push(habit_name);
+        for preset in presets {
+            for category in &preset.categories {
+                map.add(preset.id.as_str(), *category);
+            }
         }
 
-        Self {
-            categories_per_habit,
-            habits_per_category,
-        }
+        map
     }
 
-    pub fn add(&mut self, habit_name: HabitName, habit_category: HabitCategory) {
-        if !self.categories_per_habit.contains_key(&habit_name) {
-            self.categories_per_habit
-                .insert(habit_name, vec![habit_category]);
-        } else {
-            let categories = self.categories_per_habit.get_mut(&habit_name).unwrap();
-            if !categories.contains(&habit_category) {
-                categories.push(habit_category);
-            }
+    pub fn add(&mut self, preset_id: impl Into<String>, habit_category: HabitCategory) {
+        let preset_id = preset_id.into();
+        let categories = self
+            .categories_per_habit
+            .entry(preset_id.clone())
+            .or_default();
+        if !categories.contains(&habit_category) {
+            categories.push(habit_category);
         }
 
-        if !self.habits_per_category.contains_key(&habit_category) {
-            self.habits_per_category
-                .insert(habit_category, vec![habit_name]);
-        } else {
-            let habits = self.habits_per_category.get_mut(&habit_category).unwrap();
-            if !habits.contains(&habit_name) {
-                habits.push(habit_name);
-            }
+        let habits = self.habits_per_category.entry(habit_category).or_default();
+        if !habits.contains(&preset_id) {
+            habits.push(preset_id);
         }
     }
 
     pub fn insert_habit_with_categories(
         &mut self,
-        habit_name: HabitName,
+        preset_id: impl Into<String>,
         habit_categories: Vec<HabitCategory>,
     ) {
+        let preset_id = preset_id.into();
         self.categories_per_habit
-            .insert(habit_name, habit_categories.clone());
+            .insert(preset_id.clone(), habit_categories.clone());
 
         for category in habit_categories {
             self.habits_per_category
                 .entry(category)
-                .or_insert_with(Vec::new)
-                .push(habit_name.clone());
+                .or_default()
+                .push(preset_id.clone());
         }
     }
 
     pub fn insert_category_with_habits(
         &mut self,
         habit_category: HabitCategory,
-        habit_names: Vec<HabitName>,
+        preset_ids: Vec<String>,
     ) {
         self.habits_per_category
-            .insert(habit_category, habit_names.clone());
+            .insert(habit_category, preset_ids.clone());
 
-        for habit_name in habit_names {
+        for preset_id in preset_ids {
             self.categories_per_habit
-                .entry(habit_name)
-                .or_insert_with(Vec::new)
-                .push(habit_category.clone());
+                .entry(preset_id)
+                .or_default()
+                .push(habit_category);
         }
     }
 
-    pub fn remove(&mut self, habit_name: &HabitName, habit_category: HabitCategory) {
-        if let Some(categories) = self.categories_per_habit.get_mut(habit_name) {
+    pub fn remove(&mut self, preset_id: &str, habit_category: HabitCategory) {
+        if let Some(categories) = self.categories_per_habit.get_mut(preset_id) {
             categories.retain(|&category| category != habit_category);
         }
 
-        if let Some(habit_names) = self.habits_per_category.get_mut(&habit_category) {
-            habit_names.retain(|&name| name != *habit_name);
+        if let Some(preset_ids) = self.habits_per_category.get_mut(&habit_category) {
+            preset_ids.retain(|id| id != preset_id);
         }
     }
 
-    pub fn get_categories(&self, habit_name: &HabitName) -> Option<&Vec<HabitCategory>> {
-        self.categories_per_habit.get(habit_name)
+    pub fn get_categories(&self, preset_id: &str) -> Option<&Vec<HabitCategory>> {
+        self.categories_per_habit.get(preset_id)
     }
 
-    pub fn get_habits(&self, category: &HabitCategory) -> Option<&Vec<HabitName>> {
+    pub fn get_habits(&self, category: &HabitCategory) -> Option<&Vec<String>> {
         self.habits_per_category.get(category)
     }
 }
@@ -262,12 +116,22 @@ impl HabitCategoryMap {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::{habit_preset::BUNDLED_PRESETS, HabitName};
     use std::collections::HashSet;
     use std::iter::Iterator;
 
+    // The map of the shipped presets only, whatever is in the user's presets dir
+    fn bundled_map() -> HabitCategoryMap {
+        HabitCategoryMap::from_presets(&HabitPreset::parse(BUNDLED_PRESETS).unwrap())
+    }
+
+    fn ids(names: &[HabitName]) -> Vec<String> {
+        names.iter().map(|name| name.as_str().to_string()).collect()
+    }
+
     #[test]
     fn test_default() {
-        let habit_category_map = HabitCategoryMap::default();
+        let habit_category_map = bundled_map();
         assert_eq!(habit_category_map.categories_per_habit.len(), 25);
         assert_eq!(habit_category_map.habits_per_category.len(), 9);
     }
@@ -281,18 +145,18 @@ mod tests {
 
     #[test]
     fn test_categories_per_habit() {
-        let habit_category_map = HabitCategoryMap::default();
+        let habit_category_map = bundled_map();
         assert_eq!(
             habit_category_map
                 .categories_per_habit
-                .get(&HabitName::Exercise)
+                .get(HabitName::Exercise.as_str())
                 .unwrap(),
             &[HabitCategory::Body]
         );
         assert_eq!(
             habit_category_map
                 .categories_per_habit
-                .get(&HabitName::Meditation)
+                .get(HabitName::Meditation.as_str())
                 .unwrap(),
             &[HabitCategory::Mind]
         );
@@ -300,64 +164,64 @@ mod tests {
 
     #[test]
     fn test_habits_per_category() {
-        let habit_category_map = HabitCategoryMap::default();
+        let habit_category_map = bundled_map();
         assert_eq!(
             habit_category_map
                 .habits_per_category
                 .get(&HabitCategory::Body)
                 .unwrap(),
-            &[HabitName::Exercise, HabitName::Walking, HabitName::Running]
+            &ids(&[HabitName::Exercise, HabitName::Walking, HabitName::Running])
         );
         assert_eq!(
             habit_category_map
                 .habits_per_category
                 .get(&HabitCategory::Mind)
                 .unwrap(),
-            &[HabitName::Meditation, HabitName::Reading]
+            &ids(&[HabitName::Meditation, HabitName::Reading])
         );
         assert_eq!(
             habit_category_map
                 .habits_per_category
                 .get(&HabitCategory::Health)
                 .unwrap(),
-            &[
+            &ids(&[
                 HabitName::NoSmoking,
                 HabitName::NoDrinking,
                 HabitName::NoSugar,
                 HabitName::NoFastFood
-            ]
+            ])
         );
         assert_eq!(
             habit_category_map
                 .habits_per_category
                 .get(&HabitCategory::Other)
                 .unwrap(),
-            &[
+            &ids(&[
                 HabitName::Cooking,
                 HabitName::Cleaning,
                 HabitName::Gardening,
                 HabitName::Swimming,
                 HabitName::Yoga
-            ]
+            ])
         );
     }
 
     #[test]
     fn test_add_habit_category() {
-        let mut habit_category_map = HabitCategoryMap::default();
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Body);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Mind);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Health);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Study);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Productivity);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Finance);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Social);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Abstraction);
-        habit_category_map.add(HabitName::Exercise, HabitCategory::Other);
+        let mut habit_category_map = bundled_map();
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Body);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Mind);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Health);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Study);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Productivity);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Finance);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Social);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Abstraction);
+        habit_category_map.add(HabitName::Exercise.as_str(), HabitCategory::Other);
 
         assert!(habit_category_map
             .categories_per_habit
-            .get(&HabitName::Exercise)
+            .get(HabitName::Exercise.as_str())
             .unwrap()
             .iter()
             .all(|&category| {
@@ -380,15 +244,15 @@ mod tests {
             .get(&HabitCategory::Health)
             .unwrap()
             .iter()
-            .all(|&habit_name| {
-                vec![
+            .all(|habit_name| {
+                ids(&[
                     HabitName::Exercise,
                     HabitName::NoSmoking,
                     HabitName::NoDrinking,
                     HabitName::NoSugar,
                     HabitName::NoFastFood,
-                ]
-                .contains(&habit_name)
+                ])
+                .contains(habit_name)
             }));
 
         assert!(habit_category_map
@@ -396,13 +260,13 @@ mod tests {
             .get(&HabitCategory::Finance)
             .unwrap()
             .iter()
-            .all(|&habit_name| {
-                vec![
+            .all(|habit_name| {
+                ids(&[
                     HabitName::Exercise,
                     HabitName::Investing,
                     HabitName::SavingMoney,
-                ]
-                .contains(&habit_name)
+                ])
+                .contains(habit_name)
             }));
 
         assert!(habit_category_map
@@ -410,21 +274,21 @@ mod tests {
             .get(&HabitCategory::Social)
             .unwrap()
             .iter()
-            .all(|&habit_name| {
-                vec![HabitName::Exercise, HabitName::Socializing].contains(&habit_name)
+            .all(|habit_name| {
+                ids(&[HabitName::Exercise, HabitName::Socializing]).contains(habit_name)
             }));
     }
 
     #[test]
     fn test_insert_habit_with_categories() {
         let mut hcm = HabitCategoryMap::new();
-        let habit = HabitName::Exercise;
+        let habit = HabitName::Exercise.as_str().to_string();
         let categories = vec![HabitCategory::Body, HabitCategory::Productivity];
-        hcm.insert_habit_with_categories(habit, categories.clone());
+        hcm.insert_habit_with_categories(habit.clone(), categories.clone());
         assert_eq!(hcm.categories_per_habit.get(&habit).unwrap(), &categories);
         assert_eq!(
             hcm.habits_per_category.get(&HabitCategory::Body).unwrap(),
-            &[habit]
+            &[habit.clone()]
         );
         assert_eq!(
             hcm.habits_per_category
@@ -438,12 +302,12 @@ mod tests {
     fn test_insert_category_with_habits() {
         let mut hcm = HabitCategoryMap::new();
         let category = HabitCategory::Health;
-        let habits = vec![
+        let habits = ids(&[
             HabitName::NoSmoking,
             HabitName::NoDrinking,
             HabitName::NoSugar,
             HabitName::NoFastFood,
-        ];
+        ]);
         hcm.insert_category_with_habits(category, habits.clone());
         let habits_set: HashSet<_> = habits.iter().collect();
         assert_eq!(
@@ -464,12 +328,12 @@ mod tests {
 
     #[test]
     fn test_remove() {
-        let mut habit_category_map = HabitCategoryMap::default();
-        habit_category_map.remove(&HabitName::Exercise, HabitCategory::Body);
+        let mut habit_category_map = bundled_map();
+        habit_category_map.remove(HabitName::Exercise.as_str(), HabitCategory::Body);
         assert_eq!(
             habit_category_map
                 .categories_per_habit
-                .get(&HabitName::Exercise)
+                .get(HabitName::Exercise.as_str())
                 .unwrap(),
             &[]
         );
@@ -478,34 +342,34 @@ mod tests {
                 .habits_per_category
                 .get(&HabitCategory::Body)
                 .unwrap(),
-            &[HabitName::Walking, HabitName::Running]
+            &ids(&[HabitName::Walking, HabitName::Running])
         );
     }
 
     #[test]
     fn test_get_categories() {
-        let habit_category_map = HabitCategoryMap::default();
+        let habit_category_map = bundled_map();
         assert_eq!(
             habit_category_map
-                .get_categories(&HabitName::Exercise)
+                .get_categories(HabitName::Exercise.as_str())
                 .unwrap(),
             &[HabitCategory::Body]
         );
         assert_eq!(
             habit_category_map
-                .get_categories(&HabitName::NoSmoking)
+                .get_categories(HabitName::NoSmoking.as_str())
                 .unwrap(),
             &[HabitCategory::Health]
         );
         assert_eq!(
             habit_category_map
-                .get_categories(&HabitName::Learning)
+                .get_categories(HabitName::Learning.as_str())
                 .unwrap(),
             &[HabitCategory::Study]
         );
         assert_eq!(
             habit_category_map
-                .get_categories(&HabitName::Cooking)
+                .get_categories(HabitName::Cooking.as_str())
                 .unwrap(),
             &[HabitCategory::Other]
         );
@@ -513,44 +377,44 @@ mod tests {
 
     #[test]
     fn test_get_habits() {
-        let habit_category_map = HabitCategoryMap::default();
+        let habit_category_map = bundled_map();
         assert_eq!(
             habit_category_map.get_habits(&HabitCategory::Body).unwrap(),
-            &[HabitName::Exercise, HabitName::Walking, HabitName::Running]
+            &ids(&[HabitName::Exercise, HabitName::Walking, HabitName::Running])
         );
         assert_eq!(
             habit_category_map
                 .get_habits(&HabitCategory::Study)
                 .unwrap(),
-            &[
+            &ids(&[
                 HabitName::Learning,
                 HabitName::OnlineCourse,
                 HabitName::LearningLanguage
-            ]
+            ])
         );
         assert_eq!(
             habit_category_map
                 .get_habits(&HabitCategory::Productivity)
                 .unwrap(),
-            &[HabitName::Programming, HabitName::Writing]
+            &ids(&[HabitName::Programming, HabitName::Writing])
         );
         assert_eq!(
             habit_category_map
                 .get_habits(&HabitCategory::Abstraction)
                 .unwrap(),
-            &[HabitName::Drawing, HabitName::Music, HabitName::Journaling]
+            &ids(&[HabitName::Drawing, HabitName::Music, HabitName::Journaling])
         );
         assert_eq!(
             habit_category_map
                 .get_habits(&HabitCategory::Other)
                 .unwrap(),
-            &[
+            &ids(&[
                 HabitName::Cooking,
                 HabitName::Cleaning,
                 HabitName::Gardening,
                 HabitName::Swimming,
                 HabitName::Yoga
-            ]
+            ])
         );
     }
 }
@@ -1,15 +1,77 @@
 use crate::{
-    core::{database, serialize},
+    core::{database, date::GDateTimeExt, serialize, sync},
     schema::habit_entry,
 };
 use diesel::prelude::*;
 use gtk::{glib, prelude::*, subclass::prelude::*};
 
+/// Changes to make to a [HabitEntry] with [HabitEntry::update]. Only the
+/// fields that are set are changed, e.g. `UpdateHabitEntry::new().value(30)`.
+#[derive(Debug, Clone, Default)]
 pub struct UpdateHabitEntry {
-    user_id: i32,
-    habit_id: i32,
-    note: Option<String>,
-    value: i32,
+    user_id: Option<u32>,
+    habit_id: Option<u32>,
+    entry_time: Option<glib::DateTime>,
+    note: Option<Option<String>>,
+    value: Option<i32>,
+}
+
+impl UpdateHabitEntry {
+    /// An update that doesn't change anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether no field is changed.
+    pub fn is_empty(&self) -> bool {
+        self.user_id.is_none()
+            && self.habit_id.is_none()
+            && self.entry_time.is_none()
+            && self.note.is_none()
+            && self.value.is_none()
+    }
+
+    #[must_use]
+    pub fn user_id(mut self, user_id: u32) -> Self {
+        self.user_id = Some(user_id);
+        self
+    }
+
+    #[must_use]
+    pub fn habit_id(mut self, habit_id: u32) -> Self {
+        self.habit_id = Some(habit_id);
+        self
+    }
+
+    #[must_use]
+    pub fn entry_time(mut self, entry_time: glib::DateTime) -> Self {
+        self.entry_time = Some(entry_time);
+        self
+    }
+
+    #[must_use]
+    pub fn note(mut self, note: Option<String>) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    #[must_use]
+    pub fn value(mut self, value: i32) -> Self {
+        self.value = Some(value);
+        self
+    }
+}
+
+/// The columns an [UpdateHabitEntry] changes, serialized as they are stored.
+#[derive(AsChangeset)]
+#[diesel(table_name = habit_entry)]
+struct HabitEntryChangeset {
+    user_id: Option<i32>,
+    habit_id: Option<i32>,
+    entry_time: Option<String>,
+    note: Option<Option<String>>,
+    value: Option<i32>,
+    updated_at: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -20,6 +82,8 @@ struct NewHabitEntry {
     entry_time: String,
     note: Option<String>,
     value: i32,
+    uuid: Option<String>,
+    updated_at: Option<String>,
 }
 
 #[derive(Queryable, Identifiable)]
@@ -31,6 +95,10 @@ struct DieselHabitEntry {
     entry_time: String,
     note: Option<String>,
     value: i32,
+    #[allow(dead_code)]
+    uuid: Option<String>,
+    #[allow(dead_code)]
+    updated_at: Option<String>,
 }
 #[doc(hidden)]
 mod imp {
@@ -118,64 +186,87 @@ impl HabitEntry {
         habit_id: u32,
         note: Option<String>,
         value: i32,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::create_at(
+            user_id,
+            habit_id,
+            &glib::DateTime::now_local().unwrap(),
+            note,
+            value,
+        )
+    }
+
+    /// Like [HabitEntry::create], for an entry made at `entry_time` instead of now.
+    pub fn create_at(
+        user_id: u32,
+        habit_id: u32,
+        entry_time: &glib::DateTime,
+        note: Option<String>,
+        value: i32,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let db = database::connection();
         let mut conn = db.get()?;
-        let curr_time = glib::DateTime::now_local()
-            .unwrap()
-            .format_iso8601()
-            .unwrap()
-            .to_string();
 
         let new_habit = NewHabitEntry {
             user_id: user_id as i32,
             habit_id: habit_id as i32,
-            entry_time: curr_time.clone(),
+            entry_time: entry_time.format_iso8601_local()?.to_string(),
             note,
             value,
+            uuid: Some(sync::new_uuid()),
+            // The entry time may lie in the past, so mark when it was really made
+            updated_at: Some(sync::now()),
         };
 
         diesel::insert_into(habit_entry::table)
             .values(&new_habit)
             .execute(&mut conn)?;
 
-        habit_entry::table
+        let habit_entry = habit_entry::table
             .order(habit_entry::columns::id.desc())
             .first::<DieselHabitEntry>(&mut conn)
-            .map_err(From::from)
-            .map(|habit_entry| {
-                Self::new(
-                    habit_entry.id as u32,
-                    habit_entry.user_id as u32,
-                    habit_entry.habit_id as u32,
-                    glib::DateTime::from_iso8601(habit_entry.entry_time.as_str(), None).unwrap(),
-                    habit_entry.note,
-                    habit_entry.value,
-                )
-                .unwrap()
-            })
+            .map(Self::from_diesel)?;
+        Ok(habit_entry)
     }
 
-    pub fn update(
-        &self,
-        updated_habit_entry: &UpdateHabitEntry,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+    /// Saves the changes of `update` and applies them to this entry.
+    pub fn update(&self, update: &UpdateHabitEntry) -> Result<(), Box<dyn std::error::Error>> {
+        if update.is_empty() {
+            return Ok(());
+        }
         let db = database::connection();
         let mut conn = db.get()?;
-        let target = habit_entry::table.filter(habit_entry::columns::id.eq(self.id() as i32));
-        diesel::update(target)
-            .set((
-                habit_entry::columns::user_id.eq(updated_habit_entry.user_id),
-                habit_entry::columns::habit_id.eq(updated_habit_entry.habit_id),
-                habit_entry::columns::note.eq(updated_habit_entry.note.clone()),
-                habit_entry::columns::value.eq(updated_habit_entry.value),
-            ))
+        let changeset = HabitEntryChangeset {
+            user_id: update.user_id.map(|user_id| user_id as i32),
+            habit_id: update.habit_id.map(|habit_id| habit_id as i32),
+            entry_time: update
+                .entry_time
+                .as_ref()
+                .map(|entry_time| entry_time.format_iso8601_local().map(String::from))
+                .transpose()?,
+            note: update.note.clone(),
+            value: update.value,
+            updated_at: Some(sync::now()),
+        };
+        diesel::update(habit_entry::table.filter(habit_entry::columns::id.eq(self.id() as i32)))
+            .set(&changeset)
             .execute(&mut conn)?;
 
-        self.set_user_id(updated_habit_entry.user_id as u32);
-        self.set_habit_id(updated_habit_entry.habit_id as u32);
-        self.set_note(updated_habit_entry.note.clone());
-        self.set_value(updated_habit_entry.value);
+        if let Some(user_id) = update.user_id {
+            self.set_user_id(user_id);
+        }
+        if let Some(habit_id) = update.habit_id {
+            self.set_habit_id(habit_id);
+        }
+        if let Some(entry_time) = &update.entry_time {
+            self.set_entry_time(entry_time.clone());
+        }
+        if let Some(note) = &update.note {
+            self.set_note(note.clone());
+        }
+        if let Some(value) = update.value {
+            self.set_value(value);
+        }
 
         Ok(())
     }
@@ -187,17 +278,7 @@ impl HabitEntry {
             .filter(habit_entry::columns::id.eq(id as i32))
             .first::<DieselHabitEntry>(&mut conn)
             .map_err(From::from)
-            .map(|habit_entry| {
-                Self::new(
-                    habit_entry.id as u32,
-                    habit_entry.user_id as u32,
-                    habit_entry.habit_id as u32,
-                    glib::DateTime::from_iso8601(habit_entry.entry_time.as_str(), None).unwrap(),
-                    habit_entry.note,
-                    habit_entry.value,
-                )
-                .unwrap()
-            })
+            .map(Self::from_diesel)
     }
 
     pub fn find_all() -> Result<Vec<Self>, Box<dyn std::error::Error>> {
@@ -207,32 +288,54 @@ impl HabitEntry {
             .order(habit_entry::columns::id.desc())
             .load::<DieselHabitEntry>(&mut conn)
             .map_err(From::from)
-            .map(|habits_entry| {
-                habits_entry
-                    .into_iter()
-                    .map(|habit_entry| {
-                        Self::new(
-                            habit_entry.id as u32,
-                            habit_entry.user_id as u32,
-                            habit_entry.habit_id as u32,
-                            glib::DateTime::from_iso8601(habit_entry.entry_time.as_str(), None)
-                                .unwrap(),
-                            habit_entry.note,
-                            habit_entry.value,
-                        )
-                        .unwrap()
-                    })
-                    .collect()
-            })
+            .map(|habit_entries| habit_entries.into_iter().map(Self::from_diesel).collect())
+    }
+
+    pub fn find_by_habit(habit_id: u32) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        let db = database::connection();
+        let mut conn = db.get()?;
+        habit_entry::table
+            .filter(habit_entry::columns::habit_id.eq(habit_id as i32))
+            .order(habit_entry::columns::id.asc())
+            .load::<DieselHabitEntry>(&mut conn)
+            .map_err(From::from)
+            .map(|habit_entries| habit_entries.into_iter().map(Self::from_diesel).collect())
+    }
+
+    pub fn find_by_user(user_id: u32) -> Result<Vec<Self>, Box<dyn std::error::Error>> {
+        let db = database::connection();
+        let mut conn = db.get()?;
+        habit_entry::table
+            .filter(habit_entry::columns::user_id.eq(user_id as i32))
+            .order(habit_entry::columns::id.asc())
+            .load::<DieselHabitEntry>(&mut conn)
+            .map_err(From::from)
+            .map(|habit_entries| habit_entries.into_iter().map(Self::from_diesel).collect())
     }
 
     pub fn delete(&self) -> Result<(), Box<dyn std::error::Error>> {
         let db = database::connection();
         let mut conn = db.get()?;
-        diesel::delete(habit_entry::table.filter(habit_entry::columns::id.eq(self.id() as i32)))
-            .execute(&mut conn)?;
+        conn.transaction(|conn| {
+            let target = habit_entry::table.filter(habit_entry::columns::id.eq(self.id() as i32));
+            let uuid = target.select(habit_entry::columns::uuid).first(conn)?;
+            sync::record_deletion(conn, "habit_entry", uuid)?;
+            diesel::delete(target).execute(conn)
+        })?;
         Ok(())
     }
+
+    fn from_diesel(habit_entry: DieselHabitEntry) -> Self {
+        Self::new(
+            habit_entry.id as u32,
+            habit_entry.user_id as u32,
+            habit_entry.habit_id as u32,
+            glib::DateTime::from_iso8601(habit_entry.entry_time.as_str(), None).unwrap(),
+            habit_entry.note,
+            habit_entry.value,
+        )
+        .unwrap()
+    }
 }
 
 #[cfg(test)]
@@ -259,12 +362,11 @@ mod tests {
         let habit_entry_search_all = HabitEntry::find_all().unwrap();
         assert_eq!(habit_entry_search_all.len(), 1);
 
-        let updated_habit_entry = UpdateHabitEntry {
-            user_id: 2,
-            habit_id: 2,
-            note: Some("test2".to_string()),
-            value: 2,
-        };
+        let updated_habit_entry = UpdateHabitEntry::new()
+            .user_id(2)
+            .habit_id(2)
+            .note(Some("test2".to_string()))
+            .value(2);
 
         assert!(habit_entry.update(&updated_habit_entry).is_ok());
         assert_eq!(habit_entry.user_id(), 2);
@@ -272,6 +374,15 @@ mod tests {
         assert_eq!(habit_entry.note(), Some("test2".to_string()));
         assert_eq!(habit_entry.value(), 2);
 
+        // Fields that aren't set are left as they are
+        let entry_time = glib::DateTime::from_local(2024, 5, 1, 7, 30, 0.0).unwrap();
+        let updated_habit_entry = UpdateHabitEntry::new().entry_time(entry_time.clone());
+        assert!(habit_entry.update(&updated_habit_entry).is_ok());
+        let habit_entry_search = HabitEntry::find(habit_entry.id()).unwrap();
+        assert_eq!(habit_entry_search.entry_time(), entry_time);
+        assert_eq!(habit_entry_search.note(), Some("test2".to_string()));
+        assert_eq!(habit_entry_search.value(), 2);
+
         assert!(habit_entry.delete().is_ok());
     }
 }
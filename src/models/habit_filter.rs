@@ -0,0 +1,237 @@
+use gtk::{glib, prelude::*, subclass::prelude::*};
+
+use crate::core::date::Date;
+use crate::models::{Habit, HabitCategory, Weekday};
+
+/// What a [HabitFilter] lets through.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HabitFilterOptions {
+    /// Only habits in this category
+    pub category: Option<HabitCategory>,
+    /// Whether archived habits are shown too
+    pub show_archived: bool,
+    /// Only habits due today
+    pub due_today: bool,
+    /// Matched case-insensitively against the name and description
+    pub search: String,
+}
+
+impl HabitFilterOptions {
+    /// Whether `habit` should be shown when today falls on `weekday`.
+    pub fn matches(&self, habit: &Habit, weekday: Weekday) -> bool {
+        if habit.archived() && !self.show_archived {
+            return false;
+        }
+        if let Some(category) = self.category {
+            let in_category = habit
+                .categories()
+                .map_or(false, |categories| categories.contains(&category));
+            if !in_category {
+                return false;
+            }
+        }
+        if self.due_today && !habit.frequency().is_due_on(weekday) {
+            return false;
+        }
+
+        let search = self.search.trim().to_lowercase();
+        search.is_empty()
+            || habit.display_name().to_lowercase().contains(&search)
+            || habit.description().map_or(false, |description| {
+                description.to_lowercase().contains(&search)
+            })
+    }
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct HabitFilter {
+        pub options: RefCell<HabitFilterOptions>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for HabitFilter {
+        const NAME: &'static str = "HabitFilter";
+        type Type = super::HabitFilter;
+        type ParentType = gtk::Filter;
+    }
+
+    impl ObjectImpl for HabitFilter {}
+
+    impl FilterImpl for HabitFilter {
+        fn match_(&self, item: &glib::Object) -> bool {
+            item.downcast_ref::<Habit>().map_or(false, |habit| {
+                self.options
+                    .borrow()
+                    .matches(habit, Date::today().weekday())
+            })
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Filters a list of [Habit]s, e.g. for a [gtk::FilterListModel].
+    pub struct HabitFilter(ObjectSubclass<imp::HabitFilter>)
+        @extends gtk::Filter;
+}
+
+impl HabitFilter {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn options(&self) -> HabitFilterOptions {
+        self.imp().options.borrow().clone()
+    }
+
+    pub fn set_category(&self, category: Option<HabitCategory>) {
+        let old = self.imp().options.borrow().category;
+        if old == category {
+            return;
+        }
+        self.imp().options.borrow_mut().category = category;
+        self.changed(match (old, category) {
+            (None, Some(_)) => gtk::FilterChange::MoreStrict,
+            (Some(_), None) => gtk::FilterChange::LessStrict,
+            _ => gtk::FilterChange::Different,
+        });
+    }
+
+    pub fn set_show_archived(&self, show_archived: bool) {
+        let mut options = self.imp().options.borrow_mut();
+        if options.show_archived == show_archived {
+            return;
+        }
+        options.show_archived = show_archived;
+        drop(options);
+        self.changed(if show_archived {
+            gtk::FilterChange::LessStrict
+        } else {
+            gtk::FilterChange::MoreStrict
+        });
+    }
+
+    pub fn set_due_today(&self, due_today: bool) {
+        let mut options = self.imp().options.borrow_mut();
+        if options.due_today == due_today {
+            return;
+        }
+        options.due_today = due_today;
+        drop(options);
+        self.changed(if due_today {
+            gtk::FilterChange::MoreStrict
+        } else {
+            gtk::FilterChange::LessStrict
+        });
+    }
+
+    pub fn set_search(&self, search: &str) {
+        let old = self.imp().options.borrow().search.trim().to_lowercase();
+        let new = search.trim().to_lowercase();
+        self.imp().options.borrow_mut().search = search.to_string();
+        if old == new {
+            return;
+        }
+        // Typing more of a word only hides habits, erasing only shows more
+        self.changed(if new.contains(&old) {
+            gtk::FilterChange::MoreStrict
+        } else if old.contains(&new) {
+            gtk::FilterChange::LessStrict
+        } else {
+            gtk::FilterChange::Different
+        });
+    }
+}
+
+impl Default for HabitFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Compare, DurationKind, Frequency, HabitName, UnitSystem};
+
+    fn habit(
+        title: &str,
+        categories: Vec<HabitCategory>,
+        weekdays: Option<Vec<Weekday>>,
+        archived: bool,
+    ) -> Habit {
+        Habit::new(
+            1,
+            1,
+            HabitName::Custom,
+            Some(title.to_string()),
+            Some("Before breakfast".to_string()),
+            Some(categories),
+            None,
+            Frequency::new(
+                Some(DurationKind::Daily),
+                Some(UnitSystem::Minutes),
+                Some(30),
+                weekdays,
+                Some(Compare::GreaterOrEqual),
+            ),
+            glib::DateTime::now_local().unwrap(),
+            None,
+            Vec::new(),
+            None,
+            archived,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_habit_filter_options() {
+        let running = habit("Running", vec![HabitCategory::Body], None, false);
+        let reading = habit(
+            "Reading",
+            vec![HabitCategory::Mind, HabitCategory::Study],
+            Some(vec![Weekday::Saturday, Weekday::Sunday]),
+            false,
+        );
+        let archived = habit("Juggling", vec![HabitCategory::Body], None, true);
+
+        let options = HabitFilterOptions::default();
+        assert!(options.matches(&running, Weekday::Monday));
+        assert!(!options.matches(&archived, Weekday::Monday));
+
+        let options = HabitFilterOptions {
+            show_archived: true,
+            category: Some(HabitCategory::Body),
+            ..Default::default()
+        };
+        assert!(options.matches(&running, Weekday::Monday));
+        assert!(!options.matches(&reading, Weekday::Monday));
+        assert!(options.matches(&archived, Weekday::Monday));
+
+        let options = HabitFilterOptions {
+            due_today: true,
+            ..Default::default()
+        };
+        assert!(options.matches(&running, Weekday::Monday));
+        assert!(!options.matches(&reading, Weekday::Monday));
+        assert!(options.matches(&reading, Weekday::Sunday));
+
+        let options = HabitFilterOptions {
+            search: " READ".to_string(),
+            ..Default::default()
+        };
+        assert!(options.matches(&reading, Weekday::Monday));
+        assert!(!options.matches(&running, Weekday::Monday));
+        let options = HabitFilterOptions {
+            search: "breakfast".to_string(),
+            ..Default::default()
+        };
+        assert!(options.matches(&running, Weekday::Monday));
+    }
+}
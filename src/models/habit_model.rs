@@ -1,13 +1,17 @@
 use crate::models::Habit;
 use gio::subclass::prelude::*;
-use gtk::{gio, glib, prelude::*};
+use gtk::{gio, glib, glib::clone, prelude::*};
 use std::cell::RefCell;
 
 mod imp {
     use super::*;
 
     #[derive(Debug, Default)]
-    pub struct HabitModel(pub(super) RefCell<Vec<Habit>>);
+    pub struct HabitModel {
+        pub(super) habits: RefCell<Vec<Habit>>,
+        /// Handlers of the `notify` signal of each habit, in the same order
+        pub(super) handlers: RefCell<Vec<glib::SignalHandlerId>>,
+    }
 
     /// Basic declaration of our type for the GObject type system
     #[glib::object_subclass]
@@ -17,17 +21,23 @@ mod imp {
         type Interfaces = (gio::ListModel,);
     }
 
-    impl ObjectImpl for HabitModel {}
+    impl ObjectImpl for HabitModel {
+        fn dispose(&self) {
+            for (habit, handler) in self.habits.take().iter().zip(self.handlers.take()) {
+                habit.disconnect(handler);
+            }
+        }
+    }
 
     impl ListModelImpl for HabitModel {
         fn item_type(&self) -> glib::Type {
             Habit::static_type()
         }
         fn n_items(&self) -> u32 {
-            self.0.borrow().len() as u32
+            self.habits.borrow().len() as u32
         }
         fn item(&self, position: u32) -> Option<glib::Object> {
-            self.0
+            self.habits
                 .borrow()
                 .get(position as usize)
                 .map(|o| o.clone().upcast::<glib::Object>())
@@ -52,20 +62,53 @@ impl HabitModel {
             // Borrow the data only once and ensure the borrow guard is dropped
             // before we emit the items_changed signal because the view
             // could call get_item / get_n_item from the signal handler to update its state
-            let mut data = imp.0.borrow_mut();
+            let mut data = imp.habits.borrow_mut();
             data.push(obj.clone());
             data.len() - 1
         };
+        imp.handlers.borrow_mut().push(self.watch(obj));
         // Emits a signal that 1 item was added, 0 removed at the position index
         self.items_changed(index as u32, 0, 1);
     }
 
+    /// Replaces all habits at once, e.g. after loading them again.
+    pub fn set_habits(&self, habits: Vec<Habit>) {
+        let imp = self.imp();
+        let handlers = habits.iter().map(|habit| self.watch(habit)).collect();
+        let old = imp.habits.replace(habits);
+        for (habit, handler) in old.iter().zip(imp.handlers.replace(handlers)) {
+            habit.disconnect(handler);
+        }
+        let (removed, added) = (old.len(), imp.habits.borrow().len());
+        self.items_changed(0, removed as u32, added as u32);
+    }
+
     pub fn remove(&self, index: u32) {
         let imp = self.imp();
-        imp.0.borrow_mut().remove(index as usize);
+        let habit = imp.habits.borrow_mut().remove(index as usize);
+        habit.disconnect(imp.handlers.borrow_mut().remove(index as usize));
         // Emits a signal that 1 item was removed, 0 added at the position index
         self.items_changed(index, 1, 0);
     }
+
+    // Tells the views that `habit` changed when one of its properties does,
+    // so they can update its row, filter and sort it again
+    fn watch(&self, habit: &Habit) -> glib::SignalHandlerId {
+        habit.connect_notify_local(
+            None,
+            clone!(@weak self as model => move |habit, _| {
+                let position = model
+                    .imp()
+                    .habits
+                    .borrow()
+                    .iter()
+                    .position(|other| other == habit);
+                if let Some(position) = position {
+                    model.items_changed(position as u32, 1, 1);
+                }
+            }),
+        )
+    }
 }
 
 impl Default for HabitModel {
@@ -73,3 +116,61 @@ impl Default for HabitModel {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Frequency, HabitName};
+    use std::{cell::RefCell, rc::Rc};
+
+    fn habit(id: u32) -> Habit {
+        Habit::new(
+            id,
+            1,
+            HabitName::Custom,
+            None,
+            None,
+            None,
+            None,
+            Frequency::default(),
+            glib::DateTime::now_local().unwrap(),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_habit_model_changes() {
+        let model = HabitModel::new();
+        let (first, second) = (habit(1), habit(2));
+        model.set_habits(vec![first.clone(), second.clone()]);
+
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        model.connect_items_changed(
+            clone!(@strong changes => move |_, position, removed, added| {
+                changes.borrow_mut().push((position, removed, added));
+            }),
+        );
+
+        second.set_title(Some("Stretching".to_string()));
+        second.set_frequency(Frequency::default());
+        assert_eq!(*changes.borrow(), vec![(1, 1, 1), (1, 1, 1)]);
+
+        model.remove(0);
+        first.set_archived(true);
+        second.set_archived(true);
+        assert_eq!(
+            *changes.borrow(),
+            vec![(1, 1, 1), (1, 1, 1), (0, 1, 0), (0, 1, 1)]
+        );
+
+        model.set_habits(Vec::new());
+        second.set_note(Some("Gone".to_string()));
+        assert_eq!(changes.borrow().last(), Some(&(0, 1, 0)));
+    }
+}
@@ -1,6 +1,6 @@
 use std::str::FromStr;
 
-use crate::models::{Compare, Frequency, UnitSystem};
+use crate::models::{Frequency, HabitCategory, HabitPreset};
 use gtk::glib;
 
 /// A [HabitName] is a particular habit.
@@ -82,6 +82,43 @@ pub struct HabitInfo {
     pub is_suitable_for_minors: bool,
     pub icon: Option<String>,
     pub frequency: Frequency,
+    pub categories: Vec<HabitCategory>,
+}
+
+impl From<&HabitPreset> for HabitInfo {
+    fn from(preset: &HabitPreset) -> Self {
+        Self {
+            name: preset.name.clone(),
+            description: preset.description.clone(),
+            is_suitable_for_minors: preset.is_suitable_for_minors,
+            icon: preset.icon.clone(),
+            frequency: preset.frequency.clone(),
+            categories: preset.categories.clone(),
+        }
+    }
+}
+
+impl HabitInfo {
+    /// The details of the [HabitPreset] with `id`. Unknown ids, e.g. of a
+    /// preset that was removed, get a bare description named after the id.
+    pub fn for_preset(id: &str) -> Self {
+        HabitPreset::find(id)
+            .map(HabitInfo::from)
+            .unwrap_or_else(|| HabitInfo {
+                name: id.to_string(),
+                description: String::new(),
+                is_suitable_for_minors: true,
+                icon: None,
+                frequency: Frequency::default(),
+                categories: Vec::new(),
+            })
+    }
+}
+
+impl From<HabitName> for String {
+    fn from(name: HabitName) -> Self {
+        name.as_str().to_string()
+    }
 }
 
 impl HabitName {
@@ -89,298 +126,9 @@ impl HabitName {
         self.as_ref()
     }
 
+    /// The details of the preset for this habit, see [HabitPreset].
     pub fn info(&self) -> HabitInfo {
-        match self {
-            HabitName::Exercise => HabitInfo {
-                name: "Exercise".to_string(),
-                description: "Perform any physical activity that gets your heart rate up."
-                    .to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(30),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Meditation => HabitInfo {
-                name: "Meditation".to_string(),
-                description: "Practice mindfulness and meditation.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(10),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Reading => HabitInfo {
-                name: "Reading".to_string(),
-                description: "Read any book or blog.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(30),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Writing => HabitInfo {
-                name: "Writing".to_string(),
-                description: "Write a blog post, journal entry, or any other text.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(30),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Programming => HabitInfo {
-                name: "Programming".to_string(),
-                description: "Write code for a project or learn a new programming language."
-                    .to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(30),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Learning => HabitInfo {
-                name: "Learning".to_string(),
-                description: "Learn a new skill or study for a test.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(30),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Drawing => HabitInfo {
-                name: "Drawing".to_string(),
-                description: "Draw a picture or sketch.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(30),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Music => HabitInfo {
-                name: "Music".to_string(),
-                description: "Play an instrument or sing.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(30),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Journaling => HabitInfo {
-                name: "Journaling".to_string(),
-                description: "Write a journal entry or diary.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::Walking => HabitInfo {
-                name: "Walking".to_string(),
-                description: "Go for a walk.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Steps),
-                    Some(10000),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Running => HabitInfo {
-                name: "Running".to_string(),
-                description: "Go for a run or jog.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Steps),
-                    Some(10000),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Cycling => HabitInfo {
-                name: "Cycling".to_string(),
-                description: "Go for a bike ride.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Kilometers),
-                    Some(5),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Swimming => HabitInfo {
-                name: "Swimming".to_string(),
-                description: "Go for a swim.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(20),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Yoga => HabitInfo {
-                name: "Yoga".to_string(),
-                description: "Practice yoga or stretching.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Minutes),
-                    Some(20),
-                    None,
-                    Some(Compare::GreaterOrEqual),
-                ),
-            },
-            HabitName::Cooking => HabitInfo {
-                name: "Cooking".to_string(),
-                description: "Cook a meal or bake something.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::Cleaning => HabitInfo {
-                name: "Cleaning".to_string(),
-                description: "Clean your home or workspace.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::Gardening => HabitInfo {
-                name: "Gardening".to_string(),
-                description: "Plant or tend to a garden.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::Socializing => HabitInfo {
-                name: "Socializing".to_string(),
-                description: "Spend time with friends or family.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::MartialArts => HabitInfo {
-                name: "Martial Arts".to_string(),
-                description: "Practice martial arts or self-defense.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::LearningLanguage => HabitInfo {
-                name: "Learning a Language".to_string(),
-                description: "Learn a new language or practice one you already know.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::Investing => HabitInfo {
-                name: "Investing".to_string(),
-                description: "Invest in stocks, bonds, or cryptocurrency.".to_string(),
-                is_suitable_for_minors: false,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::SavingMoney => HabitInfo {
-                name: "Saving Money".to_string(),
-                description: "Save money for a goal or retirement.".to_string(),
-                is_suitable_for_minors: false,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::OnlineCourse => HabitInfo {
-                name: "Online Course".to_string(),
-                description: "Take an online course or class.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-            HabitName::SleepTracking => HabitInfo {
-                name: "Sleep Tracking".to_string(),
-                description: "Track your sleep or take a nap.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(
-                    None,
-                    Some(UnitSystem::Hours),
-                    Some(8),
-                    None,
-                    Some(Compare::LessOrEqual),
-                ),
-            },
-            HabitName::NoSmoking => HabitInfo {
-                name: "No Smoking".to_string(),
-                description: "Don't smoke or use tobacco products.".to_string(),
-                is_suitable_for_minors: false,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(0), None, Some(Compare::LessOrEqual)),
-            },
-            HabitName::NoDrinking => HabitInfo {
-                name: "No Drinking".to_string(),
-                description: "Don't drink alcohol or use drugs.".to_string(),
-                is_suitable_for_minors: false,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(0), None, Some(Compare::LessOrEqual)),
-            },
-            HabitName::NoSugar => HabitInfo {
-                name: "No Sugar".to_string(),
-                description: "Don't eat any sugar or sweets.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(0), None, Some(Compare::LessOrEqual)),
-            },
-            HabitName::NoFastFood => HabitInfo {
-                name: "No Fast Food".to_string(),
-                description: "Don't eat any fast food or junk food.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(0), None, Some(Compare::Equal)),
-            },
-            HabitName::Custom => HabitInfo {
-                name: "Custom".to_string(),
-                description: "Create a custom habit.".to_string(),
-                is_suitable_for_minors: true,
-                icon: None,
-                frequency: Frequency::new(None, None, Some(1), None, Some(Compare::Equal)),
-            },
-        }
+        HabitInfo::for_preset(self.as_str())
     }
 
     pub fn iter() -> impl Iterator<Item = HabitName> {
@@ -422,7 +170,7 @@ impl HabitName {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::DurationKind;
+    use crate::models::{Compare, DurationKind, UnitSystem};
 
     #[test]
     fn test_habit_name_default() {
@@ -502,4 +250,20 @@ mod tests {
         assert!(frequency.is_one_time());
         assert_eq!(frequency.comparator, Compare::Equal);
     }
+
+    #[test]
+    fn test_habit_info_categories() {
+        assert_eq!(
+            HabitName::Exercise.info().categories,
+            vec![HabitCategory::Body]
+        );
+        assert_eq!(HabitName::Custom.info().categories, vec![]);
+    }
+
+    #[test]
+    fn test_habit_info_unknown_preset() {
+        let habit_info = HabitInfo::for_preset("removed_preset");
+        assert_eq!(habit_info.name, "removed_preset");
+        assert_eq!(habit_info.categories, vec![]);
+    }
 }
@@ -0,0 +1,204 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use gtk::glib;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Frequency, HabitCategory};
+
+pub(crate) const BUNDLED_PRESETS: &str = include_str!("../../data/resources/presets/habits.json");
+
+static PRESETS: Lazy<Vec<HabitPreset>> = Lazy::new(HabitPreset::load_all);
+
+/// A [HabitPreset] is a ready-made habit definition the user can start from.
+///
+/// The presets shipped with Declutter live in `data/resources/presets/habits.json`.
+/// Users can add their own, or override the shipped ones, by dropping JSON files
+/// with a list of presets into [HabitPreset::user_presets_dir].
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HabitPreset {
+    /// Identifies the preset, and is stored as the name of habits created from it.
+    /// Matches [HabitName::as_str](crate::models::HabitName::as_str) for the built-in habits.
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default = "default_is_suitable_for_minors")]
+    pub is_suitable_for_minors: bool,
+    #[serde(default)]
+    pub categories: Vec<HabitCategory>,
+    #[serde(default)]
+    pub frequency: Frequency,
+}
+
+fn default_is_suitable_for_minors() -> bool {
+    true
+}
+
+impl HabitPreset {
+    /// All available presets, the bundled ones first.
+    pub fn all() -> &'static [HabitPreset] {
+        &PRESETS
+    }
+
+    pub fn find(id: &str) -> Option<&'static HabitPreset> {
+        Self::all().iter().find(|preset| preset.id == id)
+    }
+
+    /// Parses a JSON list of presets.
+    pub fn parse(json: &str) -> Result<Vec<Self>> {
+        let presets: Vec<Self> = serde_json::from_str(json)?;
+        if let Some(preset) = presets.iter().find(|preset| preset.id.trim().is_empty()) {
+            anyhow::bail!("Preset \"{}\" has no id", preset.name);
+        }
+        Ok(presets)
+    }
+
+    pub fn user_presets_dir() -> PathBuf {
+        glib::user_data_dir().join("declutter").join("presets")
+    }
+
+    /// Loads the presets of all `*.json` files in `dir`. Files that can't be
+    /// read or parsed are skipped.
+    pub fn load_dir(dir: &Path) -> Vec<Self> {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut paths = read_dir
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+            .collect::<Vec<_>>();
+        // Make overrides between user files predictable
+        paths.sort();
+
+        paths
+            .into_iter()
+            .flat_map(|path| {
+                match fs::read_to_string(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|json| Self::parse(&json))
+                {
+                    Ok(presets) => presets,
+                    Err(e) => {
+                        tracing::warn!("Skipping habit presets in {}: {}", path.display(), e);
+                        Vec::new()
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The presets shipped with Declutter, without the user's.
+    pub fn bundled() -> Vec<Self> {
+        Self::parse(BUNDLED_PRESETS).expect("Bundled habit presets are invalid")
+    }
+
+    fn load_all() -> Vec<Self> {
+        Self::merge(Self::bundled(), Self::load_dir(&Self::user_presets_dir()))
+    }
+
+    /// Adds `overrides` to `presets`, replacing presets that share an id.
+    fn merge(mut presets: Vec<Self>, overrides: Vec<Self>) -> Vec<Self> {
+        for preset in overrides {
+            match presets.iter_mut().find(|p| p.id == preset.id) {
+                Some(existing) => *existing = preset,
+                None => presets.push(preset),
+            }
+        }
+        presets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Compare, DurationKind, HabitName, UnitSystem};
+
+    #[test]
+    fn test_bundled_presets_cover_habit_names() {
+        let presets = HabitPreset::parse(BUNDLED_PRESETS).unwrap();
+        for habit_name in HabitName::iter() {
+            assert!(
+                presets
+                    .iter()
+                    .any(|preset| preset.id == habit_name.as_str()),
+                "No preset for {}",
+                habit_name.as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let presets = HabitPreset::parse(
+            r#"[{
+                "id": "deep_work",
+                "name": "Deep Work",
+                "categories": ["productivity"],
+                "frequency": {
+                    "duration_kind": "daily",
+                    "unit": "minutes",
+                    "target_value": 120,
+                    "weekdays": ["monday", "tuesday", "wednesday", "thursday", "friday"],
+                    "comparator": "greater_or_equal"
+                }
+            }]"#,
+        )
+        .unwrap();
+        let preset = &presets[0];
+        assert_eq!(preset.id, "deep_work");
+        assert_eq!(preset.description, "");
+        assert!(preset.is_suitable_for_minors);
+        assert_eq!(preset.categories, vec![HabitCategory::Productivity]);
+        assert_eq!(preset.frequency.duration_kind, DurationKind::Daily);
+        assert_eq!(preset.frequency.unit, UnitSystem::Minutes);
+        assert_eq!(preset.frequency.target_value, 120);
+        assert_eq!(preset.frequency.comparator, Compare::GreaterOrEqual);
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(HabitPreset::parse(r#"[{"id": "", "name": "Nameless"}]"#).is_err());
+        assert!(HabitPreset::parse(r#"[{"id": "a", "name": "A", "categories": ["x"]}]"#).is_err());
+    }
+
+    #[test]
+    fn test_merge_overrides() {
+        let bundled = HabitPreset::parse(BUNDLED_PRESETS).unwrap();
+        let count = bundled.len();
+        let overrides = HabitPreset::parse(
+            r#"[
+                {"id": "exercise", "name": "Workout"},
+                {"id": "stretching", "name": "Stretching"}
+            ]"#,
+        )
+        .unwrap();
+        let presets = HabitPreset::merge(bundled, overrides);
+        assert_eq!(presets.len(), count + 1);
+        assert_eq!(presets[0].name, "Workout");
+        assert_eq!(presets[count].id, "stretching");
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = tempdir::TempDir::new("presets").unwrap();
+        fs::write(
+            dir.path().join("mine.json"),
+            r#"[{"id": "stretching", "name": "Stretching"}]"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("broken.json"), "{").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a preset").unwrap();
+
+        let presets = HabitPreset::load_dir(dir.path());
+        assert_eq!(presets.len(), 1);
+        assert_eq!(presets[0].name, "Stretching");
+    }
+}
@@ -0,0 +1,236 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use gtk::{glib, prelude::*, subclass::prelude::*};
+
+use crate::core::{date::Date, date::GDateTimeExt, progress};
+use crate::models::{Habit, HabitEntry};
+
+/// How a [HabitSorter] orders habits.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, strum::EnumString, strum::AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum HabitSort {
+    /// By the order the user moved them into, like the order they were added
+    Manual,
+    Name,
+    /// Oldest first
+    Created,
+    /// Longest current streak first
+    Streak,
+}
+
+impl Default for HabitSort {
+    fn default() -> Self {
+        Self::Manual
+    }
+}
+
+impl HabitSort {
+    pub fn as_str(&self) -> &str {
+        self.as_ref()
+    }
+
+    /// Compares two habits, placing habits missing from `manual_order` after
+    /// the others and breaking ties by id so the order is stable.
+    pub fn compare(
+        &self,
+        a: &Habit,
+        b: &Habit,
+        manual_order: &[u32],
+        streaks: &HashMap<u32, u32>,
+    ) -> Ordering {
+        let ordering = match self {
+            Self::Manual => {
+                let position = |habit: &Habit| {
+                    manual_order
+                        .iter()
+                        .position(|id| *id == habit.id())
+                        .unwrap_or(usize::MAX)
+                };
+                position(a).cmp(&position(b))
+            }
+            Self::Name => a
+                .display_name()
+                .to_lowercase()
+                .cmp(&b.display_name().to_lowercase()),
+            Self::Created => a.created_at().cmp(&b.created_at()),
+            Self::Streak => {
+                let streak = |habit: &Habit| streaks.get(&habit.id()).copied().unwrap_or(0);
+                streak(b).cmp(&streak(a))
+            }
+        };
+        ordering.then_with(|| a.id().cmp(&b.id()))
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct HabitSorter {
+        pub sort: Cell<HabitSort>,
+        pub manual_order: RefCell<Vec<u32>>,
+        /// Current streaks by habit id, loaded when first needed
+        pub streaks: RefCell<HashMap<u32, u32>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for HabitSorter {
+        const NAME: &'static str = "HabitSorter";
+        type Type = super::HabitSorter;
+        type ParentType = gtk::Sorter;
+    }
+
+    impl ObjectImpl for HabitSorter {}
+
+    impl SorterImpl for HabitSorter {
+        fn order(&self) -> gtk::SorterOrder {
+            gtk::SorterOrder::Total
+        }
+
+        fn compare(&self, item1: &glib::Object, item2: &glib::Object) -> gtk::Ordering {
+            let (Some(a), Some(b)) = (item1.downcast_ref::<Habit>(), item2.downcast_ref::<Habit>())
+            else {
+                return gtk::Ordering::Equal;
+            };
+            let sort = self.sort.get();
+            if sort == HabitSort::Streak {
+                self.load_streak(a);
+                self.load_streak(b);
+            }
+            sort.compare(a, b, &self.manual_order.borrow(), &self.streaks.borrow())
+                .into()
+        }
+    }
+
+    impl HabitSorter {
+        fn load_streak(&self, habit: &Habit) {
+            if self.streaks.borrow().contains_key(&habit.id()) {
+                return;
+            }
+            let streak = match HabitEntry::find_by_habit(habit.id()) {
+                Ok(entries) => {
+                    progress::status(
+                        &habit.frequency(),
+                        &progress::daily_totals(&entries),
+                        habit.created_at().to_local().unwrap().date(),
+                        Date::today(),
+                    )
+                    .streak
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to load habit entries: {}", e);
+                    0
+                }
+            };
+            self.streaks.borrow_mut().insert(habit.id(), streak);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Sorts a list of [Habit]s, e.g. for a [gtk::SortListModel].
+    pub struct HabitSorter(ObjectSubclass<imp::HabitSorter>)
+        @extends gtk::Sorter;
+}
+
+impl HabitSorter {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn sort(&self) -> HabitSort {
+        self.imp().sort.get()
+    }
+
+    pub fn set_sort(&self, sort: HabitSort) {
+        if self.imp().sort.replace(sort) != sort {
+            self.changed(gtk::SorterChange::Different);
+        }
+    }
+
+    pub fn manual_order(&self) -> Vec<u32> {
+        self.imp().manual_order.borrow().clone()
+    }
+
+    /// Sets the ids of the habits in the order the user chose.
+    pub fn set_manual_order(&self, manual_order: Vec<u32>) {
+        self.imp().manual_order.replace(manual_order);
+        if self.sort() == HabitSort::Manual {
+            self.changed(gtk::SorterChange::Different);
+        }
+    }
+
+    /// Sets the current streaks by habit id, e.g. after loading all entries
+    /// at once. Missing streaks are loaded when needed.
+    pub fn set_streaks(&self, streaks: HashMap<u32, u32>) {
+        self.imp().streaks.replace(streaks);
+        if self.sort() == HabitSort::Streak {
+            self.changed(gtk::SorterChange::Different);
+        }
+    }
+}
+
+impl Default for HabitSorter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Frequency, HabitName};
+    use std::str::FromStr;
+
+    fn habit(id: u32, title: &str, created_day: i32) -> Habit {
+        Habit::new(
+            id,
+            1,
+            HabitName::Custom,
+            Some(title.to_string()),
+            None,
+            None,
+            None,
+            Frequency::default(),
+            glib::DateTime::from_local(2024, 5, created_day, 8, 0, 0.0).unwrap(),
+            None,
+            Vec::new(),
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn sorted(sort: HabitSort, habits: &[Habit], manual_order: &[u32]) -> Vec<u32> {
+        let streaks = HashMap::from([(1, 2), (2, 10), (3, 0)]);
+        let mut habits = habits.to_vec();
+        habits.sort_by(|a, b| sort.compare(a, b, manual_order, &streaks));
+        habits.iter().map(|habit| habit.id()).collect()
+    }
+
+    #[test]
+    fn test_habit_sort() {
+        let habits = [
+            habit(1, "walking", 3),
+            habit(2, "Reading", 1),
+            habit(3, "Flossing", 2),
+        ];
+        assert_eq!(sorted(HabitSort::Name, &habits, &[]), vec![3, 2, 1]);
+        assert_eq!(sorted(HabitSort::Created, &habits, &[]), vec![2, 3, 1]);
+        assert_eq!(sorted(HabitSort::Streak, &habits, &[]), vec![2, 1, 3]);
+        assert_eq!(sorted(HabitSort::Manual, &habits, &[3, 1]), vec![3, 1, 2]);
+        assert_eq!(sorted(HabitSort::Manual, &habits, &[]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_habit_sort_str() {
+        assert_eq!(HabitSort::from_str("streak"), Ok(HabitSort::Streak));
+        assert_eq!(HabitSort::Created.as_str(), "created");
+        assert!(HabitSort::from_str("invalid").is_err());
+    }
+}
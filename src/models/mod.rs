@@ -5,14 +5,18 @@ mod habit;
 mod habit_category;
 mod habit_category_map;
 mod habit_entry;
+mod habit_filter;
 mod habit_model;
 mod habit_name;
+mod habit_preset;
+mod habit_sorter;
+mod reminder_rule;
 mod unitsystem;
 mod user;
 mod weekday;
 
-pub use self::habit::Habit;
-pub use self::habit_entry::HabitEntry;
+pub use self::habit::{Habit, UpdateHabit};
+pub use self::habit_entry::{HabitEntry, UpdateHabitEntry};
 pub use self::user::User;
 
 pub use compare::*;
@@ -20,7 +24,11 @@ pub use duration_kind::*;
 pub use frequency::*;
 pub use habit_category::*;
 pub use habit_category_map::*;
+pub use habit_filter::*;
 pub use habit_model::*;
 pub use habit_name::*;
+pub use habit_preset::*;
+pub use habit_sorter::*;
+pub use reminder_rule::*;
 pub use unitsystem::*;
 pub use weekday::*;
@@ -0,0 +1,203 @@
+use gtk::glib;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{
+        date::{Date, Time},
+        i18n_f, ni18n_f,
+    },
+    models::Weekday,
+};
+
+/// A [ReminderRule] is a recurring reminder at a time of day, e.g. "weekdays
+/// at 07:30" or "Saturdays at 10:00".
+///
+/// Rules repeat on every day their habit is due unless limited to some
+/// `weekdays`. The `offset_minutes` shift the reminder from `time`, e.g. to
+/// remind 15 minutes ahead of a scheduled habit.
+///
+/// A rule can also be written as just its time, `"07:30"`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "ReminderRuleRepr")]
+pub struct ReminderRule {
+    pub time: Time,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekdays: Option<Vec<Weekday>>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub offset_minutes: i32,
+}
+
+/// How far a reminder can be moved from its time, a day either way. Reminders
+/// are only looked for on the days around their habit's due days.
+pub const MAX_OFFSET_MINUTES: i32 = 24 * 60;
+
+fn is_zero(minutes: &i32) -> bool {
+    *minutes == 0
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ReminderRuleRepr {
+    Time(Time),
+    Rule {
+        time: Time,
+        #[serde(default)]
+        weekdays: Option<Vec<Weekday>>,
+        #[serde(default)]
+        offset_minutes: i32,
+    },
+}
+
+impl From<ReminderRuleRepr> for ReminderRule {
+    fn from(repr: ReminderRuleRepr) -> Self {
+        match repr {
+            ReminderRuleRepr::Time(time) => Self::new(time),
+            ReminderRuleRepr::Rule {
+                time,
+                weekdays,
+                offset_minutes,
+            } => Self {
+                time,
+                weekdays,
+                offset_minutes: offset_minutes.clamp(-MAX_OFFSET_MINUTES, MAX_OFFSET_MINUTES),
+            },
+        }
+    }
+}
+
+impl ReminderRule {
+    /// A reminder at `time` on every day the habit is due.
+    pub fn new(time: Time) -> Self {
+        Self {
+            time,
+            weekdays: None,
+            offset_minutes: 0,
+        }
+    }
+
+    /// Limits the rule to `weekdays`.
+    #[must_use]
+    pub fn on(mut self, weekdays: Vec<Weekday>) -> Self {
+        self.weekdays = Some(weekdays);
+        self
+    }
+
+    #[must_use]
+    pub fn with_offset(mut self, minutes: i32) -> Self {
+        self.offset_minutes = minutes;
+        self
+    }
+
+    /// Whether the rule reminds on `weekday`, before the habit's frequency is
+    /// taken into account.
+    pub fn applies_on(&self, weekday: Weekday) -> bool {
+        self.weekdays
+            .as_ref()
+            .map_or(true, |weekdays| weekdays.contains(&weekday))
+    }
+
+    /// When the rule reminds of the habit due on `date`. The offset can move
+    /// this to the day before or after. [None] if that's out of range.
+    pub fn fire_time(&self, date: Date) -> Option<glib::DateTime> {
+        date.and_time_local(self.time)
+            .add_minutes(self.offset_minutes)
+            .ok()
+    }
+
+    /// A short description, e.g. "Mon, Tue at 07:30, 15 minutes early".
+    pub fn describe(&self) -> String {
+        let time = format!("{:02}:{:02}", self.time.hour(), self.time.minutes());
+        let when = match &self.weekdays {
+            Some(weekdays) if weekdays.len() < 7 => {
+                let weekdays = weekdays
+                    .iter()
+                    .map(|weekday| weekday.as_short_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                i18n_f("{} at {}", &[&weekdays, &time])
+            }
+            _ => time,
+        };
+        if self.offset_minutes == 0 {
+            return when;
+        }
+        let minutes = self.offset_minutes.unsigned_abs();
+        let args = [when.as_str(), &minutes.to_string()];
+        if self.offset_minutes < 0 {
+            ni18n_f(
+                "{}, {} minute early",
+                "{}, {} minutes early",
+                minutes,
+                &args,
+            )
+        } else {
+            ni18n_f("{}, {} minute late", "{}, {} minutes late", minutes, &args)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, glib::Boxed, PartialEq, Eq, Deserialize, Serialize)]
+#[boxed_type(name = "ReminderRules")]
+pub struct ReminderRules(pub Vec<ReminderRule>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u8, minutes: u8) -> Time {
+        Time::new(hour, minutes, 0).unwrap()
+    }
+
+    #[test]
+    fn test_fire_time() {
+        // 2024-05-06 is a Monday
+        let monday = Date::new(2024, 5, 6).unwrap();
+        let rule = ReminderRule::new(time(7, 30)).on(vec![Weekday::Monday, Weekday::Friday]);
+        assert!(rule.applies_on(Weekday::Friday));
+        assert!(!rule.applies_on(Weekday::Saturday));
+        assert_eq!(
+            rule.fire_time(monday),
+            Some(monday.and_time_local(time(7, 30)))
+        );
+
+        let early = ReminderRule::new(time(0, 10)).with_offset(-15);
+        assert_eq!(
+            early.fire_time(monday),
+            Some(monday.add_days(-1).and_time_local(time(23, 55)))
+        );
+
+        let absurd = ReminderRule::new(time(7, 30)).with_offset(-2_000_000_000);
+        assert_eq!(absurd.fire_time(monday), None);
+    }
+
+    #[test]
+    fn test_serde() {
+        let rules: Vec<ReminderRule> = serde_json::from_str(
+            r#"["07:30", {"time": "10:00:00", "weekdays": ["saturday"], "offset_minutes": -5}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            rules,
+            vec![
+                ReminderRule::new(time(7, 30)),
+                ReminderRule::new(time(10, 0))
+                    .on(vec![Weekday::Saturday])
+                    .with_offset(-5),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&rules[0]).unwrap(),
+            r#"{"time":"07:30:00"}"#
+        );
+        assert_eq!(rules[0].describe(), "07:30");
+        assert_eq!(rules[1].describe(), "Sat at 10:00, 5 minutes early");
+        assert_eq!(
+            ReminderRule::new(time(7, 30)).with_offset(1).describe(),
+            "07:30, 1 minute late"
+        );
+
+        let rule: ReminderRule =
+            serde_json::from_str(r#"{"time": "10:00", "offset_minutes": -2000000000}"#).unwrap();
+        assert_eq!(rule.offset_minutes, -MAX_OFFSET_MINUTES);
+    }
+}
@@ -73,6 +73,48 @@ impl UnitSystem {
     pub fn as_str(&self) -> &str {
         self.as_ref()
     }
+
+    /// Formats an amount in this unit for display, e.g. `120 minutes`.
+    pub fn format_value(&self, value: i64) -> String {
+        match self {
+            UnitSystem::Count => format!("{value}×"),
+            UnitSystem::Unit => value.to_string(),
+            _ => format!("{value} {}", self.as_str()),
+        }
+    }
+
+    pub fn iter() -> impl Iterator<Item = UnitSystem> {
+        vec![
+            UnitSystem::Count,
+            UnitSystem::Minutes,
+            UnitSystem::Hours,
+            UnitSystem::Days,
+            UnitSystem::Pages,
+            UnitSystem::Words,
+            UnitSystem::Currency,
+            UnitSystem::Milliliters,
+            UnitSystem::Liters,
+            UnitSystem::Gallons,
+            UnitSystem::Meters,
+            UnitSystem::Kilometers,
+            UnitSystem::Yards,
+            UnitSystem::Miles,
+            UnitSystem::Grams,
+            UnitSystem::Kilograms,
+            UnitSystem::Pounds,
+            UnitSystem::Ounces,
+            UnitSystem::Calories,
+            UnitSystem::Steps,
+            UnitSystem::Unit,
+        ]
+        .into_iter()
+    }
+
+    /// Whether values measure an amount, like minutes or kilometers, rather
+    /// than just counting how often something was done.
+    pub fn is_quantity(&self) -> bool {
+        !matches!(self, UnitSystem::Count | UnitSystem::Unit)
+    }
 }
 
 impl serde::Serialize for UnitSystem {
@@ -119,4 +161,23 @@ mod tests {
         assert_eq!(UnitSystem::Count.as_str(), "count");
         assert_eq!(UnitSystem::Minutes.as_str(), "minutes");
     }
+
+    #[test]
+    fn test_unit_systems_count() {
+        assert_eq!(21, UnitSystem::iter().count());
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(UnitSystem::Minutes.format_value(120), "120 minutes");
+        assert_eq!(UnitSystem::Count.format_value(3), "3×");
+        assert_eq!(UnitSystem::Unit.format_value(3), "3");
+    }
+
+    #[test]
+    fn test_is_quantity() {
+        assert!(UnitSystem::Kilometers.is_quantity());
+        assert!(!UnitSystem::Count.is_quantity());
+        assert!(!UnitSystem::Unit.is_quantity());
+    }
 }
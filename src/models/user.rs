@@ -2,7 +2,10 @@ use anyhow::Result;
 use diesel::prelude::*;
 use gtk::{glib, prelude::*, subclass::prelude::*};
 
-use crate::{core::database, schema::user};
+use crate::{
+    core::{database, sync},
+    schema::user,
+};
 
 #[derive(Insertable)]
 #[diesel(table_name = user)]
@@ -10,6 +13,7 @@ struct NewUser {
     name: String,
     date_of_birth: String,
     created_at: String,
+    uuid: Option<String>,
 }
 
 #[derive(Queryable, Identifiable)]
@@ -19,6 +23,10 @@ struct DieselUser {
     name: String,
     date_of_birth: String,
     created_at: String,
+    #[allow(dead_code)]
+    uuid: Option<String>,
+    #[allow(dead_code)]
+    updated_at: Option<String>,
 }
 
 #[doc(hidden)]
@@ -107,7 +115,10 @@ mod imp {
 
             let target = user::table.filter(user::columns::id.eq(id));
             diesel::update(target)
-                .set(user::columns::name.eq(name))
+                .set((
+                    user::columns::name.eq(name),
+                    user::columns::updated_at.eq(sync::now()),
+                ))
                 .execute(&mut conn)?;
             Ok(())
         }
@@ -145,6 +156,7 @@ impl User {
                 name: name.to_owned(),
                 date_of_birth: date_of_birth.to_owned(),
                 created_at: curr_time,
+                uuid: Some(sync::new_uuid()),
             })
             .execute(&mut conn)?;
 
@@ -269,6 +281,7 @@ impl User {
             .set((
                 user::columns::name.eq(user_name),
                 user::columns::date_of_birth.eq(user_date_of_birth),
+                user::columns::updated_at.eq(sync::now()),
             ))
             .execute(&mut conn)?;
 
@@ -278,8 +291,12 @@ impl User {
     pub fn delete(&self) -> Result<()> {
         let db = database::connection();
         let mut conn = db.get()?;
-        diesel::delete(user::table.filter(user::columns::id.eq(self.id() as i32)))
-            .execute(&mut conn)?;
+        conn.transaction(|conn| {
+            let target = user::table.filter(user::columns::id.eq(self.id() as i32));
+            let uuid = target.select(user::columns::uuid).first(conn)?;
+            sync::record_deletion(conn, "user", uuid)?;
+            diesel::delete(target).execute(conn)
+        })?;
         Ok(())
     }
 }
@@ -11,11 +11,13 @@ diesel::table! {
         frequency -> Text,
         created_at -> Text,
         updated_at -> Nullable<Text>,
-        reminder_times -> Nullable<Text>,
+        reminder_rules -> Nullable<Text>,
         note -> Nullable<Text>,
         archived -> Integer,
         archived_date -> Nullable<Text>,
         archived_reason -> Nullable<Text>,
+        title -> Nullable<Text>,
+        uuid -> Nullable<Text>,
     }
 }
 
@@ -27,6 +29,16 @@ diesel::table! {
         entry_time -> Text,
         note -> Nullable<Text>,
         value -> Integer,
+        uuid -> Nullable<Text>,
+        updated_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    habit_strength (habit_id) {
+        habit_id -> Integer,
+        day -> Text,
+        score -> Double,
     }
 }
 
@@ -36,11 +48,44 @@ diesel::table! {
         name -> Text,
         date_of_birth -> Text,
         created_at -> Text,
+        uuid -> Nullable<Text>,
+        updated_at -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    sync_cursor (device_id) {
+        device_id -> Text,
+        position -> Integer,
+    }
+}
+
+diesel::table! {
+    sync_deletion (uuid) {
+        uuid -> Text,
+        table_name -> Text,
+        deleted_at -> Text,
+    }
+}
+
+diesel::table! {
+    sync_state (key) {
+        key -> Text,
+        value -> Text,
     }
 }
 
 diesel::joinable!(habit -> user (user_id));
 diesel::joinable!(habit_entry -> habit (habit_id));
+diesel::joinable!(habit_strength -> habit (habit_id));
 diesel::joinable!(habit_entry -> user (user_id));
 
-diesel::allow_tables_to_appear_in_same_query!(habit, habit_entry, user,);
+diesel::allow_tables_to_appear_in_same_query!(
+    habit,
+    habit_entry,
+    habit_strength,
+    sync_cursor,
+    sync_deletion,
+    sync_state,
+    user,
+);
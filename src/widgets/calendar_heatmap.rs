@@ -0,0 +1,332 @@
+use std::collections::BTreeMap;
+
+use gtk::{gdk, glib, graphene, gsk, prelude::*, subclass::prelude::*};
+
+use crate::core::{
+    date::{Date, GDateTimeExt, Time},
+    i18n_f, progress,
+};
+use crate::models::{Frequency, Habit, HabitEntry};
+
+/// Number of shades a day can take besides the empty one.
+pub const LEVELS: u8 = 4;
+
+const CELL_SIZE: f32 = 12.0;
+const CELL_SPACING: f32 = 3.0;
+const CELL_STEP: f32 = CELL_SIZE + CELL_SPACING;
+
+/// How strongly to shade a day with `total` logged, from 0 for nothing to
+/// [LEVELS] for the whole target. Abstention habits have no amount to reach,
+/// so their days are either fully shaded or not at all.
+pub fn level(frequency: &Frequency, total: i64) -> u8 {
+    if frequency.is_abstraction() {
+        return if frequency.is_satisfied(total) {
+            LEVELS
+        } else {
+            0
+        };
+    }
+    if total <= 0 {
+        return 0;
+    }
+    let share = total as f64 / f64::from(frequency.target_value);
+    (share * f64::from(LEVELS)).ceil().min(f64::from(LEVELS)) as u8
+}
+
+/// The grid of a year of days: one column per week starting on Monday, one row
+/// per weekday, ending with the week of `today`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HeatmapLayout {
+    pub start: Date,
+    pub today: Date,
+}
+
+impl HeatmapLayout {
+    pub const WEEKS: i64 = 53;
+
+    pub fn new(today: Date) -> Self {
+        Self {
+            start: today
+                .start_of_week()
+                .add_days(-7 * (Self::WEEKS as i32 - 1)),
+            today,
+        }
+    }
+
+    /// The column and row of `date`, [None] if it isn't on the grid.
+    pub fn position(&self, date: Date) -> Option<(i64, i64)> {
+        if date < self.start || date > self.today {
+            return None;
+        }
+        let days = self.start.days_until(&date);
+        Some((days / 7, days % 7))
+    }
+
+    /// The day at `column` and `row`, [None] if there is none yet.
+    pub fn date_at(&self, column: i64, row: i64) -> Option<Date> {
+        if !(0..Self::WEEKS).contains(&column) || !(0..7).contains(&row) {
+            return None;
+        }
+        let date = self.start.add_days((column * 7 + row) as i32);
+        (date <= self.today).then_some(date)
+    }
+
+    pub fn dates(&self) -> Vec<Date> {
+        Date::range(self.start, self.today)
+    }
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::subclass::Signal;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug)]
+    pub struct CalendarHeatmap {
+        pub habit: RefCell<Option<Habit>>,
+        pub totals: RefCell<BTreeMap<Date, i64>>,
+        pub layout: Cell<HeatmapLayout>,
+    }
+
+    impl Default for CalendarHeatmap {
+        fn default() -> Self {
+            Self {
+                habit: RefCell::default(),
+                totals: RefCell::default(),
+                layout: Cell::new(HeatmapLayout::new(Date::today())),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CalendarHeatmap {
+        const NAME: &'static str = "CalendarHeatmap";
+        type Type = super::CalendarHeatmap;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("calendar-heatmap");
+        }
+    }
+
+    impl ObjectImpl for CalendarHeatmap {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("day-activated")
+                    .param_types([glib::DateTime::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.set_has_tooltip(true);
+
+            let click = gtk::GestureClick::new();
+            click.connect_released(|gesture, _, x, y| {
+                let heatmap = gesture
+                    .widget()
+                    .downcast::<super::CalendarHeatmap>()
+                    .unwrap();
+                if let Some(date) = heatmap.date_at(x, y) {
+                    let day = date.and_time_local(Time::new(0, 0, 0).unwrap());
+                    heatmap.emit_by_name::<()>("day-activated", &[&day]);
+                }
+            });
+            obj.add_controller(click);
+
+            obj.connect_query_tooltip(|heatmap, x, y, _, tooltip| {
+                let Some(habit) = heatmap.habit() else {
+                    return false;
+                };
+                let Some(date) = heatmap.date_at(f64::from(x), f64::from(y)) else {
+                    return false;
+                };
+                let total = heatmap.imp().totals.borrow().get(&date).copied();
+                let day = date
+                    .and_time_local(Time::new(0, 0, 0).unwrap())
+                    .format_local();
+                let value = habit.frequency().unit.format_value(total.unwrap_or(0));
+                tooltip.set_text(Some(&i18n_f("{} on {}", &[&value, &day])));
+                true
+            });
+        }
+    }
+
+    impl WidgetImpl for CalendarHeatmap {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            let cells = match orientation {
+                gtk::Orientation::Horizontal => HeatmapLayout::WEEKS as f32,
+                _ => 7.0,
+            };
+            let size = (cells * CELL_STEP - CELL_SPACING).ceil() as i32;
+            (size, size, -1, -1)
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let Some(habit) = self.habit.borrow().clone() else {
+                return;
+            };
+            let frequency = habit.frequency();
+            let created = habit.created_at().to_local().unwrap().date();
+            let layout = self.layout.get();
+            let totals = self.totals.borrow();
+
+            let style = self.obj().style_context();
+            let foreground = style.color();
+            let accent = style
+                .lookup_color("accent_bg_color")
+                .unwrap_or(gdk::RGBA::new(0.21, 0.52, 0.89, 1.0));
+            let empty = foreground.with_alpha(0.1);
+            let untracked = foreground.with_alpha(0.04);
+
+            for date in layout.dates() {
+                let (column, row) = layout.position(date).unwrap();
+                let level = level(&frequency, totals.get(&date).copied().unwrap_or(0));
+                let color = if date < created {
+                    untracked
+                } else if level == 0 {
+                    empty
+                } else {
+                    accent.with_alpha(f32::from(level) / f32::from(LEVELS))
+                };
+
+                let rect = graphene::Rect::new(
+                    column as f32 * CELL_STEP,
+                    row as f32 * CELL_STEP,
+                    CELL_SIZE,
+                    CELL_SIZE,
+                );
+                let rounded = gsk::RoundedRect::from_rect(rect, 2.0);
+                snapshot.push_rounded_clip(&rounded);
+                snapshot.append_color(&color, &rect);
+                snapshot.pop();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A year of a habit's history, one square per day shaded by how much of
+    /// the target was reached. Clicking a day emits `day-activated`.
+    pub struct CalendarHeatmap(ObjectSubclass<imp::CalendarHeatmap>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl CalendarHeatmap {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn habit(&self) -> Option<Habit> {
+        self.imp().habit.borrow().clone()
+    }
+
+    /// Shows the history of `habit`, or nothing if [None].
+    pub fn set_habit(&self, habit: Option<&Habit>) {
+        self.imp().habit.replace(habit.cloned());
+        self.set_visible(habit.is_some());
+        self.reload();
+    }
+
+    /// Loads the entries of the habit again, e.g. after they changed.
+    pub fn reload(&self) {
+        let entries = match self
+            .habit()
+            .map(|habit| HabitEntry::find_by_habit(habit.id()))
+        {
+            Some(Ok(entries)) => entries,
+            Some(Err(e)) => {
+                tracing::warn!("Failed to load habit entries: {}", e);
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+        self.set_entries(&entries);
+    }
+
+    /// Shows `entries` rather than loading them from the database.
+    pub fn set_entries(&self, entries: &[HabitEntry]) {
+        let imp = self.imp();
+        imp.totals.replace(progress::daily_totals(entries));
+        imp.layout.set(HeatmapLayout::new(Date::today()));
+        self.queue_draw();
+    }
+
+    /// The day drawn at `x` and `y`, [None] if it's between or outside the days.
+    pub fn date_at(&self, x: f64, y: f64) -> Option<Date> {
+        let step = f64::from(CELL_STEP);
+        if x < 0.0 || y < 0.0 || x % step > f64::from(CELL_SIZE) || y % step > f64::from(CELL_SIZE)
+        {
+            return None;
+        }
+        self.imp()
+            .layout
+            .get()
+            .date_at((x / step) as i64, (y / step) as i64)
+    }
+
+    pub fn connect_day_activated<F: Fn(&Self, Date) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("day-activated", false, move |values| {
+            let heatmap = values[0].get::<Self>().unwrap();
+            let day = values[1].get::<glib::DateTime>().unwrap();
+            f(&heatmap, day.date());
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Compare, DurationKind, UnitSystem, Weekday};
+
+    // 2024-05-06 is a Monday
+    fn date(month: u8, day: u8) -> Date {
+        Date::new(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_layout() {
+        // A Wednesday
+        let layout = HeatmapLayout::new(date(5, 8));
+        assert_eq!(layout.start, date(5, 6).add_days(-7 * 52));
+        assert_eq!(layout.start.weekday(), Weekday::Monday);
+        assert_eq!(layout.position(date(5, 8)), Some((52, 2)));
+        assert_eq!(layout.position(date(5, 9)), None);
+        assert_eq!(layout.date_at(52, 2), Some(date(5, 8)));
+        assert_eq!(layout.date_at(52, 3), None);
+        assert_eq!(layout.date_at(0, 0), Some(layout.start));
+        assert_eq!(layout.date_at(0, 7), None);
+        assert_eq!(layout.dates().len(), 52 * 7 + 3);
+    }
+
+    #[test]
+    fn test_level() {
+        let frequency = Frequency::new(
+            Some(DurationKind::Daily),
+            Some(UnitSystem::Minutes),
+            Some(30),
+            None,
+            None,
+        );
+        assert_eq!(level(&frequency, 0), 0);
+        assert_eq!(level(&frequency, 5), 1);
+        assert_eq!(level(&frequency, 15), 2);
+        assert_eq!(level(&frequency, 30), LEVELS);
+        assert_eq!(level(&frequency, 90), LEVELS);
+
+        let abstention = Frequency::new(None, None, Some(0), None, Some(Compare::LessOrEqual));
+        assert_eq!(level(&abstention, 0), LEVELS);
+        assert_eq!(level(&abstention, 1), 0);
+    }
+}
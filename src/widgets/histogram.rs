@@ -0,0 +1,136 @@
+use gtk::{gdk, glib, graphene, gsk, prelude::*, subclass::prelude::*};
+
+/// Height of the row of labels under the bars.
+const LABEL_HEIGHT: f32 = 18.0;
+
+/// One bar of a [Histogram].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HistogramBar {
+    /// Shown under the bar, may be empty to label only some of them
+    pub label: String,
+    /// Shown when hovering the bar
+    pub tooltip: String,
+    pub count: usize,
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct Histogram {
+        pub bars: RefCell<Vec<HistogramBar>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Histogram {
+        const NAME: &'static str = "Histogram";
+        type Type = super::Histogram;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("histogram");
+        }
+    }
+
+    impl ObjectImpl for Histogram {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.set_has_tooltip(true);
+
+            obj.connect_query_tooltip(|histogram, x, _, _, tooltip| {
+                let bars = histogram.imp().bars.borrow();
+                if bars.is_empty() || histogram.width() <= 0 {
+                    return false;
+                }
+                let slot = f64::from(histogram.width()) / bars.len() as f64;
+                match bars.get((f64::from(x) / slot) as usize) {
+                    Some(bar) if !bar.tooltip.is_empty() => {
+                        tooltip.set_text(Some(&bar.tooltip));
+                        true
+                    }
+                    _ => false,
+                }
+            });
+        }
+    }
+
+    impl WidgetImpl for Histogram {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            match orientation {
+                gtk::Orientation::Horizontal => (200, 360, -1, -1),
+                _ => (100, 120, -1, -1),
+            }
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let obj = self.obj();
+            let bars = self.bars.borrow();
+            let (width, height) = (obj.width() as f32, obj.height() as f32);
+            if bars.is_empty() || width <= 0.0 || height <= LABEL_HEIGHT {
+                return;
+            }
+
+            let style = obj.style_context();
+            let foreground = style.color();
+            let accent = style
+                .lookup_color("accent_bg_color")
+                .unwrap_or(gdk::RGBA::new(0.21, 0.52, 0.89, 1.0));
+
+            let max = bars.iter().map(|bar| bar.count).max().unwrap_or(0).max(1) as f32;
+            let plot_height = height - LABEL_HEIGHT;
+            let slot = width / bars.len() as f32;
+            for (i, bar) in bars.iter().enumerate() {
+                let x = i as f32 * slot;
+                // Empty bars still show as a line, so the axis is visible
+                let bar_height = (plot_height * bar.count as f32 / max).max(2.0);
+                let rect = graphene::Rect::new(
+                    x + slot * 0.15,
+                    plot_height - bar_height,
+                    slot * 0.7,
+                    bar_height,
+                );
+                let color = if bar.count == 0 {
+                    foreground.with_alpha(0.1)
+                } else {
+                    accent
+                };
+                snapshot.push_rounded_clip(&gsk::RoundedRect::from_rect(rect, 2.0));
+                snapshot.append_color(&color, &rect);
+                snapshot.pop();
+
+                if !bar.label.is_empty() {
+                    let layout = obj.create_pango_layout(Some(&bar.label));
+                    let (label_width, _) = layout.pixel_size();
+                    snapshot.save();
+                    snapshot.translate(&graphene::Point::new(
+                        x + (slot - label_width as f32) / 2.0,
+                        plot_height + 2.0,
+                    ));
+                    snapshot.append_layout(&layout, &foreground.with_alpha(0.55));
+                    snapshot.restore();
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A bar chart of counts, like the number of entries per hour of the day.
+    pub struct Histogram(ObjectSubclass<imp::Histogram>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn set_bars(&self, bars: Vec<HistogramBar>) {
+        self.imp().bars.replace(bars);
+        self.queue_draw();
+    }
+}
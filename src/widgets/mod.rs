@@ -0,0 +1,13 @@
+#[doc(hidden)]
+pub mod calendar_heatmap;
+#[doc(hidden)]
+pub mod histogram;
+#[doc(hidden)]
+pub mod trend_chart;
+
+#[doc(inline)]
+pub use calendar_heatmap::*;
+#[doc(inline)]
+pub use histogram::*;
+#[doc(inline)]
+pub use trend_chart::*;
@@ -0,0 +1,433 @@
+use std::collections::BTreeMap;
+
+use gtk::{cairo, gdk, glib, graphene, prelude::*, subclass::prelude::*};
+
+use crate::core::{
+    date::{Date, GDateTimeExt, Time},
+    i18n, i18n_f, progress,
+};
+use crate::models::{DurationKind, Frequency, Habit, HabitEntry};
+
+/// How far back a [TrendChart] goes and how it adds up the values.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TrendRange {
+    /// Daily totals of the last 30 days
+    #[default]
+    Days,
+    /// Weekly totals of the last 12 weeks
+    Weeks,
+    /// Monthly totals of the last 12 months
+    Months,
+}
+
+impl TrendRange {
+    pub const ALL: [TrendRange; 3] = [TrendRange::Days, TrendRange::Weeks, TrendRange::Months];
+
+    pub fn label(&self) -> String {
+        match self {
+            TrendRange::Days => i18n("Days"),
+            TrendRange::Weeks => i18n("Weeks"),
+            TrendRange::Months => i18n("Months"),
+        }
+    }
+
+    /// The first and last day of each data point, oldest first, the last one
+    /// containing `today`.
+    pub fn buckets(&self, today: Date) -> Vec<(Date, Date)> {
+        let mut buckets = Vec::new();
+        match self {
+            TrendRange::Days => {
+                for days in (0..30).rev() {
+                    let date = today.add_days(-days);
+                    buckets.push((date, date));
+                }
+            }
+            TrendRange::Weeks => {
+                let monday = today.start_of_week();
+                for weeks in (0..12).rev() {
+                    let start = monday.add_days(-7 * weeks);
+                    buckets.push((start, start.add_days(6)));
+                }
+            }
+            TrendRange::Months => {
+                let mut month = today.first_of_month();
+                for _ in 0..12 {
+                    buckets.push((month, month.last_of_month()));
+                    month = month.add_days(-1).first_of_month();
+                }
+                buckets.reverse();
+            }
+        }
+        buckets
+    }
+
+    /// Describes the data point starting on `start`, e.g. for its tooltip.
+    pub fn bucket_label(&self, start: Date) -> String {
+        let datetime = start.and_time_local(Time::new(0, 0, 0).unwrap());
+        match self {
+            TrendRange::Days => datetime.format_local(),
+            TrendRange::Weeks => i18n_f("Week of {}", &[&datetime.format_local()]),
+            TrendRange::Months => datetime.format("%B %Y").unwrap().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ChartStyle {
+    #[default]
+    Line,
+    Bars,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DataPoint {
+    pub start: Date,
+    pub end: Date,
+    pub total: i64,
+    pub target: Option<i64>,
+}
+
+/// What `frequency` asks for from `start` to `end`: the daily target on each
+/// due day of a daily habit, the monthly target over a whole month. [None] if
+/// the days don't add up to a target, like a single day of a monthly habit.
+pub fn bucket_target(frequency: &Frequency, start: Date, end: Date) -> Option<i64> {
+    let target = i64::from(frequency.target_value);
+    match frequency.duration_kind {
+        DurationKind::Daily => {
+            let due_days = Date::range(start, end)
+                .into_iter()
+                .filter(|date| frequency.is_due_on(date.weekday()))
+                .count();
+            Some(target * due_days as i64)
+        }
+        DurationKind::Monthly => {
+            (start == start.first_of_month() && end == start.last_of_month()).then_some(target)
+        }
+    }
+}
+
+/// The totals of the daily `totals` over `range`, with their targets.
+pub fn data_points(
+    frequency: &Frequency,
+    totals: &BTreeMap<Date, i64>,
+    range: TrendRange,
+    today: Date,
+) -> Vec<DataPoint> {
+    range
+        .buckets(today)
+        .into_iter()
+        .map(|(start, end)| DataPoint {
+            start,
+            end,
+            total: totals.range(start..=end).map(|(_, value)| value).sum(),
+            target: bucket_target(frequency, start, end),
+        })
+        .collect()
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct TrendChart {
+        pub habit: RefCell<Option<Habit>>,
+        pub totals: RefCell<BTreeMap<Date, i64>>,
+        pub points: RefCell<Vec<DataPoint>>,
+        pub range: Cell<TrendRange>,
+        pub style: Cell<ChartStyle>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TrendChart {
+        const NAME: &'static str = "TrendChart";
+        type Type = super::TrendChart;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("trend-chart");
+        }
+    }
+
+    impl ObjectImpl for TrendChart {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            obj.set_has_tooltip(true);
+
+            obj.connect_query_tooltip(|chart, x, _, _, tooltip| {
+                let imp = chart.imp();
+                let Some(habit) = chart.habit() else {
+                    return false;
+                };
+                let points = imp.points.borrow();
+                if points.is_empty() || chart.width() <= 0 {
+                    return false;
+                }
+                let slot = f64::from(chart.width()) / points.len() as f64;
+                let Some(point) = points.get((f64::from(x) / slot) as usize) else {
+                    return false;
+                };
+
+                let unit = habit.frequency().unit;
+                let mut text = format!(
+                    "{}: {}",
+                    imp.range.get().bucket_label(point.start),
+                    unit.format_value(point.total)
+                );
+                if let Some(target) = point.target {
+                    text.push('\n');
+                    text.push_str(&i18n_f("Target: {}", &[&unit.format_value(target)]));
+                }
+                tooltip.set_text(Some(&text));
+                true
+            });
+        }
+    }
+
+    impl WidgetImpl for TrendChart {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            match orientation {
+                gtk::Orientation::Horizontal => (240, 480, -1, -1),
+                _ => (120, 160, -1, -1),
+            }
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let obj = self.obj();
+            let (width, height) = (obj.width() as f32, obj.height() as f32);
+            if self.points.borrow().is_empty() || width <= 0.0 || height <= 0.0 {
+                return;
+            }
+            let cr = snapshot.append_cairo(&graphene::Rect::new(0.0, 0.0, width, height));
+            if let Err(e) = self.draw(&cr, f64::from(width), f64::from(height)) {
+                tracing::warn!("Failed to draw trend chart: {}", e);
+            }
+        }
+    }
+
+    impl TrendChart {
+        fn draw(&self, cr: &cairo::Context, width: f64, height: f64) -> Result<(), cairo::Error> {
+            let points = self.points.borrow();
+            let style = self.obj().style_context();
+            let foreground = style.color();
+            let accent = style
+                .lookup_color("accent_bg_color")
+                .unwrap_or(gdk::RGBA::new(0.21, 0.52, 0.89, 1.0));
+
+            let max = points
+                .iter()
+                .map(|point| point.total.max(point.target.unwrap_or(0)))
+                .max()
+                .unwrap_or(0)
+                .max(1) as f64;
+            // Keep the dots and line widths inside
+            let top = 4.0;
+            let plot_height = height - 2.0 * top;
+            let y = |value: i64| top + plot_height * (1.0 - value as f64 / max);
+            let slot = width / points.len() as f64;
+
+            set_source(cr, &accent);
+            match self.style.get() {
+                ChartStyle::Bars => {
+                    for (i, point) in points.iter().enumerate() {
+                        let top = y(point.total);
+                        cr.rectangle(
+                            (i as f64 + 0.15) * slot,
+                            top,
+                            slot * 0.7,
+                            height - top - 4.0,
+                        );
+                    }
+                    cr.fill()?;
+                }
+                ChartStyle::Line => {
+                    for (i, point) in points.iter().enumerate() {
+                        cr.line_to((i as f64 + 0.5) * slot, y(point.total));
+                    }
+                    cr.set_line_width(2.0);
+                    cr.stroke()?;
+                    for (i, point) in points.iter().enumerate() {
+                        cr.new_sub_path();
+                        cr.arc(
+                            (i as f64 + 0.5) * slot,
+                            y(point.total),
+                            3.0,
+                            0.0,
+                            std::f64::consts::TAU,
+                        );
+                    }
+                    cr.fill()?;
+                }
+            }
+
+            // The target of each data point as a dashed step
+            set_source(cr, &foreground.with_alpha(0.5));
+            cr.set_line_width(1.0);
+            cr.set_dash(&[4.0, 4.0], 0.0);
+            for (i, point) in points.iter().enumerate() {
+                if let Some(target) = point.target {
+                    cr.move_to(i as f64 * slot, y(target));
+                    cr.line_to((i + 1) as f64 * slot, y(target));
+                }
+            }
+            cr.stroke()
+        }
+    }
+
+    fn set_source(cr: &cairo::Context, color: &gdk::RGBA) {
+        cr.set_source_rgba(
+            f64::from(color.red()),
+            f64::from(color.green()),
+            f64::from(color.blue()),
+            f64::from(color.alpha()),
+        );
+    }
+}
+
+glib::wrapper! {
+    /// A chart of the daily, weekly or monthly totals of a habit, drawn as a
+    /// line or as bars, with the target as a dashed line.
+    pub struct TrendChart(ObjectSubclass<imp::TrendChart>)
+        @extends gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+impl TrendChart {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn habit(&self) -> Option<Habit> {
+        self.imp().habit.borrow().clone()
+    }
+
+    /// Charts the entries of `habit`, or nothing if [None].
+    pub fn set_habit(&self, habit: Option<&Habit>) {
+        self.imp().habit.replace(habit.cloned());
+        self.reload();
+    }
+
+    /// Loads the entries of the habit again, e.g. after they changed.
+    pub fn reload(&self) {
+        let entries = match self
+            .habit()
+            .map(|habit| HabitEntry::find_by_habit(habit.id()))
+        {
+            Some(Ok(entries)) => entries,
+            Some(Err(e)) => {
+                tracing::warn!("Failed to load habit entries: {}", e);
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+        self.set_entries(&entries);
+    }
+
+    /// Charts `entries` rather than loading them from the database.
+    pub fn set_entries(&self, entries: &[HabitEntry]) {
+        self.imp().totals.replace(progress::daily_totals(entries));
+        self.update_points();
+    }
+
+    pub fn range(&self) -> TrendRange {
+        self.imp().range.get()
+    }
+
+    pub fn set_range(&self, range: TrendRange) {
+        self.imp().range.set(range);
+        self.update_points();
+    }
+
+    pub fn chart_style(&self) -> ChartStyle {
+        self.imp().style.get()
+    }
+
+    pub fn set_chart_style(&self, style: ChartStyle) {
+        self.imp().style.set(style);
+        self.queue_draw();
+    }
+
+    fn update_points(&self) {
+        let imp = self.imp();
+        let points = match self.habit() {
+            Some(habit) => data_points(
+                &habit.frequency(),
+                &imp.totals.borrow(),
+                imp.range.get(),
+                Date::today(),
+            ),
+            None => Vec::new(),
+        };
+        imp.points.replace(points);
+        self.queue_draw();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{UnitSystem, Weekday};
+
+    // 2024-05-06 is a Monday
+    fn date(month: u8, day: u8) -> Date {
+        Date::new(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_buckets() {
+        let days = TrendRange::Days.buckets(date(5, 8));
+        assert_eq!(days.len(), 30);
+        assert_eq!(days[29], (date(5, 8), date(5, 8)));
+
+        let weeks = TrendRange::Weeks.buckets(date(5, 8));
+        assert_eq!(weeks.len(), 12);
+        assert_eq!(weeks[11], (date(5, 6), date(5, 12)));
+        assert_eq!(weeks[10], (date(4, 29), date(5, 5)));
+
+        let months = TrendRange::Months.buckets(date(5, 8));
+        assert_eq!(months.len(), 12);
+        assert_eq!(
+            months[0],
+            (
+                Date::new(2023, 6, 1).unwrap(),
+                Date::new(2023, 6, 30).unwrap()
+            )
+        );
+        assert_eq!(months[11], (date(5, 1), date(5, 31)));
+    }
+
+    #[test]
+    fn test_data_points() {
+        let weekdays = Frequency::new(
+            Some(DurationKind::Daily),
+            Some(UnitSystem::Kilometers),
+            Some(5),
+            Some(vec![Weekday::Monday, Weekday::Wednesday, Weekday::Friday]),
+            None,
+        );
+        let totals = BTreeMap::from([(date(5, 5), 3), (date(5, 6), 5), (date(5, 8), 7)]);
+        let points = data_points(&weekdays, &totals, TrendRange::Weeks, date(5, 8));
+        assert_eq!(
+            points[11],
+            DataPoint {
+                start: date(5, 6),
+                end: date(5, 12),
+                total: 12,
+                target: Some(15),
+            }
+        );
+        assert_eq!(points[10].total, 3);
+
+        let monthly = Frequency::new(
+            Some(DurationKind::Monthly),
+            Some(UnitSystem::Pages),
+            Some(300),
+            None,
+            None,
+        );
+        assert_eq!(bucket_target(&monthly, date(5, 1), date(5, 31)), Some(300));
+        assert_eq!(bucket_target(&monthly, date(5, 6), date(5, 12)), None);
+    }
+}
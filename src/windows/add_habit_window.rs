@@ -1,26 +1,84 @@
-use gtk::{glib, prelude::*};
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{glib, glib::clone};
+
+use crate::core::{date::Time, habit_file::HabitDefinition, i18n};
+use crate::models::{
+    Compare, DurationKind, Frequency, Habit, HabitCategory, HabitInfo, HabitName, HabitPreset,
+    ReminderRule, UnitSystem, Weekday,
+};
+
+/// The comparators offered, in the order of the comparison row.
+const COMPARATORS: [Compare; 6] = [
+    Compare::GreaterOrEqual,
+    Compare::Greater,
+    Compare::Equal,
+    Compare::NotEqual,
+    Compare::LessOrEqual,
+    Compare::Less,
+];
+
+/// The duration kinds offered, in the order of the period row.
+const DURATION_KINDS: [DurationKind; 2] = [DurationKind::Daily, DurationKind::Monthly];
 
 mod imp {
-    use gtk::{glib, prelude::*, subclass::prelude::*, CompositeTemplate};
+    use glib::subclass::Signal;
+    use once_cell::sync::Lazy;
+
+    use super::*;
 
-    #[derive(Debug, CompositeTemplate, Default)]
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
     #[template(resource = "/com/amankrx/Declutter/ui/add_habit_window.ui")]
     pub struct AddHabitWindow {
         #[template_child]
-        pub button_cancel: TemplateChild<gtk::Button>,
+        pub add_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub error_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub preset_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub title_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub description_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub icon_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub icon_image: TemplateChild<gtk::Image>,
+        #[template_child]
+        pub categories_box: TemplateChild<gtk::FlowBox>,
+        #[template_child]
+        pub duration_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub comparator_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub target_button: TemplateChild<gtk::SpinButton>,
+        #[template_child]
+        pub unit_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub weekdays_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub weekdays_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub reminders_group: TemplateChild<adw::PreferencesGroup>,
         #[template_child]
-        pub button_ok: TemplateChild<gtk::Button>,
+        pub reminder_row: TemplateChild<adw::EntryRow>,
+        pub user_id: Cell<u32>,
+        pub category_buttons: RefCell<Vec<(HabitCategory, gtk::ToggleButton)>>,
+        pub weekday_buttons: RefCell<Vec<(Weekday, gtk::ToggleButton)>>,
+        pub reminders: RefCell<Vec<(ReminderRule, adw::ActionRow)>>,
     }
 
     #[glib::object_subclass]
     impl ObjectSubclass for AddHabitWindow {
         const NAME: &'static str = "AddHabitWindow";
-        type ParentType = gtk::Dialog;
         type Type = super::AddHabitWindow;
+        type ParentType = adw::Window;
 
         fn class_init(klass: &mut Self::Class) {
-            Self::bind_template(klass);
-            Self::Type::bind_template_callbacks(klass);
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -29,43 +87,334 @@ mod imp {
     }
 
     impl ObjectImpl for AddHabitWindow {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("habit-created")
+                    .param_types([Habit::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
         fn constructed(&self) {
             self.parent_constructed();
-            let obj = self.obj();
-
-            obj.add_action_widget(&*self.button_cancel, gtk::ResponseType::Cancel);
-            obj.add_action_widget(&*self.button_ok, gtk::ResponseType::Ok);
+            self.obj().setup_rows();
         }
     }
+
     impl WidgetImpl for AddHabitWindow {}
     impl WindowImpl for AddHabitWindow {}
-    impl DialogImpl for AddHabitWindow {}
+    impl AdwWindowImpl for AddHabitWindow {}
 }
 
 glib::wrapper! {
-    /// popup dialog box that adds activity/weight/water intake data .
+    /// A dialog to create a new habit, from a preset or from scratch.
     pub struct AddHabitWindow(ObjectSubclass<imp::AddHabitWindow>)
-        @extends gtk::Widget, gtk::Window, gtk::Dialog,
+        @extends gtk::Widget, gtk::Window, adw::Window,
         @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
 }
 
-#[gtk::template_callbacks]
 impl AddHabitWindow {
-    /// Create a new [AddHabitWindow].
-    ///
-    /// # Arguments
-    /// * `parent` - The [GtkWindow](gtk::Window) who is the transient parent of this dialog.
-    pub fn new(parent: &gtk::Window) -> Self {
-        gtk::Dialog::with_buttons(
-            Some("Add Habit"),
-            Some(parent),
-            gtk::DialogFlags::MODAL,
-            &[
-                ("Cancel", gtk::ResponseType::Cancel),
-                ("Ok", gtk::ResponseType::Ok),
-            ],
+    /// Create a new [AddHabitWindow] adding a habit for the user with `user_id`.
+    pub fn new(parent: &impl IsA<gtk::Window>, user_id: u32) -> Self {
+        let window: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        window.imp().user_id.set(user_id);
+        window
+    }
+
+    /// Calls `f` with each habit created through the dialog.
+    pub fn connect_habit_created<F: Fn(&Self, &Habit) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "habit-created",
+            false,
+            glib::closure_local!(move |window: Self, habit: Habit| f(&window, &habit)),
         )
-        .downcast()
-        .expect("Failed to create AddHabitWindow")
+    }
+
+    // The ids of the presets to pick from, a custom habit first
+    fn presets() -> Vec<String> {
+        let custom = String::from(HabitName::Custom);
+        let presets = HabitPreset::all()
+            .iter()
+            .filter(|preset| preset.id != custom)
+            .map(|preset| preset.id.clone());
+        std::iter::once(custom).chain(presets).collect()
+    }
+
+    fn comparator_label(comparator: Compare) -> String {
+        match comparator {
+            Compare::Greater => i18n("More than"),
+            Compare::GreaterOrEqual => i18n("At least"),
+            Compare::Equal => i18n("Exactly"),
+            Compare::NotEqual => i18n("Anything but"),
+            Compare::LessOrEqual => i18n("At most"),
+            Compare::Less => i18n("Less than"),
+        }
+    }
+
+    fn setup_rows(&self) {
+        let imp = self.imp();
+        let presets = Self::presets()
+            .iter()
+            .map(|id| match HabitPreset::find(id) {
+                Some(preset) if id != HabitName::Custom.as_str() => preset.name.clone(),
+                _ => i18n("Custom"),
+            })
+            .collect::<Vec<_>>();
+        imp.preset_row.set_model(Some(&gtk::StringList::new(
+            &presets.iter().map(String::as_str).collect::<Vec<_>>(),
+        )));
+
+        let comparators = COMPARATORS
+            .iter()
+            .map(|comparator| Self::comparator_label(*comparator))
+            .collect::<Vec<_>>();
+        imp.comparator_row.set_model(Some(&gtk::StringList::new(
+            &comparators.iter().map(String::as_str).collect::<Vec<_>>(),
+        )));
+
+        let units = UnitSystem::iter().collect::<Vec<_>>();
+        imp.unit_row.set_model(Some(&gtk::StringList::new(
+            &units.iter().map(UnitSystem::as_str).collect::<Vec<_>>(),
+        )));
+
+        for category in HabitCategory::iter() {
+            let button = gtk::ToggleButton::with_label(&category.label());
+            button.connect_toggled(clone!(@weak self as window => move |_| {
+                window.validate();
+            }));
+            imp.categories_box.append(&button);
+            imp.category_buttons.borrow_mut().push((category, button));
+        }
+
+        for weekday in Weekday::range(Weekday::Monday, Weekday::Sunday) {
+            let button = gtk::ToggleButton::builder()
+                .label(weekday.as_short_str())
+                .build();
+            button.connect_toggled(clone!(@weak self as window => move |_| {
+                window.validate();
+            }));
+            imp.weekdays_box.append(&button);
+            imp.weekday_buttons.borrow_mut().push((weekday, button));
+        }
+
+        self.load_definition(&HabitDefinition::new(HabitName::Custom));
+    }
+
+    // Fills in the rows from `definition`, keeping the reminders
+    fn load_definition(&self, definition: &HabitDefinition) {
+        let imp = self.imp();
+        let title = if definition.name == HabitName::Custom.as_str() {
+            definition.title.clone().unwrap_or_default()
+        } else {
+            definition.display_name()
+        };
+        imp.title_row.set_text(&title);
+        imp.description_row
+            .set_text(definition.description.as_deref().unwrap_or_default());
+        imp.icon_row
+            .set_text(definition.icon.as_deref().unwrap_or_default());
+        for (category, button) in imp.category_buttons.borrow().iter() {
+            button.set_active(definition.categories.contains(category));
+        }
+
+        let frequency = &definition.frequency;
+        let position = |found: Option<usize>| found.unwrap_or_default() as u32;
+        imp.duration_row.set_selected(position(
+            DURATION_KINDS
+                .iter()
+                .position(|kind| *kind == frequency.duration_kind),
+        ));
+        imp.comparator_row.set_selected(position(
+            COMPARATORS
+                .iter()
+                .position(|comparator| *comparator == frequency.comparator),
+        ));
+        imp.unit_row.set_selected(position(
+            UnitSystem::iter().position(|unit| unit == frequency.unit),
+        ));
+        imp.target_button
+            .set_value(f64::from(frequency.target_value));
+        for (weekday, button) in imp.weekday_buttons.borrow().iter() {
+            button.set_active(
+                frequency
+                    .weekdays
+                    .as_ref()
+                    .map_or(true, |weekdays| weekdays.contains(weekday)),
+            );
+        }
+        self.validate();
+    }
+
+    fn selected_preset(&self) -> String {
+        Self::presets()
+            .get(self.imp().preset_row.selected() as usize)
+            .cloned()
+            .unwrap_or_else(|| HabitName::Custom.into())
+    }
+
+    // The habit as currently filled in
+    fn definition(&self) -> HabitDefinition {
+        let imp = self.imp();
+        let name = self.selected_preset();
+        let mut definition = HabitDefinition::new(name.as_str());
+
+        let text = |row: &adw::EntryRow| {
+            let text = row.text().trim().to_string();
+            (!text.is_empty()).then_some(text)
+        };
+        // Presets keep their translated name unless it's changed
+        definition.title = text(&imp.title_row).filter(|title| {
+            name == HabitName::Custom.as_str() || *title != HabitInfo::for_preset(&name).name
+        });
+        definition.description = text(&imp.description_row);
+        definition.icon = text(&imp.icon_row);
+        definition.categories = imp
+            .category_buttons
+            .borrow()
+            .iter()
+            .filter(|(_, button)| button.is_active())
+            .map(|(category, _)| *category)
+            .collect();
+
+        let duration_kind = DURATION_KINDS
+            .get(imp.duration_row.selected() as usize)
+            .copied()
+            .unwrap_or_default();
+        let weekdays = imp
+            .weekday_buttons
+            .borrow()
+            .iter()
+            .filter(|(_, button)| button.is_active())
+            .map(|(weekday, _)| *weekday)
+            .collect::<Vec<_>>();
+        definition.frequency = Frequency {
+            duration_kind,
+            unit: UnitSystem::iter()
+                .nth(imp.unit_row.selected() as usize)
+                .unwrap_or_default(),
+            target_value: imp.target_button.value_as_int().max(0) as u32,
+            weekdays: (duration_kind == DurationKind::Daily).then_some(weekdays),
+            comparator: COMPARATORS
+                .get(imp.comparator_row.selected() as usize)
+                .copied()
+                .unwrap_or_default(),
+        };
+        definition.reminders = imp
+            .reminders
+            .borrow()
+            .iter()
+            .map(|(rule, _)| rule.clone())
+            .collect();
+        definition
+    }
+
+    // Only lets the habit be added once it's valid, telling why it isn't
+    fn validate(&self) -> bool {
+        let imp = self.imp();
+        match self.definition().validate() {
+            Ok(()) => {
+                imp.error_label.set_visible(false);
+                imp.add_button.set_sensitive(true);
+                true
+            }
+            Err(e) => {
+                imp.error_label.set_label(&e.to_string());
+                // Don't complain about the missing name before anything is typed
+                imp.error_label
+                    .set_visible(!imp.title_row.text().trim().is_empty());
+                imp.add_button.set_sensitive(false);
+                false
+            }
+        }
+    }
+
+    fn add_reminder(&self, rule: ReminderRule) {
+        let imp = self.imp();
+        let row = adw::ActionRow::builder().title(rule.describe()).build();
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(i18n("Remove Reminder"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        remove_button.connect_clicked(clone!(@weak self as window, @weak row => move |_| {
+            let imp = window.imp();
+            imp.reminders_group.remove(&row);
+            imp.reminders.borrow_mut().retain(|(_, other)| *other != row);
+            window.validate();
+        }));
+        row.add_suffix(&remove_button);
+        imp.reminders_group.add(&row);
+        imp.reminders.borrow_mut().push((rule, row));
+        self.validate();
+    }
+
+    fn show_error(&self, heading: &str, body: &str) {
+        let dialog = adw::MessageDialog::new(Some(self), Some(heading), Some(body));
+        dialog.add_response("close", &i18n("_Close"));
+        dialog.present();
+    }
+}
+
+#[gtk::template_callbacks]
+impl AddHabitWindow {
+    #[template_callback]
+    fn on_preset_selected(&self) {
+        self.load_definition(&HabitDefinition::new(self.selected_preset()));
+    }
+
+    #[template_callback]
+    fn on_duration_selected(&self) {
+        let imp = self.imp();
+        imp.weekdays_row.set_visible(
+            DURATION_KINDS.get(imp.duration_row.selected() as usize) == Some(&DurationKind::Daily),
+        );
+        self.validate();
+    }
+
+    #[template_callback]
+    fn on_icon_changed(&self) {
+        let imp = self.imp();
+        let icon_name = imp.icon_row.text();
+        imp.icon_image
+            .set_icon_name(Some(icon_name.trim()).filter(|name| !name.is_empty()));
+    }
+
+    #[template_callback]
+    fn on_changed(&self) {
+        self.validate();
+    }
+
+    #[template_callback]
+    fn on_reminder_apply(&self) {
+        let row = self.imp().reminder_row.get();
+        match Time::parse(row.text().trim()) {
+            Ok(time) if time.hour() < 24 && time.minutes() < 60 => {
+                row.remove_css_class("error");
+                row.set_text("");
+                self.add_reminder(ReminderRule::new(time));
+            }
+            _ => row.add_css_class("error"),
+        }
+    }
+
+    #[template_callback]
+    fn on_add_clicked(&self) {
+        if !self.validate() {
+            return;
+        }
+        match self.definition().create(self.imp().user_id.get()) {
+            Ok(habit) => {
+                self.emit_by_name::<()>("habit-created", &[&habit]);
+                self.close();
+            }
+            Err(e) => self.show_error(&i18n("Couldn't Add Habit"), &e.to_string()),
+        }
     }
 }
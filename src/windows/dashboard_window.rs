@@ -0,0 +1,143 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::core::{
+    dashboard::{CategorySummary, Dashboard},
+    date::Time,
+    i18n, i18n_f,
+    insights::Insight,
+};
+
+/// Most insights to list, the strongest ones.
+const MAX_INSIGHTS: usize = 10;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/com/amankrx/Declutter/ui/dashboard_window.ui")]
+    pub struct DashboardWindow {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub categories_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub insights_group: TemplateChild<adw::PreferencesGroup>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DashboardWindow {
+        const NAME: &'static str = "DashboardWindow";
+        type Type = super::DashboardWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DashboardWindow {}
+    impl WidgetImpl for DashboardWindow {}
+    impl WindowImpl for DashboardWindow {}
+    impl AdwWindowImpl for DashboardWindow {}
+}
+
+glib::wrapper! {
+    /// How each category of habits is going, and how it went the last months.
+    pub struct DashboardWindow(ObjectSubclass<imp::DashboardWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl DashboardWindow {
+    /// Create a new [DashboardWindow] showing `dashboard` and the ranked
+    /// `insights`.
+    pub fn new(
+        parent: &impl IsA<gtk::Window>,
+        dashboard: &Dashboard,
+        insights: &[Insight],
+    ) -> Self {
+        let window: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        window.set_dashboard(dashboard);
+        window.set_insights(insights);
+        window
+    }
+
+    fn set_insights(&self, insights: &[Insight]) {
+        let group = &self.imp().insights_group;
+        for insight in insights.iter().take(MAX_INSIGHTS) {
+            let row = adw::ActionRow::builder()
+                .title(insight.text())
+                .title_lines(0)
+                .subtitle(i18n_f(
+                    "Compared over {} days",
+                    &[&insight.days.to_string()],
+                ))
+                .build();
+            group.add(&row);
+        }
+        group.set_visible(!insights.is_empty());
+    }
+
+    fn set_dashboard(&self, dashboard: &Dashboard) {
+        let imp = self.imp();
+        for summary in &dashboard.categories {
+            imp.categories_group.add(&Self::category_row(summary));
+        }
+        imp.stack
+            .set_visible_child_name(if dashboard.categories.is_empty() {
+                "empty"
+            } else {
+                "categories"
+            });
+    }
+
+    fn category_row(summary: &CategorySummary) -> adw::ExpanderRow {
+        let row = adw::ExpanderRow::builder()
+            .title(summary.category.label())
+            .subtitle(i18n_f(
+                "{} active habits",
+                &[&summary.active_habits.to_string()],
+            ))
+            .build();
+
+        let level_bar = gtk::LevelBar::builder()
+            .value(summary.score.unwrap_or(0.0))
+            .width_request(80)
+            .valign(gtk::Align::Center)
+            .build();
+        row.add_suffix(&Self::score_label(summary.score));
+        row.add_suffix(&level_bar);
+
+        // Newest month first
+        for point in summary.trend.iter().rev() {
+            let month = point
+                .month
+                .and_time_local(Time::new(0, 0, 0).unwrap())
+                .format("%B %Y")
+                .unwrap();
+            let month_row = adw::ActionRow::builder().title(month).build();
+            month_row.add_suffix(&Self::score_label(point.score));
+            row.add_row(&month_row);
+        }
+        row
+    }
+
+    fn score_label(score: Option<f64>) -> gtk::Label {
+        let text = match score {
+            Some(score) => i18n_f("{}%", &[&format!("{:.0}", score * 100.0)]),
+            None => i18n("No data yet"),
+        };
+        gtk::Label::builder()
+            .label(text)
+            .css_classes(["dim-label", "numeric"])
+            .build()
+    }
+}
@@ -1,9 +1,25 @@
 #[doc(hidden)]
 pub mod add_habit_window;
 #[doc(hidden)]
+pub mod dashboard_window;
+#[doc(hidden)]
+pub mod preferences_window;
+#[doc(hidden)]
+pub mod review_window;
+#[doc(hidden)]
+pub mod timing_window;
+#[doc(hidden)]
 pub mod window;
 
 #[doc(inline)]
 pub use add_habit_window::*;
 #[doc(inline)]
+pub use dashboard_window::*;
+#[doc(inline)]
+pub use preferences_window::*;
+#[doc(inline)]
+pub use review_window::*;
+#[doc(inline)]
+pub use timing_window::*;
+#[doc(inline)]
 pub use window::*;
@@ -0,0 +1,195 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{gio, glib, glib::clone};
+
+use crate::config::APP_ID;
+use crate::core::{date::Time, i18n};
+use crate::models::Habit;
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, gtk::CompositeTemplate)]
+    #[template(resource = "/com/amankrx/Declutter/ui/preferences_window.ui")]
+    pub struct PreferencesWindow {
+        #[template_child]
+        pub run_in_background_switch: TemplateChild<gtk::Switch>,
+        #[template_child]
+        pub bundle_reminders_switch: TemplateChild<gtk::Switch>,
+        #[template_child]
+        pub digest_row: TemplateChild<adw::ExpanderRow>,
+        #[template_child]
+        pub digest_time_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub quiet_hours_row: TemplateChild<adw::ExpanderRow>,
+        #[template_child]
+        pub quiet_hours_start_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub quiet_hours_end_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub habits_group: TemplateChild<adw::PreferencesGroup>,
+        pub settings: gio::Settings,
+    }
+
+    impl Default for PreferencesWindow {
+        fn default() -> Self {
+            Self {
+                run_in_background_switch: TemplateChild::default(),
+                bundle_reminders_switch: TemplateChild::default(),
+                digest_row: TemplateChild::default(),
+                digest_time_row: TemplateChild::default(),
+                quiet_hours_row: TemplateChild::default(),
+                quiet_hours_start_row: TemplateChild::default(),
+                quiet_hours_end_row: TemplateChild::default(),
+                habits_group: TemplateChild::default(),
+                settings: gio::Settings::new(APP_ID),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PreferencesWindow {
+        const NAME: &'static str = "PreferencesWindow";
+        type Type = super::PreferencesWindow;
+        type ParentType = adw::PreferencesWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PreferencesWindow {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let settings = &self.settings;
+
+            settings
+                .bind(
+                    "run-in-background",
+                    &*self.run_in_background_switch,
+                    "active",
+                )
+                .build();
+            settings
+                .bind("bundle-reminders", &*self.bundle_reminders_switch, "active")
+                .build();
+            settings
+                .bind(
+                    "quiet-hours-enabled",
+                    &*self.quiet_hours_row,
+                    "enable-expansion",
+                )
+                .build();
+            settings
+                .bind("digest-enabled", &*self.digest_row, "enable-expansion")
+                .build();
+            self.digest_time_row
+                .set_text(&settings.string("digest-time"));
+            self.quiet_hours_start_row
+                .set_text(&settings.string("quiet-hours-start"));
+            self.quiet_hours_end_row
+                .set_text(&settings.string("quiet-hours-end"));
+        }
+    }
+
+    impl WidgetImpl for PreferencesWindow {}
+    impl WindowImpl for PreferencesWindow {}
+    impl AdwWindowImpl for PreferencesWindow {}
+    impl PreferencesWindowImpl for PreferencesWindow {}
+}
+
+glib::wrapper! {
+    /// Preferences, for now those of notifications.
+    pub struct PreferencesWindow(ObjectSubclass<imp::PreferencesWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window, adw::PreferencesWindow,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+#[gtk::template_callbacks]
+impl PreferencesWindow {
+    /// Create a new [PreferencesWindow] listing the habits of `habits`.
+    pub fn new(parent: &impl IsA<gtk::Window>, habits: &[Habit]) -> Self {
+        let window: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        window.add_habit_rows(habits);
+        window
+    }
+
+    // One switch per habit to turn its notifications on and off
+    fn add_habit_rows(&self, habits: &[Habit]) {
+        let imp = self.imp();
+        let muted = imp.settings.get::<Vec<u32>>("muted-habits");
+        for habit in habits.iter().filter(|habit| !habit.archived()) {
+            let habit_id = habit.id();
+            let switch = gtk::Switch::builder()
+                .valign(gtk::Align::Center)
+                .active(!muted.contains(&habit_id))
+                .build();
+            switch.connect_active_notify(clone!(@weak self as window => move |switch| {
+                window.set_habit_muted(habit_id, !switch.is_active());
+            }));
+
+            let row = adw::ActionRow::builder()
+                .title(habit.display_name())
+                .activatable_widget(&switch)
+                .build();
+            row.add_suffix(&switch);
+            imp.habits_group.add(&row);
+        }
+        imp.habits_group.set_visible(!habits.is_empty());
+    }
+
+    fn set_habit_muted(&self, habit_id: u32, muted: bool) {
+        let settings = &self.imp().settings;
+        let mut muted_habits = settings.get::<Vec<u32>>("muted-habits");
+        muted_habits.retain(|id| *id != habit_id);
+        if muted {
+            muted_habits.push(habit_id);
+        }
+        if let Err(e) = settings.set("muted-habits", muted_habits.to_variant()) {
+            tracing::warn!("Failed to save muted habits: {}", e);
+        }
+    }
+
+    // Saves a time of day if it's a valid time, or marks the row as invalid
+    fn apply_time(&self, row: &adw::EntryRow, key: &str) {
+        match Time::parse(row.text().trim()) {
+            Ok(time) if time.hour() < 24 && time.minutes() < 60 => {
+                row.remove_css_class("error");
+                let text = format!("{:02}:{:02}", time.hour(), time.minutes());
+                row.set_text(&text);
+                if let Err(e) = self.imp().settings.set_string(key, &text) {
+                    tracing::warn!("Failed to save {}: {}", key, e);
+                }
+            }
+            _ => {
+                row.add_css_class("error");
+                self.add_toast(adw::Toast::new(&i18n("Enter a time like 22:00")));
+            }
+        }
+    }
+
+    #[template_callback]
+    fn on_digest_time_apply(&self) {
+        let row = self.imp().digest_time_row.get();
+        self.apply_time(&row, "digest-time");
+    }
+
+    #[template_callback]
+    fn on_quiet_hours_start_apply(&self) {
+        let row = self.imp().quiet_hours_start_row.get();
+        self.apply_time(&row, "quiet-hours-start");
+    }
+
+    #[template_callback]
+    fn on_quiet_hours_end_apply(&self) {
+        let row = self.imp().quiet_hours_end_row.get();
+        self.apply_time(&row, "quiet-hours-end");
+    }
+}
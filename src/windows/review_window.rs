@@ -0,0 +1,266 @@
+use std::cell::{Cell, RefCell};
+
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::{glib, glib::clone};
+
+use crate::core::{date::Date, i18n, i18n_f, report::ReportFormat, review::YearReview};
+
+mod imp {
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/com/amankrx/Declutter/ui/review_window.ui")]
+    pub struct ReviewWindow {
+        #[template_child]
+        pub window_title: TemplateChild<adw::WindowTitle>,
+        #[template_child]
+        pub next_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub export_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub content_box: TemplateChild<gtk::Box>,
+        pub user_id: Cell<u32>,
+        pub review: RefCell<Option<YearReview>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ReviewWindow {
+        const NAME: &'static str = "ReviewWindow";
+        type Type = super::ReviewWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ReviewWindow {}
+    impl WidgetImpl for ReviewWindow {}
+    impl WindowImpl for ReviewWindow {}
+    impl AdwWindowImpl for ReviewWindow {}
+}
+
+glib::wrapper! {
+    /// The review of a year of the user's habits, which can be exported as
+    /// HTML or Markdown.
+    pub struct ReviewWindow(ObjectSubclass<imp::ReviewWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl ReviewWindow {
+    /// Create a new [ReviewWindow] showing `year` of the user with `user_id`.
+    pub fn new(parent: &impl IsA<gtk::Window>, user_id: u32, year: u16) -> Self {
+        let window: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        window.imp().user_id.set(user_id);
+        window.load_year(year);
+        window
+    }
+
+    fn year(&self) -> Option<u16> {
+        self.imp()
+            .review
+            .borrow()
+            .as_ref()
+            .map(|review| review.year)
+    }
+
+    fn load_year(&self, year: u16) {
+        let imp = self.imp();
+        imp.window_title.set_subtitle(&year.to_string());
+        imp.next_button.set_sensitive(year < Date::today().year());
+        match YearReview::generate(imp.user_id.get(), year) {
+            Ok(review) => self.set_review(review),
+            Err(e) => {
+                tracing::warn!("Failed to build the review of {}: {}", year, e);
+                self.set_review(YearReview::new(
+                    year,
+                    &[],
+                    &[],
+                    &Default::default(),
+                    Date::today(),
+                ));
+            }
+        }
+    }
+
+    fn set_review(&self, review: YearReview) {
+        let imp = self.imp();
+        while let Some(child) = imp.content_box.first_child() {
+            imp.content_box.remove(&child);
+        }
+
+        let overview = adw::PreferencesGroup::builder()
+            .title(review.title())
+            .build();
+        for (label, value) in review.overview() {
+            overview.add(&Self::value_row(&label, &value));
+        }
+        imp.content_box.append(&overview);
+
+        if !review.longest_streaks.is_empty() {
+            let group = adw::PreferencesGroup::builder()
+                .title(i18n("Longest Streaks"))
+                .build();
+            for highlight in &review.longest_streaks {
+                group.add(&Self::value_row(&highlight.habit, &highlight.streak_text()));
+            }
+            imp.content_box.append(&group);
+        }
+
+        if let Some(improvement) = &review.most_improved {
+            let group = adw::PreferencesGroup::builder()
+                .title(i18n("Most Improved"))
+                .build();
+            group.add(
+                &adw::ActionRow::builder()
+                    .title(&improvement.habit)
+                    .subtitle(improvement.text())
+                    .subtitle_lines(0)
+                    .build(),
+            );
+            imp.content_box.append(&group);
+        }
+
+        if !review.unit_totals.is_empty() {
+            let group = adw::PreferencesGroup::builder()
+                .title(i18n("Totals"))
+                .build();
+            for total in &review.unit_totals {
+                group.add(
+                    &adw::ActionRow::builder()
+                        .title(total.total_text())
+                        .subtitle(total.habits.join(", "))
+                        .build(),
+                );
+            }
+            imp.content_box.append(&group);
+        }
+
+        if !review.categories.is_empty() {
+            let group = adw::PreferencesGroup::builder()
+                .title(i18n("Category Balance"))
+                .description(i18n("Share of the entries of the year"))
+                .build();
+            for share in &review.categories {
+                let row = adw::ActionRow::builder()
+                    .title(share.category.label())
+                    .build();
+                row.add_suffix(
+                    &gtk::LevelBar::builder()
+                        .value(share.share)
+                        .width_request(80)
+                        .valign(gtk::Align::Center)
+                        .build(),
+                );
+                row.add_suffix(
+                    &gtk::Label::builder()
+                        .label(i18n_f("{}%", &[&format!("{:.0}", share.share * 100.0)]))
+                        .css_classes(["dim-label", "numeric"])
+                        .build(),
+                );
+                group.add(&row);
+            }
+            imp.content_box.append(&group);
+        }
+
+        imp.stack
+            .set_visible_child_name(if review.is_empty() { "empty" } else { "review" });
+        imp.export_button.set_sensitive(!review.is_empty());
+        imp.review.replace(Some(review));
+    }
+
+    fn value_row(title: &str, value: &str) -> adw::ActionRow {
+        let row = adw::ActionRow::builder().title(title).build();
+        row.add_suffix(
+            &gtk::Label::builder()
+                .label(value)
+                .css_classes(["dim-label", "numeric"])
+                .build(),
+        );
+        row
+    }
+
+    fn export(&self, path: &std::path::Path) -> std::io::Result<()> {
+        match self.imp().review.borrow().as_ref() {
+            Some(review) => std::fs::write(path, review.render(ReportFormat::from_path(path))),
+            None => Ok(()),
+        }
+    }
+
+    fn show_error(&self, heading: &str, body: &str) {
+        let dialog = adw::MessageDialog::new(Some(self), Some(heading), Some(body));
+        dialog.add_response("close", &i18n("_Close"));
+        dialog.present();
+    }
+}
+
+#[gtk::template_callbacks]
+impl ReviewWindow {
+    #[template_callback]
+    fn on_previous_clicked(&self) {
+        if let Some(year) = self.year() {
+            self.load_year(year - 1);
+        }
+    }
+
+    #[template_callback]
+    fn on_next_clicked(&self) {
+        if let Some(year) = self.year() {
+            self.load_year(year + 1);
+        }
+    }
+
+    #[template_callback]
+    fn on_export_clicked(&self) {
+        let Some(year) = self.year() else {
+            return;
+        };
+        let dialog = gtk::FileChooserNative::new(
+            Some(&i18n("Export Review")),
+            Some(self),
+            gtk::FileChooserAction::Save,
+            Some(&i18n("_Export")),
+            Some(&i18n("_Cancel")),
+        );
+        dialog.set_modal(true);
+        dialog.set_current_name(&format!(
+            "declutter-review-{}.{}",
+            year,
+            ReportFormat::Html.extension()
+        ));
+        for (name, pattern) in [
+            (i18n("HTML Document"), "*.html"),
+            (i18n("Markdown Document"), "*.md"),
+        ] {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(&name));
+            filter.add_pattern(pattern);
+            dialog.add_filter(&filter);
+        }
+
+        dialog.connect_response(
+            clone!(@weak self as window, @strong dialog => move |_, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = window.export(&path) {
+                            window.show_error(&i18n("Couldn't Export Review"), &e.to_string());
+                        }
+                    }
+                }
+                dialog.destroy();
+            }),
+        );
+        dialog.show();
+    }
+}
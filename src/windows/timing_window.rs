@@ -0,0 +1,184 @@
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+use gtk::glib;
+
+use crate::core::{
+    i18n, i18n_f,
+    timing::{self, TimeOfDay},
+    Application,
+};
+use crate::models::{Habit, HabitEntry, ReminderRule, Weekday};
+use crate::widgets::{Histogram, HistogramBar};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[derive(Debug, Default, gtk::CompositeTemplate)]
+    #[template(resource = "/com/amankrx/Declutter/ui/timing_window.ui")]
+    pub struct TimingWindow {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub hours_histogram: TemplateChild<Histogram>,
+        #[template_child]
+        pub weekdays_histogram: TemplateChild<Histogram>,
+        #[template_child]
+        pub apply_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub current_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub suggested_row: TemplateChild<adw::ActionRow>,
+        pub habit: RefCell<Option<Habit>>,
+        pub suggestions: RefCell<Vec<ReminderRule>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TimingWindow {
+        const NAME: &'static str = "TimingWindow";
+        type Type = super::TimingWindow;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Histogram::ensure_type();
+            klass.bind_template();
+            klass.bind_template_instance_callbacks();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for TimingWindow {}
+    impl WidgetImpl for TimingWindow {}
+    impl WindowImpl for TimingWindow {}
+    impl AdwWindowImpl for TimingWindow {}
+}
+
+glib::wrapper! {
+    /// When a habit is usually logged, by hour and by weekday, with reminder
+    /// times suggested from that.
+    pub struct TimingWindow(ObjectSubclass<imp::TimingWindow>)
+        @extends gtk::Widget, gtk::Window, adw::Window,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+impl TimingWindow {
+    /// Create a new [TimingWindow] for `habit` with its `entries`.
+    pub fn new(parent: &impl IsA<gtk::Window>, habit: &Habit, entries: &[HabitEntry]) -> Self {
+        let window: Self = glib::Object::builder()
+            .property("transient-for", parent)
+            .build();
+        window.set_habit(habit, entries);
+        window
+    }
+
+    fn set_habit(&self, habit: &Habit, entries: &[HabitEntry]) {
+        let imp = self.imp();
+        imp.habit.replace(Some(habit.clone()));
+
+        let time_of_day = TimeOfDay::new(entries);
+        imp.hours_histogram.set_bars(
+            time_of_day
+                .by_hour
+                .iter()
+                .enumerate()
+                .map(|(hour, count)| HistogramBar {
+                    // Label every six hours, the bars are too narrow for more
+                    label: if hour % 6 == 0 {
+                        format!("{hour:02}")
+                    } else {
+                        String::new()
+                    },
+                    tooltip: i18n_f(
+                        "{} entries from {}:00",
+                        &[&count.to_string(), &format!("{hour:02}")],
+                    ),
+                    count: *count,
+                })
+                .collect(),
+        );
+        imp.weekdays_histogram.set_bars(
+            Weekday::range(Weekday::Monday, Weekday::Sunday)
+                .into_iter()
+                .zip(time_of_day.by_weekday)
+                .map(|(weekday, count)| HistogramBar {
+                    label: weekday.as_short_str().to_string(),
+                    tooltip: i18n_f(
+                        "{} entries on {}",
+                        &[&count.to_string(), weekday.as_short_str()],
+                    ),
+                    count,
+                })
+                .collect(),
+        );
+        imp.stack
+            .set_visible_child_name(if time_of_day.total() == 0 {
+                "empty"
+            } else {
+                "timing"
+            });
+
+        imp.suggestions.replace(timing::suggest_reminders(entries));
+        self.update_reminders();
+    }
+
+    fn update_reminders(&self) {
+        let imp = self.imp();
+        let Some(habit) = imp.habit.borrow().clone() else {
+            return;
+        };
+        let current = habit.reminder_rules();
+        let suggestions = imp.suggestions.borrow();
+        imp.current_row.set_subtitle(&if current.is_empty() {
+            i18n("No reminders")
+        } else {
+            Self::describe(&current)
+        });
+        imp.suggested_row.set_subtitle(&if suggestions.is_empty() {
+            i18n_f(
+                "Log the habit at least {} times for a suggestion",
+                &[&timing::MIN_ENTRIES.to_string()],
+            )
+        } else {
+            Self::describe(&suggestions)
+        });
+        imp.apply_button
+            .set_sensitive(!suggestions.is_empty() && *suggestions != current);
+    }
+
+    fn describe(rules: &[ReminderRule]) -> String {
+        rules
+            .iter()
+            .map(ReminderRule::describe)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn show_error(&self, heading: &str, body: &str) {
+        let dialog = adw::MessageDialog::new(Some(self), Some(heading), Some(body));
+        dialog.add_response("close", &i18n("_Close"));
+        dialog.present();
+    }
+}
+
+#[gtk::template_callbacks]
+impl TimingWindow {
+    #[template_callback]
+    fn on_apply_clicked(&self) {
+        let imp = self.imp();
+        let Some(habit) = imp.habit.borrow().clone() else {
+            return;
+        };
+        let suggestions = imp.suggestions.borrow().clone();
+        match habit.update_reminder_rules(suggestions) {
+            Ok(()) => {
+                Application::notify_habits_changed();
+                self.update_reminders();
+            }
+            Err(e) => self.show_error(&i18n("Couldn't Update Reminders"), &e.to_string()),
+        }
+    }
+}
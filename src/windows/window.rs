@@ -1,14 +1,34 @@
+use std::collections::HashMap;
+
+use adw::prelude::*;
 use adw::subclass::prelude::*;
-use gtk::prelude::*;
-use gtk::{gio, glib};
+use anyhow::anyhow;
+use gtk::{gdk, gio, glib, glib::clone};
 
 use crate::config::{APP_ID, PROFILE};
-use crate::core::Application;
+use crate::core::{
+    date::{Date, GDateTimeExt, Time},
+    forecast::MonthlyForecast,
+    habit_file::{HabitDefinition, HABIT_FILE_EXTENSION, HABIT_FILE_MIME_TYPE},
+    i18n, i18n_f, progress, spawn, strength, Application,
+};
+use crate::models::{
+    DurationKind, Frequency, Habit, HabitEntry, HabitFilter, HabitModel, HabitSort, HabitSorter,
+    UpdateHabitEntry,
+};
+use crate::widgets::{CalendarHeatmap, ChartStyle, TrendChart, TrendRange};
+use crate::windows::{AddHabitWindow, TimingWindow};
 
-mod imp {
-    use gtk::ListBoxRow;
+/// What the row of a habit shows, computed for all habits at once when
+/// they're loaded.
+#[derive(Debug, Copy, Clone)]
+pub struct HabitSummary {
+    pub status: progress::Status,
+    pub strength: Option<f64>,
+}
 
-    use crate::models::{Frequency, Habit, HabitModel};
+mod imp {
+    use std::cell::{Cell, RefCell};
 
     use super::*;
 
@@ -18,16 +38,68 @@ mod imp {
         #[template_child]
         pub headerbar: TemplateChild<adw::HeaderBar>,
         #[template_child]
+        pub search_bar: TemplateChild<gtk::SearchBar>,
+        #[template_child]
+        pub search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
         pub list_box: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub heatmap: TemplateChild<CalendarHeatmap>,
+        #[template_child]
+        pub forecast_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub trend_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub trend_chart: TemplateChild<TrendChart>,
+        #[template_child]
+        pub trend_weeks_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub trend_months_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub trend_bars_button: TemplateChild<gtk::ToggleButton>,
         pub settings: gio::Settings,
+        pub habits: HabitModel,
+        pub filter: HabitFilter,
+        pub sorter: HabitSorter,
+        /// The habits as listed: filtered, then sorted
+        pub list_model: gtk::SortListModel,
+        /// The summaries of the habits by id
+        pub summaries: RefCell<HashMap<u32, HabitSummary>>,
+        /// Whether reloading the habits is already scheduled
+        pub reload_pending: Cell<bool>,
     }
 
     impl Default for Window {
         fn default() -> Self {
+            let habits = HabitModel::new();
+            let filter = HabitFilter::new();
+            let sorter = HabitSorter::new();
+            let list_model = gtk::SortListModel::new(
+                Some(gtk::FilterListModel::new(
+                    Some(habits.clone()),
+                    Some(filter.clone()),
+                )),
+                Some(sorter.clone()),
+            );
             Self {
                 headerbar: TemplateChild::default(),
+                search_bar: TemplateChild::default(),
+                search_entry: TemplateChild::default(),
                 list_box: TemplateChild::default(),
+                heatmap: TemplateChild::default(),
+                forecast_label: TemplateChild::default(),
+                trend_box: TemplateChild::default(),
+                trend_chart: TemplateChild::default(),
+                trend_weeks_button: TemplateChild::default(),
+                trend_months_button: TemplateChild::default(),
+                trend_bars_button: TemplateChild::default(),
                 settings: gio::Settings::new(APP_ID),
+                habits,
+                filter,
+                sorter,
+                list_model,
+                summaries: RefCell::default(),
+                reload_pending: Cell::new(false),
             }
         }
     }
@@ -39,7 +111,33 @@ mod imp {
         type ParentType = adw::ApplicationWindow;
 
         fn class_init(klass: &mut Self::Class) {
+            CalendarHeatmap::ensure_type();
+            TrendChart::ensure_type();
             klass.bind_template();
+            klass.bind_template_instance_callbacks();
+
+            klass.install_action("win.add-habit", None, |window, _, _| {
+                window.show_add_habit();
+            });
+            klass.install_action("win.import-habit", None, |window, _, _| {
+                window.show_import_habit_dialog();
+            });
+            klass.install_action("win.export-habit", None, |window, _, _| {
+                window.show_export_habit_dialog();
+            });
+            klass.install_action("win.habit-timing", None, |window, _, _| {
+                window.show_habit_timing();
+            });
+            klass.install_action("win.search", None, |window, _, _| {
+                let search_bar = &window.imp().search_bar;
+                search_bar.set_search_mode(!search_bar.is_search_mode());
+            });
+            klass.install_action("win.move-habit-up", None, |window, _, _| {
+                window.move_selected_habit(-1);
+            });
+            klass.install_action("win.move-habit-down", None, |window, _, _| {
+                window.move_selected_habit(1);
+            });
         }
 
         // You must call `Widget`'s `init_template()` within `instance_init()`.
@@ -61,35 +159,26 @@ mod imp {
             // Load latest window state
             obj.load_window_size();
 
-            let store = HabitModel::new();
-            store.append(
-                &Habit::new(
-                    0,
-                    0,
-                    crate::models::HabitName::Cooking,
-                    None,
-                    None,
-                    None,
-                    Frequency::new(None, None, None, None, None),
-                    glib::DateTime::now_local().unwrap(),
-                    None,
-                    None,
-                    None,
-                    false,
-                    None,
-                    None,
-                )
-                .unwrap(),
-            );
-            self.list_box.bind_model(Some(&store), move |item| {
-                let row = ListBoxRow::builder();
+            obj.setup_drop_target();
+            obj.setup_heatmap();
 
-                let row = row.child(&gtk::Label::new(Some(
-                    item.downcast_ref::<Habit>().unwrap().name().as_str(),
-                )));
-
-                return row.build().upcast::<gtk::Widget>();
-            });
+            self.search_bar.set_key_capture_widget(Some(&*obj));
+            self.list_box.bind_model(
+                Some(&self.list_model),
+                clone!(@weak obj => @default-panic, move |item| {
+                    let habit = item.downcast_ref::<Habit>().unwrap();
+                    obj.habit_row(habit).upcast()
+                }),
+            );
+            self.list_box.set_placeholder(Some(
+                &gtk::Label::builder()
+                    .label(i18n("No habits yet"))
+                    .margin_top(24)
+                    .margin_bottom(24)
+                    .css_classes(["dim-label"])
+                    .build(),
+            ));
+            obj.setup_habits();
         }
     }
 
@@ -148,4 +237,654 @@ impl Window {
             self.maximize();
         }
     }
+
+    // Loads the habits of the active user and keeps them up to date, sorted
+    // and filtered as chosen in the menu
+    fn setup_habits(&self) {
+        let imp = self.imp();
+        self.add_action(&imp.settings.create_action("habit-sort"));
+        self.add_action(&imp.settings.create_action("show-archived"));
+        for key in ["habit-sort", "show-archived"] {
+            imp.settings.connect_changed(
+                Some(key),
+                clone!(@weak self as window => move |_, _| {
+                    window.apply_list_settings();
+                }),
+            );
+        }
+        imp.sorter
+            .set_manual_order(imp.settings.get::<Vec<u32>>("habit-order"));
+        self.apply_list_settings();
+
+        if let Some(app) = self.application() {
+            for signal in ["habits-changed", "entries-changed"] {
+                app.connect_local(
+                    signal,
+                    false,
+                    clone!(@weak self as window => @default-return None, move |_| {
+                        window.schedule_reload();
+                        None
+                    }),
+                );
+            }
+            // Habits due today and their progress change with the date
+            app.connect_local(
+                "day-changed",
+                false,
+                clone!(@weak self as window => @default-return None, move |_| {
+                    window.imp().filter.changed(gtk::FilterChange::Different);
+                    window.schedule_reload();
+                    None
+                }),
+            );
+        }
+        imp.settings.connect_changed(
+            Some("active-user-id"),
+            clone!(@weak self as window => move |_, _| {
+                window.load_habits();
+            }),
+        );
+        self.load_habits();
+    }
+
+    // Reloads once the current changes are done, as an import can change
+    // many habits and entries in a row
+    fn schedule_reload(&self) {
+        let imp = self.imp();
+        if imp.reload_pending.replace(true) {
+            return;
+        }
+        glib::idle_add_local_once(clone!(@weak self as window => move || {
+            window.imp().reload_pending.set(false);
+            window.load_habits();
+        }));
+    }
+
+    fn apply_list_settings(&self) {
+        let imp = self.imp();
+        let sort = imp.settings.string("habit-sort");
+        imp.sorter
+            .set_sort(sort.parse::<HabitSort>().unwrap_or_default());
+        imp.filter
+            .set_show_archived(imp.settings.boolean("show-archived"));
+    }
+
+    /// Lists the habits of the active user, keeping the selected one
+    /// selected.
+    pub fn load_habits(&self) {
+        let imp = self.imp();
+        let habits = match Habit::find_by_user(self.active_user_id()) {
+            Ok(habits) => habits,
+            Err(e) => {
+                tracing::warn!("Failed to load habits: {}", e);
+                Vec::new()
+            }
+        };
+
+        let entries = match HabitEntry::find_by_user(self.active_user_id()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                tracing::warn!("Failed to load habit entries: {}", e);
+                Vec::new()
+            }
+        };
+        let today = Date::today();
+        // Strengths are cached in the database up to the last closed period
+        let strengths = match database::connection().get() {
+            Ok(mut conn) => habits
+                .iter()
+                .filter_map(|habit| match strength::load(&mut conn, habit, today) {
+                    Ok(strength) => Some((habit.id(), strength)),
+                    Err(e) => {
+                        tracing::warn!("Failed to compute the strength of a habit: {}", e);
+                        None
+                    }
+                })
+                .collect::<HashMap<_, _>>(),
+            Err(e) => {
+                tracing::warn!("Failed to compute the strength of habits: {}", e);
+                HashMap::new()
+            }
+        };
+        let summaries = habits
+            .iter()
+            .map(|habit| {
+                let frequency = habit.frequency();
+                let created = habit.created_at().to_local().unwrap().date();
+                let habit_entries = entries
+                    .iter()
+                    .filter(|entry| entry.habit_id() == habit.id())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let totals = progress::daily_totals(&habit_entries);
+                let summary = HabitSummary {
+                    status: progress::status(&frequency, &totals, created, today),
+                    strength: strengths.get(&habit.id()).copied(),
+                };
+                (habit.id(), summary)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let selected_id = self.selected_habit().map(|habit| habit.id());
+        imp.sorter.set_streaks(
+            summaries
+                .iter()
+                .map(|(id, summary)| (*id, summary.status.streak))
+                .collect(),
+        );
+        imp.summaries.replace(summaries);
+        imp.habits.set_habits(habits);
+        if !selected_id.map_or(false, |id| self.select_habit(id)) {
+            self.show_habit_history(None);
+        }
+    }
+
+    // Selects the habit with `id` if it's listed
+    fn select_habit(&self, id: u32) -> bool {
+        let imp = self.imp();
+        let position = imp
+            .list_model
+            .iter::<Habit>()
+            .position(|habit| habit.map_or(false, |habit| habit.id() == id));
+        match position.and_then(|position| imp.list_box.row_at_index(position as i32)) {
+            Some(row) => {
+                imp.list_box.select_row(Some(&row));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Moves the selected habit `offset` places up or down in the list,
+    // switching to the manual order
+    fn move_selected_habit(&self, offset: i32) {
+        let imp = self.imp();
+        let Some(habit) = self.selected_habit() else {
+            return;
+        };
+        let mut order = imp
+            .list_model
+            .iter::<Habit>()
+            .filter_map(Result::ok)
+            .map(|habit| habit.id())
+            .collect::<Vec<_>>();
+        let listed = order.len();
+        // Habits that are filtered out keep their place after the listed ones
+        for id in imp.sorter.manual_order() {
+            if !order.contains(&id) {
+                order.push(id);
+            }
+        }
+        let Some(from) = order.iter().position(|id| *id == habit.id()) else {
+            return;
+        };
+        let to = from as i32 + offset;
+        if to < 0 || to as usize >= listed {
+            return;
+        }
+        order.swap(from, to as usize);
+
+        if let Err(e) = imp.settings.set("habit-order", order.to_variant()) {
+            tracing::warn!("Failed to save the habit order: {}", e);
+        }
+        imp.sorter.set_manual_order(order);
+        if let Err(e) = imp
+            .settings
+            .set_string("habit-sort", HabitSort::Manual.as_str())
+        {
+            tracing::warn!("Failed to save the habit sort order: {}", e);
+        }
+        self.apply_list_settings();
+        self.select_habit(habit.id());
+    }
+
+    fn habit_row(&self, habit: &Habit) -> adw::ActionRow {
+        let frequency = habit.frequency();
+        let summary = self.imp().summaries.borrow().get(&habit.id()).copied();
+        let status = summary.map(|summary| summary.status);
+
+        let row = adw::ActionRow::builder()
+            .title(habit.display_name())
+            .use_markup(false)
+            .subtitle(status.map_or_else(String::new, |status| {
+                Self::progress_text(&frequency, &status)
+            }))
+            .build();
+        let icon_name = habit
+            .icon()
+            .or_else(|| habit.info().icon)
+            .unwrap_or_else(|| "object-select-symbolic".to_string());
+        row.add_prefix(&gtk::Image::from_icon_name(&icon_name));
+
+        if let Some(status) = status {
+            let done = status.current.map_or(false, |period| {
+                period.completed && !frequency.is_abstraction()
+            });
+            if done {
+                row.add_suffix(
+                    &gtk::Image::builder()
+                        .icon_name("object-select-symbolic")
+                        .tooltip_text(i18n("Done"))
+                        .css_classes(["success"])
+                        .build(),
+                );
+            }
+            if status.streak > 0 {
+                let streak = status.streak.to_string();
+                row.add_suffix(
+                    &gtk::Label::builder()
+                        .label(match frequency.duration_kind {
+                            DurationKind::Daily => i18n_f("{} days", &[&streak]),
+                            DurationKind::Monthly => i18n_f("{} months", &[&streak]),
+                        })
+                        .tooltip_text(i18n("Current streak"))
+                        .css_classes(["dim-label", "numeric"])
+                        .build(),
+                );
+            }
+        }
+        if let Some(strength) = summary.and_then(|summary| summary.strength) {
+            row.add_suffix(&Self::strength_label(strength));
+        }
+        row
+    }
+
+    // Today's progress towards the target, e.g. "10 / 30 minutes today"
+    fn progress_text(frequency: &Frequency, status: &progress::Status) -> String {
+        let Some(current) = status.current else {
+            return i18n("Not due today");
+        };
+        if frequency.is_abstraction() {
+            return if current.completed {
+                i18n("On track")
+            } else {
+                i18n("Slipped")
+            };
+        }
+        let progress = format!(
+            "{} / {}",
+            current.total,
+            frequency
+                .unit
+                .format_value(i64::from(frequency.target_value))
+        );
+        match frequency.duration_kind {
+            DurationKind::Daily => i18n_f("{} today", &[&progress]),
+            DurationKind::Monthly => i18n_f("{} this month", &[&progress]),
+        }
+    }
+
+    // The strength of a habit as a percentage, next to its name in the list
+    fn strength_label(strength: f64) -> gtk::Label {
+        gtk::Label::builder()
+            .label(i18n_f("{}%", &[&format!("{:.0}", strength * 100.0)]))
+            .tooltip_text(i18n("Habit strength"))
+            .css_classes(["dim-label", "numeric"])
+            .build()
+    }
+
+    fn active_user_id(&self) -> u32 {
+        self.imp().settings.int("active-user-id").max(0) as u32
+    }
+
+    fn selected_habit(&self) -> Option<Habit> {
+        let row = self.imp().list_box.selected_row()?;
+        self.imp()
+            .list_model
+            .item(row.index() as u32)
+            .and_downcast::<Habit>()
+    }
+
+    // Shows the history of the selected habit, letting its days be edited
+    fn setup_heatmap(&self) {
+        let imp = self.imp();
+        imp.list_box
+            .connect_row_selected(clone!(@weak self as window => move |_, _| {
+                window.show_habit_history(window.selected_habit().as_ref());
+            }));
+        imp.heatmap
+            .connect_day_activated(clone!(@weak self as window => move |heatmap, date| {
+                if let Some(habit) = heatmap.habit() {
+                    window.show_day_entries(&habit, date);
+                }
+            }));
+    }
+
+    fn show_habit_history(&self, habit: Option<&Habit>) {
+        let imp = self.imp();
+        imp.heatmap.set_habit(habit);
+        // Charts only tell something about habits measured in amounts
+        let quantity = habit.filter(|habit| habit.frequency().unit.is_quantity());
+        imp.trend_chart.set_habit(quantity);
+        imp.trend_box.set_visible(quantity.is_some());
+        self.update_forecast();
+    }
+
+    // Shows where a monthly habit is heading this month, if it has a target to reach
+    fn update_forecast(&self) {
+        let label = &self.imp().forecast_label;
+        let forecast = self.imp().heatmap.habit().and_then(|habit| {
+            let entries = HabitEntry::find_by_habit(habit.id())
+                .map_err(|e| tracing::warn!("Failed to load habit entries: {}", e))
+                .ok()?;
+            MonthlyForecast::new(
+                &habit.frequency(),
+                &progress::daily_totals(&entries),
+                Date::today(),
+            )
+        });
+        if let Some(forecast) = &forecast {
+            label.set_label(&forecast.text());
+        }
+        label.set_visible(forecast.is_some());
+    }
+
+    /// Lists the entries of `habit` made on `date` to change or delete them,
+    /// or to add another one.
+    pub fn show_day_entries(&self, habit: &Habit, date: Date) {
+        let entries = match HabitEntry::find_by_habit(habit.id()) {
+            Ok(entries) => entries
+                .into_iter()
+                .filter(|entry| entry.entry_time().to_local().unwrap().date() == date)
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                self.show_error(&i18n("Couldn't Load Entries"), &e.to_string());
+                return;
+            }
+        };
+        // New entries are made at the current time of day
+        let now = glib::DateTime::now_local().unwrap();
+        let time = Time::new(now.hour() as u8, now.minute() as u8, 0).unwrap();
+        let day = date.and_time_local(time).format_local();
+
+        let dialog = adw::MessageDialog::new(Some(self), Some(&habit.display_name()), Some(&day));
+        let list_box = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        list_box.set_placeholder(Some(
+            &gtk::Label::builder()
+                .label(i18n("Nothing logged"))
+                .margin_top(12)
+                .margin_bottom(12)
+                .css_classes(["dim-label"])
+                .build(),
+        ));
+
+        let mut spin_buttons = Vec::new();
+        for entry in &entries {
+            let spin_button = gtk::SpinButton::with_range(0.0, f64::from(i32::MAX), 1.0);
+            spin_button.set_value(f64::from(entry.value()));
+            spin_button.set_valign(gtk::Align::Center);
+            let delete_button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(i18n("Delete"))
+                .valign(gtk::Align::Center)
+                .css_classes(["flat"])
+                .build();
+
+            let row = adw::ActionRow::builder()
+                .title(entry.entry_time().to_local().unwrap().format("%X").unwrap())
+                .subtitle(entry.note().unwrap_or_default())
+                .build();
+            row.add_suffix(&spin_button);
+            row.add_suffix(&delete_button);
+            list_box.append(&row);
+
+            delete_button.connect_clicked(
+                clone!(@weak self as window, @weak list_box, @weak row, @strong entry => move |_| {
+                    match entry.delete() {
+                        Ok(()) => {
+                            list_box.remove(&row);
+                            Application::notify_entries_changed();
+                        }
+                        Err(e) => window.show_error(&i18n("Couldn't Delete Entry"), &e.to_string()),
+                    }
+                }),
+            );
+            spin_buttons.push((entry.clone(), spin_button));
+        }
+        dialog.set_extra_child(Some(&list_box));
+
+        dialog.add_responses(&[
+            ("cancel", &i18n("_Cancel")),
+            ("add", &i18n("_Add Entry")),
+            ("save", &i18n("_Save")),
+        ]);
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("save"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("save"),
+            clone!(@weak self as window => move |_, _| {
+                let mut changed = false;
+                for (entry, spin_button) in &spin_buttons {
+                    let value = spin_button.value_as_int();
+                    if value == entry.value() {
+                        continue;
+                    }
+                    match entry.update(&UpdateHabitEntry::new().value(value)) {
+                        Ok(()) => changed = true,
+                        Err(e) => window.show_error(&i18n("Couldn't Save Entry"), &e.to_string()),
+                    }
+                }
+                if changed {
+                    Application::notify_entries_changed();
+                }
+            }),
+        );
+        dialog.connect_response(
+            Some("add"),
+            clone!(@weak self as window, @strong habit => move |_, _| {
+                let result = HabitEntry::create_at(
+                    habit.user_id(),
+                    habit.id(),
+                    &date.and_time_local(time),
+                    None,
+                    habit.frequency().default_entry_value(),
+                );
+                match result {
+                    Ok(_) => {
+                        Application::notify_entries_changed();
+                        window.show_day_entries(&habit, date);
+                    }
+                    Err(e) => window.show_error(&i18n("Couldn't Log Habit"), &e.to_string()),
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    fn habit_file_filter() -> gtk::FileFilter {
+        let filter = gtk::FileFilter::new();
+        filter.set_name(Some(&i18n("Declutter Habits")));
+        filter.add_mime_type(HABIT_FILE_MIME_TYPE);
+        filter.add_suffix(HABIT_FILE_EXTENSION);
+        filter
+    }
+
+    // Lets habit files be dropped onto the window to import them
+    fn setup_drop_target(&self) {
+        let drop_target = gtk::DropTarget::new(gio::File::static_type(), gdk::DragAction::COPY);
+        drop_target.connect_drop(
+            clone!(@weak self as window => @default-return false, move |_, value, _, _| {
+                match value.get::<gio::File>() {
+                    Ok(file) => {
+                        window.import_habit_file(&file);
+                        true
+                    }
+                    Err(_) => false,
+                }
+            }),
+        );
+        self.add_controller(drop_target);
+    }
+
+    fn show_import_habit_dialog(&self) {
+        let dialog = gtk::FileChooserNative::new(
+            Some(&i18n("Import Habit")),
+            Some(self),
+            gtk::FileChooserAction::Open,
+            Some(&i18n("_Open")),
+            Some(&i18n("_Cancel")),
+        );
+        dialog.set_modal(true);
+        dialog.add_filter(&Self::habit_file_filter());
+
+        dialog.connect_response(
+            clone!(@weak self as window, @strong dialog => move |_, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(file) = dialog.file() {
+                        window.import_habit_file(&file);
+                    }
+                }
+                dialog.destroy();
+            }),
+        );
+        dialog.show();
+    }
+
+    /// Reads the habit definition in `file` and asks whether to add it to the
+    /// habits of the active user.
+    pub fn import_habit_file(&self, file: &gio::File) {
+        spawn(clone!(@weak self as window, @strong file => async move {
+            let definition = match file.load_contents_future().await {
+                Ok((bytes, _)) => HabitDefinition::parse(&bytes),
+                Err(e) => Err(anyhow!("{e}")),
+            };
+            match definition {
+                Ok(definition) => window.show_import_preview(definition),
+                Err(e) => window.show_error(&i18n("Couldn't Import Habit"), &e.to_string()),
+            }
+        }));
+    }
+
+    fn show_import_preview(&self, definition: HabitDefinition) {
+        let dialog = adw::MessageDialog::new(
+            Some(self),
+            Some(&i18n_f("Import “{}”?", &[&definition.display_name()])),
+            Some(&definition.summary()),
+        );
+        dialog.add_responses(&[("cancel", &i18n("_Cancel")), ("import", &i18n("_Import"))]);
+        dialog.set_response_appearance("import", adw::ResponseAppearance::Suggested);
+        dialog.set_default_response(Some("import"));
+        dialog.set_close_response("cancel");
+
+        dialog.connect_response(
+            Some("import"),
+            clone!(@weak self as window => move |_, _| {
+                match definition.create(window.active_user_id()) {
+                    Ok(habit) => {
+                        window.load_habits();
+                        window.select_habit(habit.id());
+                    }
+                    Err(e) => window.show_error(&i18n("Couldn't Import Habit"), &e.to_string()),
+                }
+            }),
+        );
+        dialog.present();
+    }
+
+    fn show_export_habit_dialog(&self) {
+        let Some(habit) = self.selected_habit() else {
+            self.show_error(
+                &i18n("No Habit Selected"),
+                &i18n("Select the habit to export first."),
+            );
+            return;
+        };
+        let definition = HabitDefinition::from_habit(&habit);
+
+        let dialog = gtk::FileChooserNative::new(
+            Some(&i18n("Export Habit")),
+            Some(self),
+            gtk::FileChooserAction::Save,
+            Some(&i18n("_Export")),
+            Some(&i18n("_Cancel")),
+        );
+        dialog.set_modal(true);
+        dialog.set_current_name(&format!(
+            "{}.{}",
+            definition.display_name(),
+            HABIT_FILE_EXTENSION
+        ));
+        dialog.add_filter(&Self::habit_file_filter());
+
+        dialog.connect_response(
+            clone!(@weak self as window, @strong dialog => move |_, response| {
+                if response == gtk::ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = std::fs::write(&path, definition.to_json()) {
+                            window.show_error(&i18n("Couldn't Export Habit"), &e.to_string());
+                        }
+                    }
+                }
+                dialog.destroy();
+            }),
+        );
+        dialog.show();
+    }
+
+    fn show_add_habit(&self) {
+        let dialog = AddHabitWindow::new(self, self.active_user_id());
+        dialog.connect_habit_created(clone!(@weak self as window => move |_, habit| {
+            window.load_habits();
+            window.select_habit(habit.id());
+        }));
+        dialog.present();
+    }
+
+    fn show_habit_timing(&self) {
+        let Some(habit) = self.selected_habit() else {
+            self.show_error(
+                &i18n("No Habit Selected"),
+                &i18n("Select a habit to see when you usually do it."),
+            );
+            return;
+        };
+        match HabitEntry::find_by_habit(habit.id()) {
+            Ok(entries) => TimingWindow::new(self, &habit, &entries).present(),
+            Err(e) => self.show_error(&i18n("Couldn't Load Entries"), &e.to_string()),
+        }
+    }
+
+    pub fn show_error(&self, heading: &str, body: &str) {
+        let dialog = adw::MessageDialog::new(Some(self), Some(heading), Some(body));
+        dialog.add_response("close", &i18n("_Close"));
+        dialog.present();
+    }
+}
+
+#[gtk::template_callbacks]
+impl Window {
+    #[template_callback]
+    fn on_search_changed(&self) {
+        let imp = self.imp();
+        imp.filter.set_search(&imp.search_entry.text());
+    }
+
+    #[template_callback]
+    fn on_trend_range_toggled(&self) {
+        let imp = self.imp();
+        let range = if imp.trend_weeks_button.is_active() {
+            TrendRange::Weeks
+        } else if imp.trend_months_button.is_active() {
+            TrendRange::Months
+        } else {
+            TrendRange::Days
+        };
+        imp.trend_chart.set_range(range);
+    }
+
+    #[template_callback]
+    fn on_trend_style_toggled(&self) {
+        let imp = self.imp();
+        let style = if imp.trend_bars_button.is_active() {
+            ChartStyle::Bars
+        } else {
+            ChartStyle::Line
+        };
+        imp.trend_chart.set_chart_style(style);
+    }
 }